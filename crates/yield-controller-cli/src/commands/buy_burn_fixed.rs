@@ -0,0 +1,337 @@
+use crate::commands::{parse_sol, BuyBurnTarget, RetirementBackend, SwapPoolArgs};
+use crate::context::Context;
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use yield_controller_client::buy_burn_fixed::{
+    self, AllocateYieldInput, GenericStateInput, MigrateStateInput, RetirementReceipt, State,
+    SwapAndBurnInput,
+};
+use yield_controller_client::pda;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show a buy-burn-fixed state
+    Show(BuyBurnTarget),
+    /// Register a buy-burn-fixed state
+    Register {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[command(flatten)]
+        state: StateArgs,
+    },
+    /// Update a buy-burn-fixed state, keeping any settings not given
+    Update {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[command(flatten)]
+        state: StateArgs,
+    },
+    /// Move the state of a mint registered before states were indexed to an indexed state
+    MigrateState {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[arg(long)]
+        price_staleness_threshold: u64,
+        #[arg(long, default_value_t = 0)]
+        max_price_change_bps: u16,
+        #[arg(long, default_value_t = 100)]
+        max_slippage_bps: u16,
+    },
+    /// Set the price the state buys tokens at
+    UpdatePrice {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        /// The price, in lamports per token base unit
+        price: u64,
+    },
+    /// Retire tokens from the holding token account, paying for them from the yield account
+    AllocateYield {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[arg(long, value_parser = parse_sol)]
+        sol_amount: u64,
+        /// The value of the tokens to retire, in lamports
+        #[arg(long)]
+        token_amount: u64,
+    },
+    /// Swap yield to tokens through the state's pool and retire them
+    SwapAndBurn {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[arg(long, value_parser = parse_sol)]
+        sol_amount: u64,
+        /// The fewest tokens (in base units) the swap may return
+        #[arg(long)]
+        minimum_amount_out: u64,
+        #[command(flatten)]
+        pool: SwapPoolArgs,
+    },
+    /// List the state's retirement receipts
+    Receipts(BuyBurnTarget),
+}
+
+#[derive(Args, Debug)]
+pub struct StateArgs {
+    #[arg(long)]
+    treasury: Option<Pubkey>,
+    #[arg(long)]
+    holding_account: Option<Pubkey>,
+    #[arg(long)]
+    holding_token_account: Option<Pubkey>,
+    /// The price, in lamports per token base unit
+    #[arg(long)]
+    price: Option<u64>,
+    #[arg(long)]
+    purchase_threshold: Option<u64>,
+    /// The share of the yield paid to the treasury, between 0 and 1
+    #[arg(long)]
+    purchase_proportion: Option<f32>,
+    /// The oldest a price may be, in seconds, for yield to be allocated at it (0 for no limit) [default on register: a day]
+    #[arg(long)]
+    price_staleness_threshold: Option<u64>,
+    /// The most the price may change by within an hour, in basis points (0 for no limit)
+    #[arg(long)]
+    max_price_change_bps: Option<u16>,
+    #[arg(long)]
+    swap_program: Option<Pubkey>,
+    #[arg(long)]
+    swap_pool: Option<Pubkey>,
+    /// The most a swap may return below what the price buys, in basis points [default on register: 100]
+    #[arg(long)]
+    max_slippage_bps: Option<u16>,
+    #[arg(long, value_enum)]
+    retirement_backend: Option<RetirementBackend>,
+    #[arg(long)]
+    retirement_vault: Option<Pubkey>,
+    #[arg(long)]
+    retirement_beneficiary: Option<Pubkey>,
+    /// [default on register: the keypair]
+    #[arg(long)]
+    update_authority: Option<Pubkey>,
+}
+
+impl StateArgs {
+    fn apply(self, state_in: &mut GenericStateInput) {
+        if let Some(retirement_backend) = self.retirement_backend {
+            state_in.retirement_backend = retirement_backend.into();
+        }
+        macro_rules! apply {
+            ($($field:ident),*) => {
+                $(if let Some(value) = self.$field {
+                    state_in.$field = value;
+                })*
+            };
+        }
+        apply!(
+            treasury,
+            holding_account,
+            holding_token_account,
+            price,
+            purchase_threshold,
+            purchase_proportion,
+            price_staleness_threshold,
+            max_price_change_bps,
+            swap_program,
+            swap_pool,
+            max_slippage_bps,
+            retirement_vault,
+            retirement_beneficiary,
+            update_authority
+        );
+    }
+}
+
+fn state_input(state: &State) -> GenericStateInput {
+    GenericStateInput {
+        mint: state.mint,
+        update_authority: state.update_authority,
+        treasury: state.treasury,
+        holding_account: state.holding_account,
+        holding_token_account: state.holding_token_account,
+        price: state.price,
+        purchase_threshold: state.purchase_threshold,
+        purchase_proportion: state.purchase_proportion,
+        price_staleness_threshold: state.price_staleness_threshold,
+        max_price_change_bps: state.max_price_change_bps,
+        swap_program: state.swap_program,
+        swap_pool: state.swap_pool,
+        max_slippage_bps: state.max_slippage_bps,
+        retirement_backend: state.retirement_backend,
+        retirement_vault: state.retirement_vault,
+        retirement_beneficiary: state.retirement_beneficiary,
+        index: state.index,
+    }
+}
+
+fn backend_name(backend: buy_burn_fixed::RetirementBackend) -> &'static str {
+    match backend {
+        buy_burn_fixed::RetirementBackend::Burn => "burn",
+        buy_burn_fixed::RetirementBackend::Vault => "vault",
+    }
+}
+
+pub fn state_json(address: &Pubkey, state: &State) -> Value {
+    json!({
+        "address": address.to_string(),
+        "mint": state.mint.to_string(),
+        "index": state.index,
+        "updateAuthority": state.update_authority.to_string(),
+        "yieldAccount": pda::yield_account(&buy_burn_fixed::ID, address).to_string(),
+        "treasury": state.treasury.to_string(),
+        "holdingAccount": state.holding_account.to_string(),
+        "holdingTokenAccount": state.holding_token_account.to_string(),
+        "price": state.price,
+        "lastPriceUpdateTimestamp": state.last_price_update_timestamp,
+        "priceStalenessThreshold": state.price_staleness_threshold,
+        "maxPriceChangeBps": state.max_price_change_bps,
+        "purchaseThreshold": state.purchase_threshold,
+        "purchaseProportion": state.purchase_proportion,
+        "totalSpent": state.total_spent,
+        "swapProgram": state.swap_program.to_string(),
+        "swapPool": state.swap_pool.to_string(),
+        "maxSlippageBps": state.max_slippage_bps,
+        "retirementBackend": backend_name(state.retirement_backend),
+        "retirementVault": state.retirement_vault.to_string(),
+        "retirementBeneficiary": state.retirement_beneficiary.to_string(),
+        "totalRetirements": state.total_retirements,
+    })
+}
+
+pub fn receipt_json(address: &Pubkey, receipt: &RetirementReceipt) -> Value {
+    json!({
+        "address": address.to_string(),
+        "index": receipt.index,
+        "mint": receipt.mint.to_string(),
+        "beneficiary": receipt.beneficiary.to_string(),
+        "amount": receipt.amount,
+        "timestamp": receipt.timestamp,
+    })
+}
+
+pub async fn run(ctx: &Context, command: Command) -> Result<Value> {
+    let program = ctx.client.buy_burn_fixed();
+    match command {
+        Command::Show(target) => {
+            let (address, state) = program.state_for_mint(&target.mint, target.index).await?;
+            let mut output = state_json(&address, &state);
+            output["yieldAccountBalance"] = ctx
+                .balance(&pda::yield_account(&buy_burn_fixed::ID, &address))
+                .await?
+                .into();
+            Ok(output)
+        }
+        Command::Register { target, state } => {
+            let required = |field: Option<Pubkey>, name: &str| {
+                field.ok_or_else(|| anyhow!("--{} is required", name))
+            };
+            let mut state_in = GenericStateInput {
+                mint: target.mint,
+                update_authority: ctx.payer(),
+                treasury: required(state.treasury, "treasury")?,
+                holding_account: required(state.holding_account, "holding-account")?,
+                holding_token_account: required(
+                    state.holding_token_account,
+                    "holding-token-account",
+                )?,
+                price: state.price.ok_or_else(|| anyhow!("--price is required"))?,
+                purchase_threshold: 0,
+                purchase_proportion: 0.0,
+                price_staleness_threshold: 24 * 60 * 60,
+                max_price_change_bps: 0,
+                swap_program: Pubkey::default(),
+                swap_pool: Pubkey::default(),
+                max_slippage_bps: 100,
+                retirement_backend: Default::default(),
+                retirement_vault: Pubkey::default(),
+                retirement_beneficiary: Pubkey::default(),
+                index: target.index,
+            };
+            state.apply(&mut state_in);
+            ctx.execute(vec![buy_burn_fixed::register_state(&ctx.payer(), state_in)])
+                .await
+        }
+        Command::Update {
+            target,
+            state: args,
+        } => {
+            let (_, state) = program.state_for_mint(&target.mint, target.index).await?;
+            let mut state_in = state_input(&state);
+            args.apply(&mut state_in);
+            ctx.execute(vec![buy_burn_fixed::update_state(&ctx.payer(), state_in)])
+                .await
+        }
+        Command::MigrateState {
+            target,
+            price_staleness_threshold,
+            max_price_change_bps,
+            max_slippage_bps,
+        } => {
+            ctx.execute(vec![buy_burn_fixed::migrate_state(
+                &ctx.payer(),
+                &target.mint,
+                MigrateStateInput {
+                    index: target.index,
+                    price_staleness_threshold,
+                    max_price_change_bps,
+                    max_slippage_bps,
+                },
+            )])
+            .await
+        }
+        Command::UpdatePrice { target, price } => {
+            let address = pda::buy_burn_state(&buy_burn_fixed::ID, &target.mint, target.index);
+            ctx.execute(vec![buy_burn_fixed::update_price(
+                &ctx.payer(),
+                &address,
+                price,
+            )])
+            .await
+        }
+        Command::AllocateYield {
+            target,
+            sol_amount,
+            token_amount,
+        } => {
+            let (address, state) = program.state_for_mint(&target.mint, target.index).await?;
+            ctx.execute(vec![buy_burn_fixed::allocate_yield(
+                &ctx.payer(),
+                &address,
+                &state,
+                AllocateYieldInput {
+                    sol_amount,
+                    token_amount,
+                },
+            )])
+            .await
+        }
+        Command::SwapAndBurn {
+            target,
+            sol_amount,
+            minimum_amount_out,
+            pool,
+        } => {
+            let (address, state) = program.state_for_mint(&target.mint, target.index).await?;
+            ctx.execute(vec![buy_burn_fixed::swap_and_burn(
+                &ctx.payer(),
+                &address,
+                &state,
+                &pool.into(),
+                SwapAndBurnInput {
+                    sol_amount,
+                    minimum_amount_out,
+                },
+            )])
+            .await
+        }
+        Command::Receipts(target) => {
+            let address = pda::buy_burn_state(&buy_burn_fixed::ID, &target.mint, target.index);
+            let receipts = program.retirement_receipts(&address).await?;
+            Ok(receipts
+                .iter()
+                .map(|(address, receipt)| receipt_json(address, receipt))
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use crate::commands::{BuyBurnTarget, RetirementBackend, SwapPoolArgs};
+use crate::context::Context;
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use yield_controller_client::buy_burn_switchboard::{
+    self, AdjustmentLog, GenericStateInput, RetirementReceipt, State,
+};
+use yield_controller_client::pda;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show a buy-burn-switchboard state
+    Show(BuyBurnTarget),
+    /// Register a buy-burn-switchboard state
+    Register {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[command(flatten)]
+        state: StateArgs,
+    },
+    /// Update a buy-burn-switchboard state, keeping any settings not given
+    Update {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        #[command(flatten)]
+        state: StateArgs,
+    },
+    /// Upgrade a state registered before swaps, vault retirement and burn receipts were added
+    MigrateState(BuyBurnTarget),
+    /// Correct the state's total tokens purchased, recording the adjustment
+    AdjustTotalTokensPurchased {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        /// The tokens (in base units) to add, or remove if negative
+        #[arg(long, allow_negative_numbers = true)]
+        delta: i64,
+        #[arg(long, value_enum)]
+        reason: AdjustmentReason,
+    },
+    /// Retire tokens from the holding token account at the price feeds' price,
+    /// paying for them from the yield account
+    AllocateYield(BuyBurnTarget),
+    /// Swap yield to tokens through the state's pool and retire them
+    SwapAndBurn {
+        #[command(flatten)]
+        target: BuyBurnTarget,
+        /// The fewest tokens (in base units) the swap may return
+        #[arg(long)]
+        minimum_amount_out: u64,
+        #[command(flatten)]
+        pool: SwapPoolArgs,
+    },
+    /// List the state's retirement receipts
+    Receipts(BuyBurnTarget),
+    /// List the manual adjustments to the state's total tokens purchased
+    Adjustments(BuyBurnTarget),
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum AdjustmentReason {
+    Correction,
+    Reconciliation,
+    Migration,
+    Other,
+}
+
+impl From<AdjustmentReason> for buy_burn_switchboard::AdjustmentReason {
+    fn from(reason: AdjustmentReason) -> Self {
+        match reason {
+            AdjustmentReason::Correction => Self::Correction,
+            AdjustmentReason::Reconciliation => Self::Reconciliation,
+            AdjustmentReason::Migration => Self::Migration,
+            AdjustmentReason::Other => Self::Other,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct StateArgs {
+    #[arg(long)]
+    treasury: Option<Pubkey>,
+    #[arg(long)]
+    holding_account: Option<Pubkey>,
+    #[arg(long)]
+    holding_token_account: Option<Pubkey>,
+    #[arg(long)]
+    sol_usd_price_feed: Option<Pubkey>,
+    #[arg(long)]
+    nct_usd_price_feed: Option<Pubkey>,
+    /// The oldest a feed round may be, in seconds, for yield to be allocated at its price [default on register: a day]
+    #[arg(long)]
+    feed_staleness_threshold: Option<u64>,
+    #[arg(long)]
+    purchase_threshold: Option<u64>,
+    /// The share of the yield paid to the treasury, between 0 and 1
+    #[arg(long)]
+    purchase_proportion: Option<f32>,
+    #[arg(long)]
+    swap_program: Option<Pubkey>,
+    #[arg(long)]
+    swap_pool: Option<Pubkey>,
+    /// The most a swap may return below what the oracle price buys, in basis points [default on register: 100]
+    #[arg(long)]
+    max_slippage_bps: Option<u16>,
+    #[arg(long, value_enum)]
+    retirement_backend: Option<RetirementBackend>,
+    #[arg(long)]
+    retirement_vault: Option<Pubkey>,
+    #[arg(long)]
+    retirement_beneficiary: Option<Pubkey>,
+    /// The Bubblegum tree burn receipts are minted into, of which the state must be the tree delegate
+    #[arg(long)]
+    receipt_merkle_tree: Option<Pubkey>,
+    /// The owner of minted burn receipts
+    #[arg(long)]
+    receipt_vault: Option<Pubkey>,
+    /// [default on register: the keypair]
+    #[arg(long)]
+    update_authority: Option<Pubkey>,
+}
+
+impl StateArgs {
+    fn apply(self, state_in: &mut GenericStateInput) {
+        if let Some(retirement_backend) = self.retirement_backend {
+            state_in.retirement_backend = retirement_backend.into();
+        }
+        macro_rules! apply {
+            ($($field:ident),*) => {
+                $(if let Some(value) = self.$field {
+                    state_in.$field = value;
+                })*
+            };
+        }
+        apply!(
+            treasury,
+            holding_account,
+            holding_token_account,
+            sol_usd_price_feed,
+            nct_usd_price_feed,
+            feed_staleness_threshold,
+            purchase_threshold,
+            purchase_proportion,
+            swap_program,
+            swap_pool,
+            max_slippage_bps,
+            retirement_vault,
+            retirement_beneficiary,
+            receipt_merkle_tree,
+            receipt_vault,
+            update_authority
+        );
+    }
+}
+
+fn state_input(state: &State) -> GenericStateInput {
+    GenericStateInput {
+        mint: state.mint,
+        update_authority: state.update_authority,
+        treasury: state.treasury,
+        holding_account: state.holding_account,
+        holding_token_account: state.holding_token_account,
+        sol_usd_price_feed: state.sol_usd_price_feed,
+        nct_usd_price_feed: state.nct_usd_price_feed,
+        feed_staleness_threshold: state.feed_staleness_threshold,
+        purchase_threshold: state.purchase_threshold,
+        purchase_proportion: state.purchase_proportion,
+        index: state.index,
+        yield_account_bump: state.yield_account_bump,
+        swap_program: state.swap_program,
+        swap_pool: state.swap_pool,
+        max_slippage_bps: state.max_slippage_bps,
+        retirement_backend: state.retirement_backend,
+        retirement_vault: state.retirement_vault,
+        retirement_beneficiary: state.retirement_beneficiary,
+        receipt_merkle_tree: state.receipt_merkle_tree,
+        receipt_vault: state.receipt_vault,
+    }
+}
+
+fn backend_name(backend: buy_burn_switchboard::RetirementBackend) -> &'static str {
+    match backend {
+        buy_burn_switchboard::RetirementBackend::Burn => "burn",
+        buy_burn_switchboard::RetirementBackend::Vault => "vault",
+    }
+}
+
+pub fn state_json(address: &Pubkey, state: &State) -> Value {
+    json!({
+        "address": address.to_string(),
+        "mint": state.mint.to_string(),
+        "index": state.index,
+        "updateAuthority": state.update_authority.to_string(),
+        "yieldAccount": pda::yield_account(&buy_burn_switchboard::ID, address).to_string(),
+        "treasury": state.treasury.to_string(),
+        "holdingAccount": state.holding_account.to_string(),
+        "holdingTokenAccount": state.holding_token_account.to_string(),
+        "solUsdPriceFeed": state.sol_usd_price_feed.to_string(),
+        "nctUsdPriceFeed": state.nct_usd_price_feed.to_string(),
+        "feedStalenessThreshold": state.feed_staleness_threshold,
+        "purchaseThreshold": state.purchase_threshold,
+        "purchaseProportion": state.purchase_proportion,
+        "totalTokensPurchased": state.total_tokens_purchased,
+        "swapProgram": state.swap_program.to_string(),
+        "swapPool": state.swap_pool.to_string(),
+        "maxSlippageBps": state.max_slippage_bps,
+        "retirementBackend": backend_name(state.retirement_backend),
+        "retirementVault": state.retirement_vault.to_string(),
+        "retirementBeneficiary": state.retirement_beneficiary.to_string(),
+        "totalRetirements": state.total_retirements,
+        "receiptMerkleTree": state.receipt_merkle_tree.to_string(),
+        "receiptVault": state.receipt_vault.to_string(),
+    })
+}
+
+fn receipt_json(address: &Pubkey, receipt: &RetirementReceipt) -> Value {
+    json!({
+        "address": address.to_string(),
+        "index": receipt.index,
+        "mint": receipt.mint.to_string(),
+        "beneficiary": receipt.beneficiary.to_string(),
+        "amount": receipt.amount,
+        "timestamp": receipt.timestamp,
+    })
+}
+
+fn adjustments_json(log: &AdjustmentLog) -> Value {
+    log.adjustments
+        .iter()
+        .map(|adjustment| {
+            json!({
+                "delta": adjustment.delta,
+                "reason": format!("{:?}", adjustment.reason).to_lowercase(),
+                "signer": adjustment.signer.to_string(),
+                "timestamp": adjustment.timestamp,
+            })
+        })
+        .collect()
+}
+
+pub async fn run(ctx: &Context, command: Command) -> Result<Value> {
+    let program = ctx.client.buy_burn_switchboard();
+    let address_of = |target: &BuyBurnTarget| {
+        pda::buy_burn_state(&buy_burn_switchboard::ID, &target.mint, target.index)
+    };
+    match command {
+        Command::Show(target) => {
+            let (address, state) = program.state_for_mint(&target.mint, target.index).await?;
+            let mut output = state_json(&address, &state);
+            output["yieldAccountBalance"] = ctx
+                .balance(&pda::yield_account(&buy_burn_switchboard::ID, &address))
+                .await?
+                .into();
+            Ok(output)
+        }
+        Command::Register { target, state } => {
+            let required = |field: Option<Pubkey>, name: &str| {
+                field.ok_or_else(|| anyhow!("--{} is required", name))
+            };
+            let address = address_of(&target);
+            let (_, yield_account_bump) =
+                pda::yield_account_with_bump(&buy_burn_switchboard::ID, &address);
+            let mut state_in = GenericStateInput {
+                mint: target.mint,
+                update_authority: ctx.payer(),
+                treasury: required(state.treasury, "treasury")?,
+                holding_account: required(state.holding_account, "holding-account")?,
+                holding_token_account: required(
+                    state.holding_token_account,
+                    "holding-token-account",
+                )?,
+                sol_usd_price_feed: required(state.sol_usd_price_feed, "sol-usd-price-feed")?,
+                nct_usd_price_feed: required(state.nct_usd_price_feed, "nct-usd-price-feed")?,
+                feed_staleness_threshold: 24 * 60 * 60,
+                purchase_threshold: 0,
+                purchase_proportion: 0.0,
+                index: target.index,
+                yield_account_bump,
+                swap_program: Pubkey::default(),
+                swap_pool: Pubkey::default(),
+                max_slippage_bps: 100,
+                retirement_backend: Default::default(),
+                retirement_vault: Pubkey::default(),
+                retirement_beneficiary: Pubkey::default(),
+                receipt_merkle_tree: Pubkey::default(),
+                receipt_vault: Pubkey::default(),
+            };
+            state.apply(&mut state_in);
+            ctx.execute(vec![buy_burn_switchboard::register_state(
+                &ctx.payer(),
+                state_in,
+            )])
+            .await
+        }
+        Command::Update {
+            target,
+            state: args,
+        } => {
+            let (_, state) = program.state_for_mint(&target.mint, target.index).await?;
+            let mut state_in = state_input(&state);
+            args.apply(&mut state_in);
+            ctx.execute(vec![buy_burn_switchboard::update_state(
+                &ctx.payer(),
+                state_in,
+            )])
+            .await
+        }
+        Command::MigrateState(target) => {
+            ctx.execute(vec![buy_burn_switchboard::migrate_state(
+                &ctx.payer(),
+                &address_of(&target),
+            )])
+            .await
+        }
+        Command::AdjustTotalTokensPurchased {
+            target,
+            delta,
+            reason,
+        } => {
+            ctx.execute(vec![buy_burn_switchboard::adjust_total_tokens_purchased(
+                &ctx.payer(),
+                &address_of(&target),
+                delta,
+                reason.into(),
+            )])
+            .await
+        }
+        Command::AllocateYield(target) => {
+            let instruction = program
+                .allocate_yield(&ctx.payer(), &address_of(&target))
+                .await?;
+            ctx.execute(vec![instruction]).await
+        }
+        Command::SwapAndBurn {
+            target,
+            minimum_amount_out,
+            pool,
+        } => {
+            let instruction = program
+                .swap_and_burn(
+                    &ctx.payer(),
+                    &address_of(&target),
+                    &pool.into(),
+                    minimum_amount_out,
+                )
+                .await?;
+            ctx.execute(vec![instruction]).await
+        }
+        Command::Receipts(target) => {
+            let receipts = program.retirement_receipts(&address_of(&target)).await?;
+            Ok(receipts
+                .iter()
+                .map(|(address, receipt)| receipt_json(address, receipt))
+                .collect())
+        }
+        Command::Adjustments(target) => {
+            let log = program.adjustment_log(&address_of(&target)).await?;
+            Ok(log.as_ref().map_or(json!([]), adjustments_json))
+        }
+    }
+}
@@ -0,0 +1,641 @@
+use crate::commands::{parse_pair, parse_sol, parse_weighted, spendable, SwapPoolArgs};
+use crate::context::Context;
+use crate::das;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::{token, token_2022};
+use anyhow::{bail, Result};
+use clap::{Args, Subcommand};
+use serde_json::{json, Value};
+use solana_account_decoder::UiAccountEncoding;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use yield_controller_client::fund_sender::{
+    self, CertificateRecord, GenericStateInput, State, StoreCertificateAccounts,
+};
+use yield_controller_client::pda;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show a fund sender state
+    Show { destination_name: String },
+    /// Register a fund sender state for the Sunrise state
+    Register {
+        destination_name: String,
+        #[command(flatten)]
+        state: StateArgs,
+    },
+    /// Update a fund sender state, keeping any settings not given
+    Update {
+        destination_name: String,
+        #[command(flatten)]
+        state: StateArgs,
+    },
+    /// Upgrade a fund sender state registered with the original layout
+    MigrateState { destination_name: String },
+    /// Move a fund sender state to a new destination name
+    MigrateDestination {
+        destination_name: String,
+        new_destination_name: String,
+        /// A token account of the old input account and one of the same mint of the new input account,
+        /// as OLD:NEW (repeatable). Every token account of the old input account must be passed,
+        /// as it cannot sign once the state is closed
+        #[arg(long = "token-accounts", value_parser = parse_pair)]
+        token_account_pairs: Vec<(Pubkey, Pubkey)>,
+    },
+    /// Send funds from the input account to the state's destinations
+    Send {
+        destination_name: String,
+        /// The SOL to send [default: the whole spendable balance]
+        #[arg(long, value_parser = parse_sol)]
+        amount: Option<u64>,
+    },
+    /// Send the input account's balance of the state's token to the destination
+    SendToken { destination_name: String },
+    /// Move funds sent to the state account itself to its input account
+    SendFromState { destination_name: String },
+    /// Swap SOL from the input account to the state's token and send it to the destination
+    SwapAndSend {
+        destination_name: String,
+        #[arg(long, value_parser = parse_sol)]
+        amount: u64,
+        /// The fewest tokens (in base units) the swap may return
+        #[arg(long)]
+        minimum_amount_out: u64,
+        #[command(flatten)]
+        pool: SwapPoolArgs,
+    },
+    /// List the certificates the state has stored
+    Certificates { destination_name: String },
+    /// Store the input account's token certificates (SPL token or Token-2022) in the certificate vault
+    StoreCertificates {
+        destination_name: String,
+        /// The certificate mints to store (repeatable) [default: every mint the input account holds,
+        /// other than the state's token and wrapped SOL]
+        #[arg(long = "mint")]
+        mints: Vec<Pubkey>,
+        #[command(flatten)]
+        store: StoreArgs,
+    },
+    /// Store a Metaplex Core certificate held by the input account in the certificate vault
+    StoreCoreCertificate {
+        destination_name: String,
+        asset: Pubkey,
+        /// The asset's collection, if it has one
+        #[arg(long)]
+        collection: Option<Pubkey>,
+        #[command(flatten)]
+        store: StoreArgs,
+    },
+    /// Store compressed certificates held by the input account in the certificate vault,
+    /// reading them through the DAS API
+    StoreCnftCertificates {
+        destination_name: String,
+        /// The certificate asset ids to store (repeatable) [default: every compressed NFT the input account holds]
+        #[arg(long = "asset")]
+        assets: Vec<Pubkey>,
+        /// The canopy depth of the certificates' tree, whose proof nodes need not be passed
+        #[arg(long, default_value_t = 0)]
+        canopy_depth: usize,
+        #[command(flatten)]
+        store: StoreArgs,
+    },
+    /// Release token certificates from the state's certificate vault to a beneficiary
+    ReleaseCertificate {
+        destination_name: String,
+        mint: Pubkey,
+        beneficiary: Pubkey,
+        /// The amount of the certificate token, in base units
+        amount: u64,
+        /// The index of the certificate's record, to attribute it to the beneficiary
+        #[arg(long)]
+        record: Option<u64>,
+    },
+    /// Release a Metaplex Core certificate from the state's certificate vault to a beneficiary
+    ReleaseCoreCertificate {
+        destination_name: String,
+        asset: Pubkey,
+        beneficiary: Pubkey,
+        /// The asset's collection, if it has one
+        #[arg(long)]
+        collection: Option<Pubkey>,
+        /// The index of the certificate's record, to attribute it to the beneficiary
+        #[arg(long)]
+        record: Option<u64>,
+    },
+    /// Release a compressed certificate from the state's certificate vault to a beneficiary,
+    /// reading it through the DAS API
+    ReleaseCnftCertificate {
+        destination_name: String,
+        asset: Pubkey,
+        beneficiary: Pubkey,
+        /// The canopy depth of the certificate's tree, whose proof nodes need not be passed
+        #[arg(long, default_value_t = 0)]
+        canopy_depth: usize,
+        /// The index of the certificate's record, to attribute it to the beneficiary
+        #[arg(long)]
+        record: Option<u64>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct StateArgs {
+    /// The primary destination of the state's funds
+    #[arg(long)]
+    destination_account: Option<Pubkey>,
+    /// A weighted destination and its percentage of the funds, as ADDRESS:PROPORTION (repeatable).
+    /// The first must be the destination account
+    #[arg(long = "destination", value_parser = parse_weighted)]
+    destinations: Vec<(Pubkey, u8)>,
+    /// Send all funds to the destination account, removing any weighted destinations
+    #[arg(long, conflicts_with = "destinations")]
+    no_weighted_destinations: bool,
+    /// The least funds, in lamports, that are sent
+    #[arg(long)]
+    spend_threshold: Option<u64>,
+    #[arg(long)]
+    certificate_vault: Option<Pubkey>,
+    /// The vault certificates not matching the state's filter are sent to
+    #[arg(long)]
+    quarantine_vault: Option<Pubkey>,
+    /// Lock certificates in the state's program-owned vault
+    #[arg(long)]
+    lock_certificates: Option<bool>,
+    /// The beneficiary certificates are recorded against
+    #[arg(long)]
+    certificate_beneficiary: Option<Pubkey>,
+    #[arg(long)]
+    certificate_collection: Option<Pubkey>,
+    #[arg(long)]
+    certificate_creator: Option<Pubkey>,
+    /// A mint (or asset id) accepted as a certificate (repeatable)
+    #[arg(long = "allowed-certificate-mint")]
+    allowed_certificate_mints: Vec<Pubkey>,
+    /// The token sent by send-token and bought by swap-and-send
+    #[arg(long)]
+    token_mint: Option<Pubkey>,
+    /// The least tokens, in base units, that are sent
+    #[arg(long)]
+    token_spend_threshold: Option<u64>,
+    #[arg(long)]
+    swap_program: Option<Pubkey>,
+    #[arg(long)]
+    swap_pool: Option<Pubkey>,
+    /// [default on register: the keypair]
+    #[arg(long)]
+    update_authority: Option<Pubkey>,
+}
+
+impl StateArgs {
+    fn apply(self, state_in: &mut GenericStateInput) {
+        if let Some(destination_account) = self.destination_account {
+            state_in.destination_account = destination_account;
+        }
+        if !self.destinations.is_empty() {
+            (
+                state_in.destination_accounts,
+                state_in.destination_proportions,
+            ) = self.destinations.into_iter().unzip();
+        }
+        if self.no_weighted_destinations {
+            state_in.destination_accounts.clear();
+            state_in.destination_proportions.clear();
+        }
+        if !self.allowed_certificate_mints.is_empty() {
+            state_in.allowed_certificate_mints = self.allowed_certificate_mints;
+        }
+        macro_rules! apply {
+            ($($field:ident),*) => {
+                $(if let Some(value) = self.$field {
+                    state_in.$field = value;
+                })*
+            };
+        }
+        apply!(
+            spend_threshold,
+            certificate_vault,
+            quarantine_vault,
+            lock_certificates,
+            certificate_beneficiary,
+            certificate_collection,
+            certificate_creator,
+            token_mint,
+            token_spend_threshold,
+            swap_program,
+            swap_pool,
+            update_authority
+        );
+    }
+}
+
+/// Where stored certificates go and who they are recorded against
+#[derive(Args, Debug)]
+pub struct StoreArgs {
+    /// Store the certificates in the state's quarantine vault
+    #[arg(long)]
+    quarantine: bool,
+    /// The beneficiary to record the certificates against, which requires the keypair
+    /// to be the update authority [default: the state's certificate beneficiary]
+    #[arg(long)]
+    beneficiary: Option<Pubkey>,
+}
+
+impl StoreArgs {
+    fn accounts(&self, state: &State) -> StoreCertificateAccounts {
+        StoreCertificateAccounts {
+            certificate_vault: if self.quarantine {
+                state.quarantine_vault
+            } else {
+                state.certificate_vault
+            },
+            beneficiary: self.beneficiary,
+        }
+    }
+}
+
+fn state_input(state: State) -> GenericStateInput {
+    GenericStateInput {
+        destination_name: state.destination_name,
+        update_authority: state.update_authority,
+        destination_account: state.destination_account,
+        certificate_vault: state.certificate_vault,
+        spend_threshold: state.spend_threshold,
+        destination_accounts: state.destination_accounts,
+        destination_proportions: state.destination_proportions,
+        token_mint: state.token_mint,
+        token_spend_threshold: state.token_spend_threshold,
+        swap_program: state.swap_program,
+        swap_pool: state.swap_pool,
+        certificate_collection: state.certificate_collection,
+        certificate_creator: state.certificate_creator,
+        allowed_certificate_mints: state.allowed_certificate_mints,
+        quarantine_vault: state.quarantine_vault,
+        lock_certificates: state.lock_certificates,
+        certificate_beneficiary: state.certificate_beneficiary,
+    }
+}
+
+pub fn state_json(address: &Pubkey, state: &State) -> Value {
+    json!({
+        "address": address.to_string(),
+        "destinationName": state.destination_name,
+        "sunriseState": state.sunrise_state.to_string(),
+        "updateAuthority": state.update_authority.to_string(),
+        "inputAccount": pda::input_account(address).to_string(),
+        "destinationAccount": state.destination_account.to_string(),
+        "destinations": state
+            .destination_accounts
+            .iter()
+            .zip(&state.destination_proportions)
+            .map(|(account, proportion)| json!({
+                "destinationAccount": account.to_string(),
+                "proportion": proportion,
+            }))
+            .collect::<Vec<_>>(),
+        "spendThreshold": state.spend_threshold,
+        "totalSpent": state.total_spent,
+        "tokenMint": state.token_mint.to_string(),
+        "tokenSpendThreshold": state.token_spend_threshold,
+        "totalTokensSpent": state.total_tokens_spent,
+        "swapProgram": state.swap_program.to_string(),
+        "swapPool": state.swap_pool.to_string(),
+        "certificateVault": state.certificate_vault.to_string(),
+        "quarantineVault": state.quarantine_vault.to_string(),
+        "lockCertificates": state.lock_certificates,
+        "certificateBeneficiary": state.certificate_beneficiary.to_string(),
+        "certificateCollection": state.certificate_collection.to_string(),
+        "certificateCreator": state.certificate_creator.to_string(),
+        "allowedCertificateMints": state
+            .allowed_certificate_mints
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        "totalCertificates": state.total_certificates,
+    })
+}
+
+fn record_json(address: &Pubkey, record: &CertificateRecord) -> Value {
+    json!({
+        "address": address.to_string(),
+        "index": record.index,
+        "certificate": record.certificate.to_string(),
+        "amount": record.amount,
+        "timestamp": record.timestamp,
+        "totalSpent": record.total_spent,
+        "quarantined": record.quarantined,
+        "beneficiary": record.beneficiary.to_string(),
+    })
+}
+
+// the mints of the token accounts owned by `owner` with a balance, of either token program
+async fn held_mints(ctx: &Context, owner: &Pubkey) -> Result<Vec<Pubkey>> {
+    let config = RpcProgramAccountsConfig {
+        // the owner of a token account follows its mint
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            32,
+            owner.to_bytes().to_vec(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let mut mints = vec![];
+    for program_id in [token::ID, token_2022::ID] {
+        let accounts: Vec<(Pubkey, Account)> = ctx
+            .rpc()
+            .get_program_accounts_with_config(&program_id, config.clone())
+            .await?;
+        for (_, account) in accounts {
+            // a token account starts with its mint, owner and amount
+            let amount = u64::from_le_bytes(account.data[64..72].try_into()?);
+            if amount > 0 {
+                mints.push(Pubkey::try_from(&account.data[..32])?);
+            }
+        }
+    }
+    Ok(mints)
+}
+
+pub async fn run(ctx: &Context, command: Command) -> Result<Value> {
+    let sender = &ctx.client.fund_sender();
+    let state_for = |destination_name: String| async move {
+        sender
+            .state_for_destination(&destination_name, &ctx.sunrise_state)
+            .await
+    };
+    match command {
+        Command::Show { destination_name } => {
+            let (address, state) = state_for(destination_name).await?;
+            let mut output = state_json(&address, &state);
+            output["inputAccountBalance"] =
+                ctx.balance(&pda::input_account(&address)).await?.into();
+            Ok(output)
+        }
+        Command::Register {
+            destination_name,
+            state,
+        } => {
+            let mut state_in = GenericStateInput {
+                destination_name,
+                update_authority: ctx.payer(),
+                destination_account: Pubkey::default(),
+                certificate_vault: Pubkey::default(),
+                spend_threshold: 0,
+                destination_accounts: vec![],
+                destination_proportions: vec![],
+                token_mint: Pubkey::default(),
+                token_spend_threshold: 0,
+                swap_program: Pubkey::default(),
+                swap_pool: Pubkey::default(),
+                certificate_collection: Pubkey::default(),
+                certificate_creator: Pubkey::default(),
+                allowed_certificate_mints: vec![],
+                quarantine_vault: Pubkey::default(),
+                lock_certificates: false,
+                certificate_beneficiary: Pubkey::default(),
+            };
+            state.apply(&mut state_in);
+            if state_in.destination_account == Pubkey::default() {
+                bail!("--destination-account is required");
+            }
+            ctx.execute(vec![fund_sender::register_state(
+                &ctx.payer(),
+                &ctx.sunrise_state,
+                state_in,
+            )])
+            .await
+        }
+        Command::Update {
+            destination_name,
+            state: args,
+        } => {
+            let (address, state) = state_for(destination_name).await?;
+            let mut state_in = state_input(state);
+            args.apply(&mut state_in);
+            ctx.execute(vec![fund_sender::update_state(
+                &ctx.payer(),
+                &address,
+                state_in,
+            )])
+            .await
+        }
+        Command::MigrateState { destination_name } => {
+            // a legacy state cannot be read as a `State`, so derive it rather than fetching it
+            let address = pda::fund_sender_state(&destination_name, &ctx.sunrise_state);
+            ctx.execute(vec![fund_sender::migrate_state(&ctx.payer(), &address)])
+                .await
+        }
+        Command::MigrateDestination {
+            destination_name,
+            new_destination_name,
+            token_account_pairs,
+        } => {
+            let (address, state) = state_for(destination_name).await?;
+            ctx.execute(vec![fund_sender::migrate_destination(
+                &ctx.payer(),
+                &address,
+                &state,
+                new_destination_name,
+                &token_account_pairs,
+            )])
+            .await
+        }
+        Command::Send {
+            destination_name,
+            amount,
+        } => {
+            let (address, state) = state_for(destination_name).await?;
+            let instruction = match amount {
+                Some(amount) => fund_sender::send_fund(&ctx.payer(), &address, &state, amount),
+                None => {
+                    let balance = ctx.balance(&pda::input_account(&address)).await?;
+                    if spendable(balance) == 0 {
+                        bail!("no funds to send");
+                    }
+                    fund_sender::send_all_funds(&ctx.payer(), &address, &state)
+                }
+            };
+            ctx.execute(vec![instruction]).await
+        }
+        Command::SendToken { destination_name } => {
+            let (address, state) = state_for(destination_name).await?;
+            ctx.execute(vec![fund_sender::send_token_fund(
+                &ctx.payer(),
+                &address,
+                &state,
+            )])
+            .await
+        }
+        Command::SendFromState { destination_name } => {
+            let (address, _) = state_for(destination_name).await?;
+            ctx.execute(vec![fund_sender::send_from_state(&address)])
+                .await
+        }
+        Command::SwapAndSend {
+            destination_name,
+            amount,
+            minimum_amount_out,
+            pool,
+        } => {
+            let (address, state) = state_for(destination_name).await?;
+            ctx.execute(vec![fund_sender::swap_and_send(
+                &ctx.payer(),
+                &address,
+                &state,
+                &pool.into(),
+                amount,
+                minimum_amount_out,
+            )])
+            .await
+        }
+        Command::Certificates { destination_name } => {
+            let (address, _) = state_for(destination_name).await?;
+            let records = sender.certificate_records(&address).await?;
+            Ok(records
+                .iter()
+                .map(|(address, record)| record_json(address, record))
+                .collect())
+        }
+        Command::StoreCertificates {
+            destination_name,
+            mut mints,
+            store,
+        } => {
+            let (address, state) = state_for(destination_name.clone()).await?;
+            if mints.is_empty() {
+                // the state's token and wrapped SOL are funds to send, not certificates
+                mints = held_mints(ctx, &pda::input_account(&address))
+                    .await?
+                    .into_iter()
+                    .filter(|mint| *mint != state.token_mint && *mint != native_mint::ID)
+                    .collect();
+            }
+            if mints.is_empty() {
+                bail!("no certificates to store");
+            }
+            let mut outputs = vec![];
+            for mint in mints {
+                // each certificate is recorded at the state's next ledger index
+                let state = sender.state(&address).await?;
+                let instruction = sender
+                    .store_certificates(&ctx.payer(), &address, &mint, store.accounts(&state))
+                    .await?;
+                let mut output = ctx.execute(vec![instruction]).await?;
+                output["certificate"] = mint.to_string().into();
+                outputs.push(output);
+            }
+            Ok(outputs.into())
+        }
+        Command::StoreCoreCertificate {
+            destination_name,
+            asset,
+            collection,
+            store,
+        } => {
+            let (address, state) = state_for(destination_name).await?;
+            ctx.execute(vec![fund_sender::store_core_certificate(
+                &ctx.payer(),
+                &address,
+                &state,
+                &asset,
+                collection,
+                store.accounts(&state),
+            )])
+            .await
+        }
+        Command::StoreCnftCertificates {
+            destination_name,
+            mut assets,
+            canopy_depth,
+            store,
+        } => {
+            let (address, _) = state_for(destination_name).await?;
+            if assets.is_empty() {
+                assets = das::compressed_assets_by_owner(ctx.rpc(), &pda::input_account(&address))
+                    .await?;
+            }
+            if assets.is_empty() {
+                bail!("no certificates to store");
+            }
+            let mut outputs = vec![];
+            for asset in assets {
+                let state = sender.state(&address).await?;
+                let leaf = das::cnft_leaf(ctx.rpc(), &asset, canopy_depth).await?;
+                let instruction = fund_sender::store_cnft_certificate(
+                    &ctx.payer(),
+                    &address,
+                    &state,
+                    leaf.tree,
+                    store.accounts(&state),
+                    leaf.certificate,
+                    &leaf.proof,
+                );
+                let mut output = ctx.execute(vec![instruction]).await?;
+                output["certificate"] = asset.to_string().into();
+                outputs.push(output);
+            }
+            Ok(outputs.into())
+        }
+        Command::ReleaseCertificate {
+            destination_name,
+            mint,
+            beneficiary,
+            amount,
+            record,
+        } => {
+            let (address, _) = state_for(destination_name).await?;
+            let token_program = ctx.rpc().get_account(&mint).await?.owner;
+            ctx.execute(vec![fund_sender::release_certificate(
+                &ctx.payer(),
+                &address,
+                &mint,
+                &token_program,
+                &beneficiary,
+                record.map(|index| pda::certificate_record(&address, index)),
+                amount,
+            )])
+            .await
+        }
+        Command::ReleaseCoreCertificate {
+            destination_name,
+            asset,
+            beneficiary,
+            collection,
+            record,
+        } => {
+            let (address, _) = state_for(destination_name).await?;
+            ctx.execute(vec![fund_sender::release_core_certificate(
+                &ctx.payer(),
+                &address,
+                &asset,
+                collection,
+                &beneficiary,
+                record.map(|index| pda::certificate_record(&address, index)),
+            )])
+            .await
+        }
+        Command::ReleaseCnftCertificate {
+            destination_name,
+            asset,
+            beneficiary,
+            canopy_depth,
+            record,
+        } => {
+            let (address, _) = state_for(destination_name).await?;
+            let leaf = das::cnft_leaf(ctx.rpc(), &asset, canopy_depth).await?;
+            ctx.execute(vec![fund_sender::release_cnft_certificate(
+                &ctx.payer(),
+                &address,
+                &leaf.tree.merkle_tree,
+                &beneficiary,
+                record.map(|index| pda::certificate_record(&address, index)),
+                leaf.certificate,
+                &leaf.proof,
+            )])
+            .await
+        }
+    }
+}
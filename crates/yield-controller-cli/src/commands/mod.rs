@@ -0,0 +1,123 @@
+use anyhow::{anyhow, Result};
+use clap::{Args, ValueEnum};
+use solana_sdk::native_token::sol_to_lamports;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use std::str::FromStr;
+use yield_controller_client::SwapPoolAccounts;
+
+pub mod buy_burn_fixed;
+pub mod buy_burn_switchboard;
+pub mod fund_sender;
+pub mod status;
+pub mod transaction;
+pub mod yield_router;
+
+/// Parse an amount of SOL, e.g. `1.5`, into lamports
+pub fn parse_sol(s: &str) -> Result<u64> {
+    let sol = f64::from_str(s).map_err(|_| anyhow!("invalid SOL amount {}", s))?;
+    if !sol.is_finite() || sol < 0.0 {
+        return Err(anyhow!("invalid SOL amount {}", s));
+    }
+    Ok(sol_to_lamports(sol))
+}
+
+/// Parse `ADDRESS:PROPORTION`, e.g. an output yield account and its share of the yield
+pub fn parse_weighted(s: &str) -> Result<(Pubkey, u8)> {
+    let (address, proportion) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected ADDRESS:PROPORTION, got {}", s))?;
+    Ok((Pubkey::from_str(address)?, u8::from_str(proportion)?))
+}
+
+/// Parse `ADDRESS:ADDRESS`, e.g. a pair of token accounts
+pub fn parse_pair(s: &str) -> Result<(Pubkey, Pubkey)> {
+    let (first, second) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected ADDRESS:ADDRESS, got {}", s))?;
+    Ok((Pubkey::from_str(first)?, Pubkey::from_str(second)?))
+}
+
+/// The balance of a system-owned PDA that can be spent without making it rent-paying,
+/// as the programs compute it
+pub fn spendable(balance: u64) -> u64 {
+    balance.saturating_sub(Rent::default().minimum_balance(0))
+}
+
+/// The accounts of the token-swap pool a state swaps through
+#[derive(Args, Debug)]
+pub struct SwapPoolArgs {
+    #[arg(long)]
+    pub pool_authority: Pubkey,
+    /// The pool's wrapped SOL vault
+    #[arg(long)]
+    pub pool_source: Pubkey,
+    /// The pool's vault of the token being bought
+    #[arg(long)]
+    pub pool_destination: Pubkey,
+    #[arg(long)]
+    pub pool_mint: Pubkey,
+    #[arg(long)]
+    pub pool_fee_account: Pubkey,
+}
+
+impl From<SwapPoolArgs> for SwapPoolAccounts {
+    fn from(args: SwapPoolArgs) -> Self {
+        SwapPoolAccounts {
+            authority: args.pool_authority,
+            source: args.pool_source,
+            destination: args.pool_destination,
+            pool_mint: args.pool_mint,
+            fee_account: args.pool_fee_account,
+        }
+    }
+}
+
+/// A buy-burn state, identified by its mint and index
+#[derive(Args, Debug)]
+pub struct BuyBurnTarget {
+    /// The mint of the token the state buys and retires
+    #[arg(long)]
+    pub mint: Pubkey,
+    #[arg(long, default_value_t = 0)]
+    pub index: u8,
+}
+
+/// How a buy-burn state takes the tokens it buys out of circulation
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum RetirementBackend {
+    Burn,
+    /// Transfer the tokens to the state's retirement vault and record a receipt
+    Vault,
+}
+
+// both buy-burn programs share the backend of yield-controller-common
+impl From<RetirementBackend> for yield_controller_client::buy_burn_fixed::RetirementBackend {
+    fn from(backend: RetirementBackend) -> Self {
+        match backend {
+            RetirementBackend::Burn => Self::Burn,
+            RetirementBackend::Vault => Self::Vault,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_arguments() {
+        let address = Pubkey::new_unique();
+        assert_eq!(parse_sol("1.5").unwrap(), 1_500_000_000);
+        assert!(parse_sol("-1").is_err());
+        assert_eq!(
+            parse_weighted(&format!("{}:30", address)).unwrap(),
+            (address, 30)
+        );
+        assert!(parse_weighted(&address.to_string()).is_err());
+        assert_eq!(
+            parse_pair(&format!("{}:{}", address, address)).unwrap(),
+            (address, address)
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::commands::{spendable, yield_router};
+use crate::context::Context;
+use anyhow::Result;
+use serde_json::{json, Value};
+use yield_controller_client::fund_sender;
+use yield_controller_client::pda;
+
+/// The yield router and, for each of its output yield accounts, its balance and the fund sender
+/// it is the input account of, if any of `destination_names` is
+pub async fn run(ctx: &Context, destination_names: &[String]) -> Result<Value> {
+    let (address, state) = ctx
+        .client
+        .yield_router()
+        .state_for_sunrise_state(&ctx.sunrise_state)
+        .await?;
+    let mut router = yield_router::state_json(&address, &state);
+    router["inputYieldAccountBalance"] = ctx
+        .balance(&pda::input_yield_account(&address))
+        .await?
+        .into();
+
+    let mut fund_senders = vec![];
+    for destination_name in destination_names {
+        let address = pda::fund_sender_state(destination_name, &ctx.sunrise_state);
+        if let Some(state) = ctx
+            .client
+            .fetch_optional::<fund_sender::State>(&address)
+            .await?
+        {
+            fund_senders.push((address, state));
+        }
+    }
+
+    let mut outputs = vec![];
+    for (output_yield_account, proportion) in state
+        .output_yield_accounts
+        .iter()
+        .zip(&state.spend_proportions)
+    {
+        let balance = ctx.balance(output_yield_account).await?;
+        let fund_sender = match fund_senders
+            .iter()
+            .find(|(address, _)| pda::input_account(address) == *output_yield_account)
+        {
+            Some((address, state)) => json!({
+                "destinationName": state.destination_name,
+                "state": address.to_string(),
+                "spendThreshold": state.spend_threshold,
+                "destinationAccount": state.destination_account.to_string(),
+                "destinationBalance": ctx.balance(&state.destination_account).await?,
+            }),
+            None => Value::Null,
+        };
+        outputs.push(json!({
+            "outputYieldAccount": output_yield_account.to_string(),
+            "proportion": proportion,
+            "balance": balance,
+            "spendable": spendable(balance),
+            "fundSender": fund_sender,
+        }));
+    }
+
+    Ok(json!({ "yieldRouter": router, "outputs": outputs }))
+}
@@ -0,0 +1,35 @@
+use crate::context::Context;
+use crate::offline::decode_message;
+use anyhow::Result;
+use clap::Subcommand;
+use serde_json::Value;
+
+/// Transaction messages printed by `--sign-only --dump-transaction-message`, for commands that read
+/// the cluster and so cannot be rebuilt on an air-gapped machine
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Sign a message with the local keypairs, printing the signatures as `--sign-only` does
+    Sign {
+        /// The base64 message
+        message: String,
+    },
+    /// Send a message, signed with the local keypairs and the `--signer` signatures
+    Send {
+        /// The base64 message
+        message: String,
+    },
+}
+
+impl Command {
+    /// Whether the command only signs, so signers may be given by pubkey alone
+    pub fn is_sign_only(&self) -> bool {
+        matches!(self, Command::Sign { .. })
+    }
+}
+
+pub async fn run(ctx: &Context, command: Command) -> Result<Value> {
+    match command {
+        Command::Sign { message } => ctx.sign_only(decode_message(&message)?),
+        Command::Send { message } => ctx.submit(decode_message(&message)?).await,
+    }
+}
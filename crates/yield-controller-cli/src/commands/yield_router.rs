@@ -0,0 +1,133 @@
+use crate::commands::{parse_sol, parse_weighted, spendable};
+use crate::context::Context;
+use anyhow::{bail, Result};
+use clap::{Args, Subcommand};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use yield_controller_client::pda;
+use yield_controller_client::yield_router::{self, GenericStateInput, State};
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show the yield router state of the Sunrise state
+    Show,
+    /// Register a yield router state for the Sunrise state
+    Register(StateArgs),
+    /// Update the yield router state, keeping any settings not given
+    Update(StateArgs),
+    /// Allocate yield from the input yield account to the output yield accounts
+    Allocate {
+        /// The SOL to allocate [default: the whole spendable balance]
+        #[arg(long, value_parser = parse_sol)]
+        amount: Option<u64>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct StateArgs {
+    /// An output yield account and its percentage of the yield, as ADDRESS:PROPORTION (repeatable)
+    #[arg(long = "output-yield-account", value_parser = parse_weighted)]
+    outputs: Vec<(Pubkey, u8)>,
+    /// The least yield, in lamports, that is allocated
+    #[arg(long)]
+    spend_threshold: Option<u64>,
+    /// [default on register: the keypair]
+    #[arg(long)]
+    update_authority: Option<Pubkey>,
+}
+
+impl StateArgs {
+    fn apply(self, state_in: &mut GenericStateInput) {
+        if !self.outputs.is_empty() {
+            (state_in.output_yield_accounts, state_in.spend_proportions) =
+                self.outputs.into_iter().unzip();
+        }
+        if let Some(spend_threshold) = self.spend_threshold {
+            state_in.spend_threshold = spend_threshold;
+        }
+        if let Some(update_authority) = self.update_authority {
+            state_in.update_authority = update_authority;
+        }
+    }
+}
+
+pub fn state_json(address: &Pubkey, state: &State) -> Value {
+    json!({
+        "address": address.to_string(),
+        "sunriseState": state.sunrise_state.to_string(),
+        "updateAuthority": state.update_authority.to_string(),
+        "inputYieldAccount": pda::input_yield_account(address).to_string(),
+        "totalSpent": state.total_spent,
+        "spendThreshold": state.spend_threshold,
+        "outputs": state
+            .output_yield_accounts
+            .iter()
+            .zip(&state.spend_proportions)
+            .map(|(account, proportion)| json!({
+                "outputYieldAccount": account.to_string(),
+                "proportion": proportion,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub async fn run(ctx: &Context, command: Command) -> Result<Value> {
+    let router = ctx.client.yield_router();
+    match command {
+        Command::Show => {
+            let (address, state) = router.state_for_sunrise_state(&ctx.sunrise_state).await?;
+            let mut output = state_json(&address, &state);
+            output["inputYieldAccountBalance"] = ctx
+                .balance(&pda::input_yield_account(&address))
+                .await?
+                .into();
+            Ok(output)
+        }
+        Command::Register(args) => {
+            let mut state_in = GenericStateInput {
+                update_authority: ctx.payer(),
+                output_yield_accounts: vec![],
+                spend_proportions: vec![],
+                spend_threshold: 0,
+            };
+            args.apply(&mut state_in);
+            ctx.execute(vec![yield_router::register_state(
+                &ctx.payer(),
+                &ctx.sunrise_state,
+                state_in,
+            )])
+            .await
+        }
+        Command::Update(args) => {
+            let (address, state) = router.state_for_sunrise_state(&ctx.sunrise_state).await?;
+            let mut state_in = GenericStateInput {
+                update_authority: state.update_authority,
+                output_yield_accounts: state.output_yield_accounts,
+                spend_proportions: state.spend_proportions,
+                spend_threshold: state.spend_threshold,
+            };
+            args.apply(&mut state_in);
+            ctx.execute(vec![yield_router::update_state(
+                &ctx.payer(),
+                &address,
+                state_in,
+            )])
+            .await
+        }
+        Command::Allocate { amount } => {
+            let (address, state) = router.state_for_sunrise_state(&ctx.sunrise_state).await?;
+            let balance = ctx.balance(&pda::input_yield_account(&address)).await?;
+            let amount = amount.unwrap_or_else(|| spendable(balance));
+            if amount == 0 {
+                bail!("no yield to allocate");
+            }
+            ctx.execute(vec![yield_router::allocate_yield(
+                &ctx.payer(),
+                &address,
+                &state,
+                amount,
+            )])
+            .await
+        }
+    }
+}
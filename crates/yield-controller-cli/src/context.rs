@@ -0,0 +1,158 @@
+use crate::offline::{self, OfflineArgs, Signers};
+use anyhow::{bail, Context as _, Result};
+use serde_json::{json, Value};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use yield_controller_client::offline::SignOnly;
+use yield_controller_client::{BlockhashQuery, Client};
+
+/// The cluster, signers and mode the commands run with
+pub struct Context {
+    pub client: Client<RpcClient>,
+    // the keypair, which signs as the payer or authority of the instructions
+    payer: Pubkey,
+    fee_payer: Pubkey,
+    signers: Signers,
+    blockhash_query: BlockhashQuery,
+    pub sunrise_state: Pubkey,
+    // simulate transactions instead of sending them
+    pub dry_run: bool,
+    // sign transactions and print the signatures instead of sending them
+    pub sign_only: bool,
+    dump_transaction_message: bool,
+}
+
+impl Context {
+    pub fn new(
+        url: &str,
+        keypair: &str,
+        sunrise_state: Pubkey,
+        dry_run: bool,
+        offline: OfflineArgs,
+    ) -> Result<Self> {
+        let mut signers = Signers::new(offline.signers.clone(), offline.sign_only);
+        let payer = signers.add(keypair)?;
+        let fee_payer = match &offline.fee_payer {
+            Some(fee_payer) => signers.add(fee_payer)?,
+            None => payer,
+        };
+        let nonce_authority = match &offline.nonce_authority {
+            Some(nonce_authority) => signers.add(nonce_authority)?,
+            None => payer,
+        };
+        let rpc = RpcClient::new_with_commitment(normalize_url(url), CommitmentConfig::confirmed());
+        Ok(Self {
+            client: Client::new(rpc),
+            payer,
+            fee_payer,
+            signers,
+            blockhash_query: offline::blockhash_query(&offline, nonce_authority),
+            sunrise_state,
+            dry_run,
+            sign_only: offline.sign_only,
+            dump_transaction_message: offline.dump_transaction_message,
+        })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        self.client.rpc()
+    }
+
+    /// Send `instructions` in one transaction paid by the fee payer, simulate them in a dry run,
+    /// failing if the simulation fails, or with `--sign-only` print the local signers' signatures.
+    pub async fn execute(&self, instructions: Vec<Instruction>) -> Result<Value> {
+        let transaction = self
+            .client
+            .transaction(&instructions, &self.fee_payer, &self.blockhash_query)
+            .await?;
+        if self.sign_only {
+            self.sign_only(transaction.message)
+        } else {
+            self.submit(transaction.message).await
+        }
+    }
+
+    /// Sign a transaction message with the local signers, reporting the signatures
+    /// in the `solana` CLI's `--sign-only` format.
+    pub fn sign_only(&self, message: Message) -> Result<Value> {
+        let transaction = self.signers.sign(message)?;
+        let message = self
+            .dump_transaction_message
+            .then_some(&transaction.message);
+        Ok(offline::sign_only_json(
+            &SignOnly::new(&transaction),
+            message,
+        ))
+    }
+
+    /// Sign a transaction message with the local signers and the `--signer` signatures and send it,
+    /// or simulate it in a dry run.
+    pub async fn submit(&self, message: Message) -> Result<Value> {
+        if self.dry_run {
+            let simulation = self
+                .client
+                .simulate_transaction(&Transaction::new_unsigned(message))
+                .await?;
+            if let Some(err) = simulation.err {
+                bail!("simulation failed: {}\n{}", err, simulation.logs.join("\n"));
+            }
+            return Ok(json!({
+                "dryRun": true,
+                "unitsConsumed": simulation.units_consumed,
+                "logs": simulation.logs,
+            }));
+        }
+
+        let transaction = self.signers.sign(message)?;
+        let sign_only = SignOnly::new(&transaction);
+        if !sign_only.bad_sig.is_empty() {
+            bail!(
+                "signatures do not match the transaction: {:?}",
+                sign_only.bad_sig
+            );
+        }
+        if !sign_only.absent.is_empty() {
+            bail!(
+                "transaction is missing signatures of {:?}",
+                sign_only.absent
+            );
+        }
+        let signature = self
+            .client
+            .send_transaction(&transaction)
+            .await
+            .context("transaction failed")?;
+        Ok(json!({ "signature": signature.to_string() }))
+    }
+
+    /// The lamports held by `address`, or zero if it does not exist.
+    pub async fn balance(&self, address: &Pubkey) -> Result<u64> {
+        Ok(self.rpc().get_balance(address).await?)
+    }
+}
+
+/// Expand the `solana` CLI's cluster monikers to their URLs
+pub fn normalize_url(url: &str) -> String {
+    match url {
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "l" | "localhost" => "http://localhost:8899",
+        url => url,
+    }
+    .to_string()
+}
+
+/// The `solana` CLI's default keypair path
+pub fn default_keypair_path() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    format!("{}/.config/solana/id.json", home)
+}
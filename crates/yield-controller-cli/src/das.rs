@@ -0,0 +1,125 @@
+//! Reads of compressed NFTs through the Metaplex DAS API, which the RPC node must support
+
+use anyhow::{anyhow, Context as _, Result};
+use serde_json::{json, Value};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::RpcRequest;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use yield_controller_client::fund_sender::{CNFTCertificate, CNFTTreeAccounts, CertificateCreator};
+
+/// A compressed certificate, ready to be stored, with its proof
+pub struct CNFTLeaf {
+    pub tree: CNFTTreeAccounts,
+    pub certificate: CNFTCertificate,
+    pub proof: Vec<Pubkey>,
+}
+
+async fn call(rpc: &RpcClient, method: &'static str, params: Value) -> Result<Value> {
+    rpc.send(RpcRequest::Custom { method }, params)
+        .await
+        .with_context(|| format!("{} failed - does the RPC node support the DAS API?", method))
+}
+
+fn field<'a>(value: &'a Value, path: &[&str]) -> Result<&'a Value> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .ok_or_else(|| anyhow!("DAS response is missing {}", path.join(".")))
+}
+
+fn string_field<'a>(value: &'a Value, path: &[&str]) -> Result<&'a str> {
+    field(value, path)?
+        .as_str()
+        .ok_or_else(|| anyhow!("DAS response field {} is not a string", path.join(".")))
+}
+
+fn hash_field(value: &Value, path: &[&str]) -> Result<[u8; 32]> {
+    let bytes = bs58::decode(string_field(value, path)?).into_vec()?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("DAS response field {} is not a hash", path.join(".")))
+}
+
+fn pubkey_field(value: &Value, path: &[&str]) -> Result<Pubkey> {
+    Ok(Pubkey::from_str(string_field(value, path)?)?)
+}
+
+/// The ids of the compressed NFTs owned by `owner`
+pub async fn compressed_assets_by_owner(rpc: &RpcClient, owner: &Pubkey) -> Result<Vec<Pubkey>> {
+    let response = call(
+        rpc,
+        "getAssetsByOwner",
+        json!({ "ownerAddress": owner.to_string(), "page": 1, "limit": 1000 }),
+    )
+    .await?;
+    field(&response, &["items"])?
+        .as_array()
+        .ok_or_else(|| anyhow!("DAS response items is not a list"))?
+        .iter()
+        .filter(|asset| asset["compression"]["compressed"].as_bool() == Some(true))
+        .map(|asset| pubkey_field(asset, &["id"]))
+        .collect()
+}
+
+/// The leaf of a compressed NFT, with its proof less the `canopy_depth` nodes stored in the tree
+pub async fn cnft_leaf(
+    rpc: &RpcClient,
+    asset_id: &Pubkey,
+    canopy_depth: usize,
+) -> Result<CNFTLeaf> {
+    let params = json!({ "id": asset_id.to_string() });
+    let asset = call(rpc, "getAsset", params.clone()).await?;
+    let asset_proof = call(rpc, "getAssetProof", params).await?;
+
+    let proof = field(&asset_proof, &["proof"])?
+        .as_array()
+        .ok_or_else(|| anyhow!("DAS response proof is not a list"))?
+        .iter()
+        .map(|node| {
+            node.as_str()
+                .ok_or_else(|| anyhow!("DAS response proof node is not a string"))
+                .and_then(|node| Ok(Pubkey::from_str(node)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let proof_len = proof.len().saturating_sub(canopy_depth);
+    let proof = proof[..proof_len].to_vec();
+
+    let leaf_delegate = match asset["ownership"]["delegated"].as_bool() {
+        Some(true) => Some(pubkey_field(&asset, &["ownership", "delegate"])?),
+        _ => None,
+    };
+    let nonce = field(&asset, &["compression", "leaf_id"])?
+        .as_u64()
+        .ok_or_else(|| anyhow!("DAS response leaf_id is not a number"))?;
+    let creators = field(&asset, &["creators"])?
+        .as_array()
+        .ok_or_else(|| anyhow!("DAS response creators is not a list"))?
+        .iter()
+        .map(|creator| {
+            Ok(CertificateCreator {
+                address: pubkey_field(creator, &["address"])?,
+                verified: creator["verified"].as_bool().unwrap_or_default(),
+                share: creator["share"].as_u64().unwrap_or_default() as u8,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CNFTLeaf {
+        tree: CNFTTreeAccounts {
+            merkle_tree: pubkey_field(&asset_proof, &["tree_id"])?,
+            leaf_delegate,
+        },
+        certificate: CNFTCertificate {
+            root: hash_field(&asset_proof, &["root"])?,
+            data_hash: hash_field(&asset, &["compression", "data_hash"])?,
+            creator_hash: hash_field(&asset, &["compression", "creator_hash"])?,
+            nonce,
+            index: nonce as u32,
+            creators,
+            // only needed when the state filters certificates by collection
+            metadata: None,
+            proof_len: proof.len() as u8,
+        },
+        proof,
+    })
+}
@@ -0,0 +1,127 @@
+//! `yc`: register, inspect, update and crank the yield controller programs
+
+mod commands;
+mod context;
+mod das;
+mod offline;
+mod output;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use commands::{
+    buy_burn_fixed, buy_burn_switchboard, fund_sender, status, transaction, yield_router,
+};
+use context::{default_keypair_path, Context};
+use offline::OfflineArgs;
+use output::OutputFormat;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Parser, Debug)]
+#[command(name = "yc", version, about)]
+struct Cli {
+    /// The RPC URL, or a moniker (mainnet-beta, devnet, testnet or localhost)
+    #[arg(
+        short,
+        long,
+        global = true,
+        env = "YC_URL",
+        default_value = "mainnet-beta"
+    )]
+    url: String,
+    /// The keypair that signs as the payer or authority of the instructions, or with offline
+    /// signing its pubkey
+    #[arg(short, long, global = true, default_value_t = default_keypair_path())]
+    keypair: String,
+    /// The Sunrise state the yield router and fund senders belong to
+    #[arg(
+        long,
+        global = true,
+        env = "STATE_ADDRESS",
+        default_value = "43m66crxGfXSJpmx5wXRoFuHubhHA1GCvtHgmHW6cM1P"
+    )]
+    sunrise_state: Pubkey,
+    /// Simulate transactions instead of sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+    #[arg(long, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+    #[command(flatten)]
+    offline: OfflineArgs,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the yield router, its output yield accounts and the fund senders they feed
+    Status {
+        /// The destination names of the fund senders to look for
+        #[arg(
+            long = "destination-name",
+            value_delimiter = ',',
+            default_value = "ecotoken,toucan,loompact"
+        )]
+        destination_names: Vec<String>,
+    },
+    /// Manage the yield router, which splits yield between output yield accounts
+    #[command(subcommand)]
+    YieldRouter(yield_router::Command),
+    /// Manage fund senders, which send yield to a destination and hold its certificates
+    #[command(subcommand)]
+    FundSender(fund_sender::Command),
+    /// Manage buy-burn states that buy tokens at a set price
+    #[command(subcommand)]
+    BuyBurnFixed(buy_burn_fixed::Command),
+    /// Manage buy-burn states that buy tokens at the Switchboard feeds' price
+    #[command(subcommand)]
+    BuyBurnSwitchboard(buy_burn_switchboard::Command),
+    /// Sign or send a transaction message built with --sign-only --dump-transaction-message
+    #[command(subcommand)]
+    Transaction(transaction::Command),
+}
+
+async fn run(cli: Cli) -> Result<Value> {
+    let mut offline = cli.offline;
+    offline.sign_only |=
+        matches!(&cli.command, Command::Transaction(command) if command.is_sign_only());
+    let ctx = Context::new(
+        &cli.url,
+        &cli.keypair,
+        cli.sunrise_state,
+        cli.dry_run,
+        offline,
+    )?;
+    match cli.command {
+        Command::Status { destination_names } => status::run(&ctx, &destination_names).await,
+        Command::YieldRouter(command) => yield_router::run(&ctx, command).await,
+        Command::FundSender(command) => fund_sender::run(&ctx, command).await,
+        Command::BuyBurnFixed(command) => buy_burn_fixed::run(&ctx, command).await,
+        Command::BuyBurnSwitchboard(command) => buy_burn_switchboard::run(&ctx, command).await,
+        Command::Transaction(command) => transaction::run(&ctx, command).await,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let output = cli.output;
+    match run(cli).await {
+        Ok(value) => println!("{}", output.format(&value)),
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn verifies_cli() {
+        Cli::command().debug_assert();
+    }
+}
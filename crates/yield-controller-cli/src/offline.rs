@@ -0,0 +1,224 @@
+//! Offline signing, with the arguments and output of the `solana` CLI's:
+//!
+//! - `--sign-only --blockhash HASH` signs with the local keypairs and prints their signatures
+//!   instead of sending, so that an authority can sign on an air-gapped machine.
+//! - `--signer PUBKEY=SIGNATURE` adds a signature made offline, for a signer given by its pubkey.
+//! - `--nonce ACCOUNT` uses a durable nonce, which keeps the transaction valid until it is sent.
+//!
+//! A signer is given as a keypair file or, when its signature is made elsewhere, as a pubkey.
+
+use anyhow::{anyhow, bail, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use clap::Args;
+use serde_json::{json, Value};
+use solana_sdk::hash::Hash;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Signature};
+use solana_sdk::signer::null_signer::NullSigner;
+use solana_sdk::signer::presigner::Presigner;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::Transaction;
+use std::str::FromStr;
+use yield_controller_client::offline::SignOnly;
+use yield_controller_client::BlockhashQuery;
+
+#[derive(Args, Debug, Default)]
+pub struct OfflineArgs {
+    /// Sign the transaction with the local keypairs and print the signatures instead of sending it
+    #[arg(
+        long,
+        global = true,
+        requires = "blockhash",
+        conflicts_with = "dry_run"
+    )]
+    pub sign_only: bool,
+    /// With --sign-only, also print the transaction message, to be signed with `yc transaction sign`
+    #[arg(long, global = true, requires = "sign_only")]
+    pub dump_transaction_message: bool,
+    /// The blockhash to build the transaction with, or with --nonce the nonce stored in the account
+    #[arg(long, global = true)]
+    pub blockhash: Option<Hash>,
+    /// A signature made offline, as PUBKEY=SIGNATURE (repeatable)
+    #[arg(long = "signer", global = true, value_parser = parse_presigner)]
+    pub signers: Vec<(Pubkey, Signature)>,
+    /// The durable nonce account to build the transaction with
+    #[arg(long, global = true)]
+    pub nonce: Option<Pubkey>,
+    /// The keypair or pubkey of the nonce account's authority [default: the keypair]
+    #[arg(long, global = true, requires = "nonce")]
+    pub nonce_authority: Option<String>,
+    /// The keypair or pubkey that pays the transaction fee [default: the keypair]
+    #[arg(long, global = true)]
+    pub fee_payer: Option<String>,
+}
+
+/// Parse `PUBKEY=SIGNATURE`, as printed by `--sign-only`
+pub fn parse_presigner(s: &str) -> Result<(Pubkey, Signature)> {
+    let (pubkey, signature) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected PUBKEY=SIGNATURE, got {}", s))?;
+    Ok((Pubkey::from_str(pubkey)?, Signature::from_str(signature)?))
+}
+
+/// The signers the transactions are signed with, resolved from their sources
+pub struct Signers {
+    signers: Vec<Box<dyn Signer>>,
+    presigners: Vec<(Pubkey, Signature)>,
+    // whether signers given by pubkey alone may be left to sign elsewhere
+    sign_only: bool,
+}
+
+impl Signers {
+    pub fn new(presigners: Vec<(Pubkey, Signature)>, sign_only: bool) -> Self {
+        Self {
+            signers: vec![],
+            presigners,
+            sign_only,
+        }
+    }
+
+    /// Resolve a keypair file or pubkey to a signer, keeping it unless one
+    /// of the same pubkey was added before, and return its pubkey.
+    pub fn add(&mut self, source: &str) -> Result<Pubkey> {
+        let signer: Box<dyn Signer> = match Pubkey::from_str(source) {
+            Ok(pubkey) => match self.presigners.iter().find(|(key, _)| *key == pubkey) {
+                Some((pubkey, signature)) => Box::new(Presigner::new(pubkey, signature)),
+                None if self.sign_only => Box::new(NullSigner::new(&pubkey)),
+                None => bail!(
+                    "{} must sign: give its keypair, or its signature with --signer",
+                    pubkey
+                ),
+            },
+            Err(_) => Box::new(
+                read_keypair_file(source)
+                    .map_err(|err| anyhow!("could not read keypair {}: {}", source, err))?,
+            ),
+        };
+        let pubkey = signer.pubkey();
+        if !self.signers.iter().any(|signer| signer.pubkey() == pubkey) {
+            self.signers.push(signer);
+        }
+        Ok(pubkey)
+    }
+
+    /// Sign a message with every signer it requires that is known, including by a `--signer`
+    /// signature alone, leaving the others' signatures empty.
+    pub fn sign(&self, message: Message) -> Result<Transaction> {
+        let mut transaction = Transaction::new_unsigned(message);
+        let required = &transaction.message.account_keys
+            [..transaction.message.header.num_required_signatures as usize];
+        let presigners: Vec<Presigner> = self
+            .presigners
+            .iter()
+            .filter(|(pubkey, _)| !self.signers.iter().any(|signer| signer.pubkey() == *pubkey))
+            .map(|(pubkey, signature)| Presigner::new(pubkey, signature))
+            .collect();
+        let signers: Vec<&dyn Signer> = self
+            .signers
+            .iter()
+            .map(|signer| signer.as_ref())
+            .chain(presigners.iter().map(|signer| signer as &dyn Signer))
+            .filter(|signer| required.contains(&signer.pubkey()))
+            .collect();
+        let blockhash = transaction.message.recent_blockhash;
+        transaction.try_partial_sign(&signers, blockhash)?;
+        Ok(transaction)
+    }
+}
+
+/// Where the transactions' blockhash comes from, given the nonce authority's pubkey
+pub fn blockhash_query(args: &OfflineArgs, nonce_authority: Pubkey) -> BlockhashQuery {
+    match (args.nonce, args.blockhash) {
+        (Some(account), blockhash) => BlockhashQuery::Nonce {
+            account,
+            authority: nonce_authority,
+            blockhash,
+        },
+        (None, Some(blockhash)) => BlockhashQuery::Static(blockhash),
+        (None, None) => BlockhashQuery::Latest,
+    }
+}
+
+/// The `--sign-only` output of the `solana` CLI, whose `signers` can be passed back as `--signer`s
+pub fn sign_only_json(sign_only: &SignOnly, message: Option<&Message>) -> Value {
+    let mut output = json!({ "blockhash": sign_only.blockhash.to_string() });
+    if let Some(message) = message {
+        output["message"] = encode_message(message).into();
+    }
+    let signers: Vec<String> = sign_only
+        .signers
+        .iter()
+        .map(|(pubkey, signature)| format!("{}={}", pubkey, signature))
+        .collect();
+    for (key, values) in [
+        ("signers", signers),
+        ("absent", to_strings(&sign_only.absent)),
+        ("badSig", to_strings(&sign_only.bad_sig)),
+    ] {
+        if !values.is_empty() {
+            output[key] = values.into();
+        }
+    }
+    output
+}
+
+fn to_strings(pubkeys: &[Pubkey]) -> Vec<String> {
+    pubkeys.iter().map(Pubkey::to_string).collect()
+}
+
+/// A transaction message as base64, as `--dump-transaction-message` prints it
+pub fn encode_message(message: &Message) -> String {
+    BASE64_STANDARD.encode(message.serialize())
+}
+
+pub fn decode_message(s: &str) -> Result<Message> {
+    let bytes = BASE64_STANDARD
+        .decode(s)
+        .map_err(|err| anyhow!("invalid transaction message: {}", err))?;
+    bincode::deserialize(&bytes).map_err(|err| anyhow!("invalid transaction message: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn signs_offline_and_online() {
+        let fee_payer = Keypair::new();
+        let authority = Keypair::new();
+        let instruction =
+            system_instruction::transfer(&authority.pubkey(), &Pubkey::new_unique(), 1);
+        let mut message = Message::new(&[instruction], Some(&fee_payer.pubkey()));
+        message.recent_blockhash = Hash::new_unique();
+        let message = decode_message(&encode_message(&message)).unwrap();
+
+        // offline, the authority signs for itself and leaves the fee payer absent
+        let authority_path = std::env::temp_dir().join(format!("{}.json", authority.pubkey()));
+        solana_sdk::signature::write_keypair_file(&authority, &authority_path).unwrap();
+        let mut offline = Signers::new(vec![], true);
+        offline.add(authority_path.to_str().unwrap()).unwrap();
+        offline.add(&fee_payer.pubkey().to_string()).unwrap();
+        let sign_only = SignOnly::new(&offline.sign(message.clone()).unwrap());
+        std::fs::remove_file(authority_path).unwrap();
+        let output = sign_only_json(&sign_only, None);
+        assert_eq!(output["absent"], json!([fee_payer.pubkey().to_string()]));
+        let signer = output["signers"][0].as_str().unwrap();
+
+        // online, the authority is given by its pubkey and offline signature
+        let presigner = parse_presigner(signer).unwrap();
+        assert!(Signers::new(vec![], false)
+            .add(&authority.pubkey().to_string())
+            .is_err());
+        let mut online = Signers::new(vec![presigner], false);
+        online.add(&authority.pubkey().to_string()).unwrap();
+        let fee_payer_path = std::env::temp_dir().join(format!("{}.json", fee_payer.pubkey()));
+        solana_sdk::signature::write_keypair_file(&fee_payer, &fee_payer_path).unwrap();
+        online.add(fee_payer_path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(fee_payer_path).unwrap();
+        let transaction = online.sign(message).unwrap();
+        assert!(SignOnly::new(&transaction).is_complete());
+    }
+}
@@ -0,0 +1,95 @@
+use clap::ValueEnum;
+use serde_json::Value;
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Indented `key: value` lines
+    #[default]
+    Display,
+    Json,
+    /// JSON on a single line
+    JsonCompact,
+}
+
+impl OutputFormat {
+    pub fn format(self, value: &Value) -> String {
+        match self {
+            OutputFormat::Display => {
+                let mut out = String::new();
+                display(&mut out, value, 0);
+                out.trim_end().to_string()
+            }
+            OutputFormat::Json => serde_json::to_string_pretty(value).unwrap(),
+            OutputFormat::JsonCompact => value.to_string(),
+        }
+    }
+}
+
+fn display(out: &mut String, value: &Value, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                match value {
+                    Value::Object(_) | Value::Array(_) if !is_empty(value) => {
+                        writeln!(out, "{}{}:", indent, key).unwrap();
+                        display(out, value, depth + 1);
+                    }
+                    _ => writeln!(out, "{}{}: {}", indent, key, scalar(value)).unwrap(),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                match item {
+                    Value::Object(_) | Value::Array(_) => {
+                        writeln!(out, "{}[{}]", indent, i).unwrap();
+                        display(out, item, depth + 1);
+                    }
+                    _ => writeln!(out, "{}- {}", indent, scalar(item)).unwrap(),
+                }
+            }
+        }
+        value => writeln!(out, "{}{}", indent, scalar(value)).unwrap(),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Object(fields) => fields.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn displays_nested_values() {
+        let value = json!({
+            "state": "abc",
+            "proportions": [30, 70],
+            "destinations": [{ "name": "toucan", "balance": 1 }],
+            "vault": null,
+            "records": [],
+        });
+        assert_eq!(
+            OutputFormat::Display.format(&value),
+            "state: abc\nproportions:\n  - 30\n  - 70\ndestinations:\n  [0]\n    name: toucan\n    balance: 1\nvault: -\nrecords: []"
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{pda, Client, Result, Rpc, SwapPoolAccounts};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, spl_token::native_mint};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+pub use ::buy_burn_fixed::{
+    accounts, instruction, AllocateYieldInput, ErrorCode, GenericStateInput, LegacyState,
+    MigrateStateInput, PriceRecord, RetirementBackend, RetirementReceipt, State, SwapAndBurnInput,
+    ID, PRICE_CHANGE_WINDOW,
+};
+
+// the retirement vault and the state's next receipt, when the state retires into a vault
+fn retirement_accounts(state_address: &Pubkey, state: &State) -> (Option<Pubkey>, Option<Pubkey>) {
+    match state.retirement_backend {
+        RetirementBackend::Burn => (None, None),
+        RetirementBackend::Vault => (
+            Some(state.retirement_vault),
+            Some(pda::retirement_receipt(
+                &ID,
+                state_address,
+                state.total_retirements,
+            )),
+        ),
+    }
+}
+
+pub fn register_state(payer: &Pubkey, state_in: GenericStateInput) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::RegisterState {
+            payer: *payer,
+            yield_account: pda::buy_burn_state(&ID, &state_in.mint, state_in.index),
+            mint: state_in.mint,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterState { state: state_in }.data(),
+    }
+}
+
+/// `payer` must be the state's update authority.
+pub fn update_state(payer: &Pubkey, state_in: GenericStateInput) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::UpdateState {
+            payer: *payer,
+            yield_account: pda::buy_burn_state(&ID, &state_in.mint, state_in.index),
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateState { state: state_in }.data(),
+    }
+}
+
+/// Move the state of `mint` registered before states were indexed to the state at `args.index`.
+/// `payer` must be the legacy state's update authority.
+pub fn migrate_state(payer: &Pubkey, mint: &Pubkey, args: MigrateStateInput) -> Instruction {
+    let state = pda::buy_burn_state(&ID, mint, args.index);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::MigrateState {
+            payer: *payer,
+            legacy_state: pda::legacy_buy_burn_fixed_state(mint),
+            state,
+            yield_account: pda::yield_account(&ID, &state),
+            mint: *mint,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigrateState { args }.data(),
+    }
+}
+
+/// `payer` must be the state's update authority.
+pub fn update_price(payer: &Pubkey, state: &Pubkey, price: u64) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::UpdatePrice {
+            state: *state,
+            payer: *payer,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdatePrice { price }.data(),
+    }
+}
+
+/// Retire the tokens bought for `args.sol_amount` at the state's price from the holding token account,
+/// paying the state's treasury and holding account from its yield account.
+pub fn allocate_yield(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    args: AllocateYieldInput,
+) -> Instruction {
+    let (retirement_vault, retirement_receipt) = retirement_accounts(state_address, state);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::AllocateYield {
+            payer: *payer,
+            state: *state_address,
+            yield_account: pda::yield_account(&ID, state_address),
+            mint: state.mint,
+            treasury: state.treasury,
+            holding_account: state.holding_account,
+            holding_token_account: state.holding_token_account,
+            retirement_vault,
+            retirement_receipt,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AllocateYield { args }.data(),
+    }
+}
+
+/// Swap `args.sol_amount` (less the treasury's share) through `pool` and retire the tokens bought.
+/// The state's wrapped SOL and token accounts are its ATAs.
+pub fn swap_and_burn(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    pool: &SwapPoolAccounts,
+    args: SwapAndBurnInput,
+) -> Instruction {
+    let (retirement_vault, retirement_receipt) = retirement_accounts(state_address, state);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SwapAndBurn {
+            payer: *payer,
+            state: *state_address,
+            yield_account: pda::yield_account(&ID, state_address),
+            mint: state.mint,
+            treasury: state.treasury,
+            wrapped_sol_account: get_associated_token_address(state_address, &native_mint::ID),
+            purchased_token_account: get_associated_token_address(state_address, &state.mint),
+            swap_program: state.swap_program,
+            swap_pool: state.swap_pool,
+            swap_pool_authority: pool.authority,
+            swap_pool_source: pool.source,
+            swap_pool_destination: pool.destination,
+            swap_pool_mint: pool.pool_mint,
+            swap_pool_fee_account: pool.fee_account,
+            retirement_vault,
+            retirement_receipt,
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SwapAndBurn { args }.data(),
+    }
+}
+
+/// Requests against the buy-burn-fixed program
+pub struct BuyBurnFixed<'a, R> {
+    client: &'a Client<R>,
+}
+
+impl<R: Rpc> Client<R> {
+    pub fn buy_burn_fixed(&self) -> BuyBurnFixed<'_, R> {
+        BuyBurnFixed { client: self }
+    }
+}
+
+impl<R: Rpc> BuyBurnFixed<'_, R> {
+    pub async fn state(&self, state: &Pubkey) -> Result<State> {
+        self.client.fetch(state).await
+    }
+
+    /// The state of `mint` at `index`, along with its address.
+    pub async fn state_for_mint(&self, mint: &Pubkey, index: u8) -> Result<(Pubkey, State)> {
+        let address = pda::buy_burn_state(&ID, mint, index);
+        Ok((address, self.state(&address).await?))
+    }
+
+    /// The state's retirement receipts, in the order they were issued.
+    pub async fn retirement_receipts(
+        &self,
+        state: &Pubkey,
+    ) -> Result<Vec<(Pubkey, RetirementReceipt)>> {
+        let total_retirements = self.state(state).await?.total_retirements;
+        let mut receipts = Vec::with_capacity(total_retirements as usize);
+        for index in 0..total_retirements {
+            let address = pda::retirement_receipt(&ID, state, index);
+            receipts.push((address, self.client.fetch(&address).await?));
+        }
+        Ok(receipts)
+    }
+
+    pub async fn allocate_yield(
+        &self,
+        payer: &Pubkey,
+        state: &Pubkey,
+        args: AllocateYieldInput,
+    ) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(allocate_yield(payer, state, &state_account, args))
+    }
+
+    pub async fn swap_and_burn(
+        &self,
+        payer: &Pubkey,
+        state: &Pubkey,
+        pool: &SwapPoolAccounts,
+        args: SwapAndBurnInput,
+    ) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(swap_and_burn(payer, state, &state_account, pool, args))
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{pda, Client, Result, Rpc, SwapPoolAccounts};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, spl_token::native_mint};
+use mpl_bubblegum::accounts::TreeConfig;
+use mpl_bubblegum::programs::{MPL_BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+pub use ::buy_burn_switchboard::{
+    accounts, instruction, Adjustment, AdjustmentLog, AdjustmentReason, ErrorCode,
+    GenericStateInput, LegacyState, RetirementBackend, RetirementReceipt, State, ID,
+};
+
+// the retirement vault and the state's next receipt, when the state retires into a vault
+fn retirement_accounts(state_address: &Pubkey, state: &State) -> (Option<Pubkey>, Option<Pubkey>) {
+    match state.retirement_backend {
+        RetirementBackend::Burn => (None, None),
+        RetirementBackend::Vault => (
+            Some(state.retirement_vault),
+            Some(pda::retirement_receipt(
+                &ID,
+                state_address,
+                state.total_retirements,
+            )),
+        ),
+    }
+}
+
+pub fn register_state(payer: &Pubkey, state_in: GenericStateInput) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::RegisterState {
+            payer: *payer,
+            state: pda::buy_burn_state(&ID, &state_in.mint, state_in.index),
+            mint: state_in.mint,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterState { state: state_in }.data(),
+    }
+}
+
+/// `payer` must be the state's update authority.
+pub fn update_state(payer: &Pubkey, state_in: GenericStateInput) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::UpdateState {
+            payer: *payer,
+            state: pda::buy_burn_state(&ID, &state_in.mint, state_in.index),
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateState { state: state_in }.data(),
+    }
+}
+
+/// Upgrade a state registered before swaps, vault retirement and burn receipts in place.
+/// `payer` must be the state's update authority.
+pub fn migrate_state(payer: &Pubkey, state: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::MigrateState {
+            payer: *payer,
+            state: *state,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigrateState {}.data(),
+    }
+}
+
+/// Correct the state's total tokens purchased by `delta`, recording it in the state's adjustment log.
+/// `payer` must be the state's update authority.
+pub fn adjust_total_tokens_purchased(
+    payer: &Pubkey,
+    state: &Pubkey,
+    delta: i64,
+    reason: AdjustmentReason,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::AdjustTotalTokensPurchased {
+            payer: *payer,
+            state: *state,
+            adjustment_log: pda::adjustment_log(state),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AdjustTotalTokensPurchased { delta, reason }.data(),
+    }
+}
+
+/// Retire the tokens the yield account's balance buys at the price feeds' price
+/// from the holding token account, minting a receipt if the state has a receipt tree.
+pub fn allocate_yield(payer: &Pubkey, state_address: &Pubkey, state: &State) -> Instruction {
+    let (retirement_vault, retirement_receipt) = retirement_accounts(state_address, state);
+    let with_receipt = state.receipt_merkle_tree != Pubkey::default();
+    Instruction {
+        program_id: ID,
+        accounts: accounts::AllocateYield {
+            payer: *payer,
+            state: *state_address,
+            yield_account: pda::yield_account(&ID, state_address),
+            mint: state.mint,
+            treasury: state.treasury,
+            holding_account: state.holding_account,
+            holding_token_account: state.holding_token_account,
+            sol_usd_price_feed: state.sol_usd_price_feed,
+            nct_usd_price_feed: state.nct_usd_price_feed,
+            retirement_vault,
+            retirement_receipt,
+            receipt_tree_config: with_receipt
+                .then(|| TreeConfig::find_pda(&state.receipt_merkle_tree).0),
+            receipt_merkle_tree: with_receipt.then_some(state.receipt_merkle_tree),
+            receipt_vault: with_receipt.then_some(state.receipt_vault),
+            log_wrapper: with_receipt.then_some(SPL_NOOP_ID),
+            compression_program: with_receipt.then_some(SPL_ACCOUNT_COMPRESSION_ID),
+            bubblegum_program: with_receipt.then_some(MPL_BUBBLEGUM_ID),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AllocateYield {}.data(),
+    }
+}
+
+/// Swap the yield account's balance (less the treasury's share) through `pool` and retire
+/// the tokens bought, minting a receipt if the state has a receipt tree.
+/// The state's wrapped SOL and token accounts are its ATAs.
+pub fn swap_and_burn(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    pool: &SwapPoolAccounts,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let (retirement_vault, retirement_receipt) = retirement_accounts(state_address, state);
+    let with_receipt = state.receipt_merkle_tree != Pubkey::default();
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SwapAndBurn {
+            payer: *payer,
+            state: *state_address,
+            yield_account: pda::yield_account(&ID, state_address),
+            mint: state.mint,
+            treasury: state.treasury,
+            wrapped_sol_account: get_associated_token_address(state_address, &native_mint::ID),
+            purchased_token_account: get_associated_token_address(state_address, &state.mint),
+            sol_usd_price_feed: state.sol_usd_price_feed,
+            nct_usd_price_feed: state.nct_usd_price_feed,
+            swap_program: state.swap_program,
+            swap_pool: state.swap_pool,
+            swap_pool_authority: pool.authority,
+            swap_pool_source: pool.source,
+            swap_pool_destination: pool.destination,
+            swap_pool_mint: pool.pool_mint,
+            swap_pool_fee_account: pool.fee_account,
+            retirement_vault,
+            retirement_receipt,
+            receipt_tree_config: with_receipt
+                .then(|| TreeConfig::find_pda(&state.receipt_merkle_tree).0),
+            receipt_merkle_tree: with_receipt.then_some(state.receipt_merkle_tree),
+            receipt_vault: with_receipt.then_some(state.receipt_vault),
+            log_wrapper: with_receipt.then_some(SPL_NOOP_ID),
+            compression_program: with_receipt.then_some(SPL_ACCOUNT_COMPRESSION_ID),
+            bubblegum_program: with_receipt.then_some(MPL_BUBBLEGUM_ID),
+            token_program: token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SwapAndBurn { minimum_amount_out }.data(),
+    }
+}
+
+/// Requests against the buy-burn-switchboard program
+pub struct BuyBurnSwitchboard<'a, R> {
+    client: &'a Client<R>,
+}
+
+impl<R: Rpc> Client<R> {
+    pub fn buy_burn_switchboard(&self) -> BuyBurnSwitchboard<'_, R> {
+        BuyBurnSwitchboard { client: self }
+    }
+}
+
+impl<R: Rpc> BuyBurnSwitchboard<'_, R> {
+    pub async fn state(&self, state: &Pubkey) -> Result<State> {
+        self.client.fetch(state).await
+    }
+
+    /// The state of `mint` at `index`, along with its address.
+    pub async fn state_for_mint(&self, mint: &Pubkey, index: u8) -> Result<(Pubkey, State)> {
+        let address = pda::buy_burn_state(&ID, mint, index);
+        Ok((address, self.state(&address).await?))
+    }
+
+    /// The state's log of manual adjustments, if it has been adjusted.
+    pub async fn adjustment_log(&self, state: &Pubkey) -> Result<Option<AdjustmentLog>> {
+        self.client
+            .fetch_optional(&pda::adjustment_log(state))
+            .await
+    }
+
+    /// The state's retirement receipts, in the order they were issued.
+    pub async fn retirement_receipts(
+        &self,
+        state: &Pubkey,
+    ) -> Result<Vec<(Pubkey, RetirementReceipt)>> {
+        let total_retirements = self.state(state).await?.total_retirements;
+        let mut receipts = Vec::with_capacity(total_retirements as usize);
+        for index in 0..total_retirements {
+            let address = pda::retirement_receipt(&ID, state, index);
+            receipts.push((address, self.client.fetch(&address).await?));
+        }
+        Ok(receipts)
+    }
+
+    pub async fn allocate_yield(&self, payer: &Pubkey, state: &Pubkey) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(allocate_yield(payer, state, &state_account))
+    }
+
+    pub async fn swap_and_burn(
+        &self,
+        payer: &Pubkey,
+        state: &Pubkey,
+        pool: &SwapPoolAccounts,
+        minimum_amount_out: u64,
+    ) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(swap_and_burn(
+            payer,
+            state,
+            &state_account,
+            pool,
+            minimum_amount_out,
+        ))
+    }
+}
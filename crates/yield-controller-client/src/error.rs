@@ -0,0 +1,26 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::SignerError;
+use solana_sdk::transaction::TransactionError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account {0} could not be deserialized: {1}")]
+    AccountDidNotDeserialize(Pubkey, anchor_lang::error::Error),
+
+    #[error("Account {0} is not an initialized nonce account")]
+    InvalidNonceAccount(Pubkey),
+
+    #[error("Transaction failed: {0}")]
+    Transaction(#[from] TransactionError),
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,637 @@
+use crate::{pda, Client, Result, Rpc, SwapPoolAccounts};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{
+    self, get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+use anchor_spl::token::{self, spl_token::native_mint};
+use mpl_bubblegum::accounts::TreeConfig;
+use mpl_bubblegum::programs::{MPL_BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID, SPL_NOOP_ID};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+pub use ::fund_sender::{
+    accounts, instruction, CNFTCertificate, CertificateCollection, CertificateCreator,
+    CertificateMetadata, CertificateRecord, CertificateUses, ErrorCode, GenericStateInput,
+    LegacyState, State, ID, MAX_CNFT_BATCH_SIZE, MPL_CORE_ID,
+};
+
+/// The certificate being stored, and who it is recorded against
+#[derive(Clone, Copy, Debug)]
+pub struct StoreCertificateAccounts {
+    // the state's certificate vault, or its quarantine vault for certificates not matching its filter
+    pub certificate_vault: Pubkey,
+    // the beneficiary the certificate is recorded against, if not the state's certificate beneficiary.
+    // Naming one requires the payer to be the state's update authority
+    pub beneficiary: Option<Pubkey>,
+}
+
+/// A compressed certificate's tree and its leaf delegate, if not the input account
+#[derive(Clone, Copy, Debug)]
+pub struct CNFTTreeAccounts {
+    pub merkle_tree: Pubkey,
+    pub leaf_delegate: Option<Pubkey>,
+}
+
+pub fn register_state(
+    payer: &Pubkey,
+    sunrise_state: &Pubkey,
+    state_in: GenericStateInput,
+) -> Instruction {
+    let state = pda::fund_sender_state(&state_in.destination_name, sunrise_state);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::RegisterState {
+            payer: *payer,
+            state,
+            input_account: pda::input_account(&state),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterState {
+            sunrise_state: *sunrise_state,
+            state_in,
+        }
+        .data(),
+    }
+}
+
+/// `payer` must be the state's update authority.
+pub fn update_state(payer: &Pubkey, state: &Pubkey, state_in: GenericStateInput) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::UpdateState {
+            payer: *payer,
+            state: *state,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateState { state_in }.data(),
+    }
+}
+
+/// Upgrade a state registered with the original layout in place.
+/// `payer` must be the state's update authority.
+pub fn migrate_state(payer: &Pubkey, state: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::MigrateState {
+            payer: *payer,
+            state: *state,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigrateState {}.data(),
+    }
+}
+
+/// Move a state to `new_destination_name`, along with the token balances of its input account.
+/// `token_account_pairs` are the old input account's token accounts, each with the new input account's
+/// token account of the same mint; the old ones are closed. They must include the old input account's
+/// token account for the state's token if it holds a balance, as the old input account cannot sign
+/// once the state is closed. `payer` must be the state's update authority,
+/// and the state must not have stored certificates.
+pub fn migrate_destination(
+    payer: &Pubkey,
+    old_state_address: &Pubkey,
+    old_state: &State,
+    new_destination_name: String,
+    token_account_pairs: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let new_state = pda::fund_sender_state(&new_destination_name, &old_state.sunrise_state);
+    let old_input_account = pda::input_account(old_state_address);
+    let mut accounts = accounts::MigrateDestination {
+        payer: *payer,
+        old_state: *old_state_address,
+        old_input_account,
+        old_token_mint_account: get_associated_token_address(
+            &old_input_account,
+            &old_state.token_mint,
+        ),
+        new_state,
+        new_input_account: pda::input_account(&new_state),
+        system_program: system_program::ID,
+        token_program: token::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        token_account_pairs
+            .iter()
+            .flat_map(|(old, new)| [AccountMeta::new(*old, false), AccountMeta::new(*new, false)]),
+    );
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: instruction::MigrateDestination {
+            new_destination_name,
+        }
+        .data(),
+    }
+}
+
+/// Move any lamports sent to the state account itself to its input account.
+pub fn send_from_state(state: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SendFromState {
+            state: *state,
+            input_account: pda::input_account(state),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SendFromState {}.data(),
+    }
+}
+
+// the accounts of send_fund and send_all_funds, with any weighted destinations after the first
+fn send_fund_accounts(payer: &Pubkey, state_address: &Pubkey, state: &State) -> Vec<AccountMeta> {
+    let mut accounts = accounts::SendFund {
+        payer: *payer,
+        state: *state_address,
+        input_account: pda::input_account(state_address),
+        destination_account: state.destination_account,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        state
+            .destination_accounts
+            .iter()
+            .skip(1)
+            .map(|destination| AccountMeta::new(*destination, false)),
+    );
+    accounts
+}
+
+/// Send `amount` lamports from the input account to the state's destinations.
+pub fn send_fund(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: send_fund_accounts(payer, state_address, state),
+        data: instruction::SendFund { amount }.data(),
+    }
+}
+
+/// Send the whole spendable balance of the input account to the state's destinations.
+pub fn send_all_funds(payer: &Pubkey, state_address: &Pubkey, state: &State) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: send_fund_accounts(payer, state_address, state),
+        data: instruction::SendAllFunds {}.data(),
+    }
+}
+
+/// Send the input account's balance of the state's token to the destination account.
+pub fn send_token_fund(payer: &Pubkey, state_address: &Pubkey, state: &State) -> Instruction {
+    let input_account = pda::input_account(state_address);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SendTokenFund {
+            payer: *payer,
+            state: *state_address,
+            input_account,
+            token_mint: state.token_mint,
+            input_token_account: get_associated_token_address(&input_account, &state.token_mint),
+            destination_account: state.destination_account,
+            destination_token_account: get_associated_token_address(
+                &state.destination_account,
+                &state.token_mint,
+            ),
+            system_program: system_program::ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SendTokenFund {}.data(),
+    }
+}
+
+/// Swap `amount` lamports from the input account to the state's token through `pool`,
+/// sending at least `minimum_amount_out` tokens to the destination account.
+/// `payer` must be the state's update authority.
+pub fn swap_and_send(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    pool: &SwapPoolAccounts,
+    amount: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let input_account = pda::input_account(state_address);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::SwapAndSend {
+            payer: *payer,
+            state: *state_address,
+            input_account,
+            native_mint: native_mint::ID,
+            wrapped_sol_account: get_associated_token_address(&input_account, &native_mint::ID),
+            token_mint: state.token_mint,
+            destination_account: state.destination_account,
+            destination_token_account: get_associated_token_address(
+                &state.destination_account,
+                &state.token_mint,
+            ),
+            swap_program: state.swap_program,
+            swap_pool: state.swap_pool,
+            swap_pool_authority: pool.authority,
+            swap_pool_source: pool.source,
+            swap_pool_destination: pool.destination,
+            swap_pool_mint: pool.pool_mint,
+            swap_pool_fee_account: pool.fee_account,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::SwapAndSend {
+            amount,
+            minimum_amount_out,
+        }
+        .data(),
+    }
+}
+
+/// Store the input account's balance of `certificate_mint`, owned by `token_program`
+/// (SPL token or Token-2022), in the certificate vault. The certificate's metadata is passed
+/// if `with_metadata`, as the state needs it to check the certificate's collection or creator.
+pub fn store_certificates(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    certificate_mint: &Pubkey,
+    token_program: &Pubkey,
+    with_metadata: bool,
+    store: StoreCertificateAccounts,
+) -> Instruction {
+    let input_account = pda::input_account(state_address);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::StoreCertificates {
+            payer: *payer,
+            state: *state_address,
+            input_account,
+            certificate_mint: *certificate_mint,
+            input_token_account: get_associated_token_address_with_program_id(
+                &input_account,
+                certificate_mint,
+                token_program,
+            ),
+            certificate_vault: store.certificate_vault,
+            beneficiary: store.beneficiary,
+            certificate_vault_ata: get_associated_token_address_with_program_id(
+                &store.certificate_vault,
+                certificate_mint,
+                token_program,
+            ),
+            certificate_metadata: with_metadata.then(|| Metadata::find_pda(certificate_mint).0),
+            certificate_record: pda::certificate_record(state_address, state.total_certificates),
+            system_program: system_program::ID,
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::StoreCertificates {}.data(),
+    }
+}
+
+/// Release `amount` of `certificate_mint` from the state's certificate vault to `beneficiary`,
+/// attributing `certificate_record` to them if given. `payer` must be the state's update authority.
+pub fn release_certificate(
+    payer: &Pubkey,
+    state: &Pubkey,
+    certificate_mint: &Pubkey,
+    token_program: &Pubkey,
+    beneficiary: &Pubkey,
+    certificate_record: Option<Pubkey>,
+    amount: u64,
+) -> Instruction {
+    let certificate_vault = pda::certificate_vault(state);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::ReleaseCertificate {
+            payer: *payer,
+            state: *state,
+            certificate_vault,
+            certificate_mint: *certificate_mint,
+            certificate_vault_ata: get_associated_token_address_with_program_id(
+                &certificate_vault,
+                certificate_mint,
+                token_program,
+            ),
+            beneficiary: *beneficiary,
+            certificate_record,
+            beneficiary_ata: get_associated_token_address_with_program_id(
+                beneficiary,
+                certificate_mint,
+                token_program,
+            ),
+            system_program: system_program::ID,
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ReleaseCertificate { amount }.data(),
+    }
+}
+
+/// Release a Metaplex Core certificate from the state's certificate vault to `beneficiary`,
+/// attributing `certificate_record` to them if given. `payer` must be the state's update authority.
+pub fn release_core_certificate(
+    payer: &Pubkey,
+    state: &Pubkey,
+    certificate_asset: &Pubkey,
+    certificate_collection: Option<Pubkey>,
+    beneficiary: &Pubkey,
+    certificate_record: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::ReleaseCoreCertificate {
+            payer: *payer,
+            state: *state,
+            certificate_vault: pda::certificate_vault(state),
+            certificate_asset: *certificate_asset,
+            certificate_collection,
+            beneficiary: *beneficiary,
+            certificate_record,
+            system_program: system_program::ID,
+            core_program: MPL_CORE_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ReleaseCoreCertificate {}.data(),
+    }
+}
+
+/// Release a compressed certificate from the state's certificate vault to `beneficiary`,
+/// attributing `certificate_record` to them if given. `payer` must be the state's update authority.
+/// `certificate.creators`, `metadata` and `proof_len` are ignored, the whole of `proof` is passed.
+pub fn release_cnft_certificate(
+    payer: &Pubkey,
+    state: &Pubkey,
+    merkle_tree: &Pubkey,
+    beneficiary: &Pubkey,
+    certificate_record: Option<Pubkey>,
+    certificate: CNFTCertificate,
+    proof: &[Pubkey],
+) -> Instruction {
+    let mut accounts = accounts::ReleaseCNFTCertificate {
+        payer: *payer,
+        state: *state,
+        certificate_vault: pda::certificate_vault(state),
+        beneficiary: *beneficiary,
+        certificate_record,
+        tree_authority: TreeConfig::find_pda(merkle_tree).0,
+        merkle_tree: *merkle_tree,
+        system_program: system_program::ID,
+        log_wrapper: SPL_NOOP_ID,
+        compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+        bubblegum_program: MPL_BUBBLEGUM_ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node, false)),
+    );
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: instruction::ReleaseCnftCertificate {
+            root: certificate.root,
+            data_hash: certificate.data_hash,
+            creator_hash: certificate.creator_hash,
+            nonce: certificate.nonce,
+            index: certificate.index,
+        }
+        .data(),
+    }
+}
+
+/// Store a Metaplex Core certificate owned by the input account in the certificate vault.
+/// `certificate_collection` is required if the asset belongs to a collection.
+pub fn store_core_certificate(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    certificate_asset: &Pubkey,
+    certificate_collection: Option<Pubkey>,
+    store: StoreCertificateAccounts,
+) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::StoreCoreCertificate {
+            payer: *payer,
+            state: *state_address,
+            input_account: pda::input_account(state_address),
+            certificate_asset: *certificate_asset,
+            certificate_collection,
+            certificate_vault: store.certificate_vault,
+            beneficiary: store.beneficiary,
+            certificate_record: pda::certificate_record(state_address, state.total_certificates),
+            system_program: system_program::ID,
+            core_program: MPL_CORE_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::StoreCoreCertificate {}.data(),
+    }
+}
+
+/// Store a compressed certificate owned by the input account in the certificate vault.
+/// `certificate.proof_len` is ignored, the whole of `proof` is passed.
+pub fn store_cnft_certificate(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    tree: CNFTTreeAccounts,
+    store: StoreCertificateAccounts,
+    certificate: CNFTCertificate,
+    proof: &[Pubkey],
+) -> Instruction {
+    let mut accounts = accounts::StoreCNFTCertificates {
+        payer: *payer,
+        state: *state_address,
+        input_account: pda::input_account(state_address),
+        leaf_delegate: tree.leaf_delegate,
+        certificate_vault: store.certificate_vault,
+        beneficiary: store.beneficiary,
+        certificate_record: pda::certificate_record(state_address, state.total_certificates),
+        tree_authority: TreeConfig::find_pda(&tree.merkle_tree).0,
+        merkle_tree: tree.merkle_tree,
+        system_program: system_program::ID,
+        log_wrapper: SPL_NOOP_ID,
+        compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+        bubblegum_program: MPL_BUBBLEGUM_ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node, false)),
+    );
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: instruction::StoreCnftCertificate {
+            root: certificate.root,
+            data_hash: certificate.data_hash,
+            creator_hash: certificate.creator_hash,
+            nonce: certificate.nonce,
+            index: certificate.index,
+            creators: certificate.creators,
+            metadata: certificate.metadata,
+        }
+        .data(),
+    }
+}
+
+/// Store up to [`MAX_CNFT_BATCH_SIZE`] compressed certificates from the same tree, each with its proof.
+/// Each certificate's `proof_len` is set from its proof.
+pub fn store_cnft_certificates(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    tree: CNFTTreeAccounts,
+    store: StoreCertificateAccounts,
+    certificates: Vec<(CNFTCertificate, Vec<Pubkey>)>,
+) -> Instruction {
+    let mut accounts = accounts::StoreCNFTCertificateBatch {
+        payer: *payer,
+        state: *state_address,
+        input_account: pda::input_account(state_address),
+        leaf_delegate: tree.leaf_delegate,
+        certificate_vault: store.certificate_vault,
+        beneficiary: store.beneficiary,
+        tree_authority: TreeConfig::find_pda(&tree.merkle_tree).0,
+        merkle_tree: tree.merkle_tree,
+        system_program: system_program::ID,
+        log_wrapper: SPL_NOOP_ID,
+        compression_program: SPL_ACCOUNT_COMPRESSION_ID,
+        bubblegum_program: MPL_BUBBLEGUM_ID,
+    }
+    .to_account_metas(None);
+    // a record per certificate, at the ledger's next indices, then the proofs in order
+    accounts.extend((0..certificates.len() as u64).map(|i| {
+        AccountMeta::new(
+            pda::certificate_record(state_address, state.total_certificates + i),
+            false,
+        )
+    }));
+    accounts.extend(
+        certificates
+            .iter()
+            .flat_map(|(_, proof)| proof)
+            .map(|node| AccountMeta::new_readonly(*node, false)),
+    );
+
+    let certificates = certificates
+        .into_iter()
+        .map(|(certificate, proof)| CNFTCertificate {
+            proof_len: proof.len() as u8,
+            ..certificate
+        })
+        .collect();
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: instruction::StoreCnftCertificates { certificates }.data(),
+    }
+}
+
+/// Requests against the fund-sender program
+pub struct FundSender<'a, R> {
+    client: &'a Client<R>,
+}
+
+impl<R: Rpc> Client<R> {
+    pub fn fund_sender(&self) -> FundSender<'_, R> {
+        FundSender { client: self }
+    }
+}
+
+impl<R: Rpc> FundSender<'_, R> {
+    pub async fn state(&self, state: &Pubkey) -> Result<State> {
+        self.client.fetch(state).await
+    }
+
+    /// The state sending funds to `destination_name` for `sunrise_state`, along with its address.
+    pub async fn state_for_destination(
+        &self,
+        destination_name: &str,
+        sunrise_state: &Pubkey,
+    ) -> Result<(Pubkey, State)> {
+        let address = pda::fund_sender_state(destination_name, sunrise_state);
+        Ok((address, self.state(&address).await?))
+    }
+
+    /// The state's certificate records, in the order they were stored.
+    pub async fn certificate_records(
+        &self,
+        state: &Pubkey,
+    ) -> Result<Vec<(Pubkey, CertificateRecord)>> {
+        let total_certificates = self.state(state).await?.total_certificates;
+        let mut records = Vec::with_capacity(total_certificates as usize);
+        for index in 0..total_certificates {
+            let address = pda::certificate_record(state, index);
+            records.push((address, self.client.fetch(&address).await?));
+        }
+        Ok(records)
+    }
+
+    pub async fn send_fund(
+        &self,
+        payer: &Pubkey,
+        state: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(send_fund(payer, state, &state_account, amount))
+    }
+
+    pub async fn send_all_funds(&self, payer: &Pubkey, state: &Pubkey) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(send_all_funds(payer, state, &state_account))
+    }
+
+    pub async fn send_token_fund(&self, payer: &Pubkey, state: &Pubkey) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(send_token_fund(payer, state, &state_account))
+    }
+
+    /// Store the input account's certificate tokens of `certificate_mint` in the state's vault,
+    /// reading the mint's token program and whether it has Metaplex metadata from the cluster.
+    pub async fn store_certificates(
+        &self,
+        payer: &Pubkey,
+        state: &Pubkey,
+        certificate_mint: &Pubkey,
+        store: StoreCertificateAccounts,
+    ) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        let mint = self
+            .client
+            .rpc()
+            .get_account(certificate_mint)
+            .await?
+            .ok_or(crate::ClientError::AccountNotFound(*certificate_mint))?;
+        let metadata = Metadata::find_pda(certificate_mint).0;
+        let with_metadata = self.client.rpc().get_account(&metadata).await?.is_some();
+        Ok(store_certificates(
+            payer,
+            state,
+            &state_account,
+            certificate_mint,
+            &mint.owner,
+            with_metadata,
+            store,
+        ))
+    }
+}
@@ -0,0 +1,153 @@
+//! A Rust client for the yield controller programs, mirroring the TypeScript clients in `packages/`.
+//!
+//! - [`pda`] derives the program addresses of states and the accounts they control.
+//! - Each program module builds its instructions with the account metas the program expects,
+//!   from the program's own Anchor account and instruction definitions.
+//! - [`Client`] fetches and deserializes accounts and sends or simulates transactions over an [`Rpc`],
+//!   which is implemented for a JSON RPC node (`rpc-client` feature) and for a local bank (`banks` feature).
+//! - [`offline`] supports signing transactions away from the cluster, against a given blockhash or
+//!   a durable nonce.
+pub mod buy_burn_fixed;
+pub mod buy_burn_switchboard;
+mod error;
+pub mod fund_sender;
+pub mod offline;
+pub mod pda;
+pub mod rpc;
+pub mod yield_router;
+
+pub use error::{ClientError, Result};
+pub use offline::BlockhashQuery;
+pub use rpc::{Rpc, Simulation};
+
+use anchor_lang::AccountDeserialize;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+
+/// The accounts of a token-swap compatible pool, used by the programs' swap instructions
+#[derive(Clone, Copy, Debug)]
+pub struct SwapPoolAccounts {
+    pub authority: Pubkey,
+    // the pool's wrapped SOL vault
+    pub source: Pubkey,
+    // the pool's vault of the token being bought
+    pub destination: Pubkey,
+    pub pool_mint: Pubkey,
+    pub fee_account: Pubkey,
+}
+
+pub struct Client<R> {
+    rpc: R,
+}
+
+impl<R: Rpc> Client<R> {
+    pub fn new(rpc: R) -> Self {
+        Self { rpc }
+    }
+
+    pub fn rpc(&self) -> &R {
+        &self.rpc
+    }
+
+    /// Fetch and deserialize an Anchor account, checking its discriminator.
+    pub async fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let account = self
+            .rpc
+            .get_account(address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        T::try_deserialize(&mut account.data.as_slice())
+            .map_err(|err| ClientError::AccountDidNotDeserialize(*address, err))
+    }
+
+    /// Fetch and deserialize an Anchor account, or `None` if it does not exist.
+    pub async fn fetch_optional<T: AccountDeserialize>(
+        &self,
+        address: &Pubkey,
+    ) -> Result<Option<T>> {
+        match self.fetch(address).await {
+            Ok(account) => Ok(Some(account)),
+            Err(ClientError::AccountNotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Build an unsigned transaction of `instructions` paid by `payer`, against the blockhash
+    /// `blockhash` resolves to. A durable nonce is advanced by the transaction's first instruction.
+    pub async fn transaction(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        blockhash: &BlockhashQuery,
+    ) -> Result<Transaction> {
+        let (instructions, blockhash) = match *blockhash {
+            BlockhashQuery::Latest => (
+                instructions.to_vec(),
+                self.rpc.get_latest_blockhash().await?,
+            ),
+            BlockhashQuery::Static(blockhash) => (instructions.to_vec(), blockhash),
+            BlockhashQuery::Nonce {
+                account,
+                authority,
+                blockhash,
+            } => {
+                let blockhash = match blockhash {
+                    Some(blockhash) => blockhash,
+                    None => {
+                        let nonce_account = self
+                            .rpc
+                            .get_account(&account)
+                            .await?
+                            .ok_or(ClientError::AccountNotFound(account))?;
+                        offline::nonce_blockhash(&account, &nonce_account)?
+                    }
+                };
+                let advance_nonce = system_instruction::advance_nonce_account(&account, &authority);
+                ([&[advance_nonce], instructions].concat(), blockhash)
+            }
+        };
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(payer));
+        transaction.message.recent_blockhash = blockhash;
+        Ok(transaction)
+    }
+
+    /// Send a fully signed transaction and wait for it to be confirmed.
+    pub async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        self.rpc.send_transaction(transaction).await
+    }
+
+    /// Simulate a transaction, which need not be signed.
+    pub async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation> {
+        self.rpc.simulate_transaction(transaction).await
+    }
+
+    /// Sign `instructions` with `signers` against the latest blockhash, send them in one transaction
+    /// and wait for it to be confirmed. `payer` must be one of the signers.
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        signers: &[&dyn Signer],
+    ) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut transaction = Transaction::new_with_payer(instructions, Some(payer));
+        transaction.try_sign(signers, blockhash)?;
+        self.rpc.send_transaction(&transaction).await
+    }
+
+    /// Simulate `instructions` paid by `payer`, without signing them.
+    pub async fn simulate(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+    ) -> Result<Simulation> {
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut transaction = Transaction::new_with_payer(instructions, Some(payer));
+        transaction.message.recent_blockhash = blockhash;
+        self.rpc.simulate_transaction(&transaction).await
+    }
+}
@@ -0,0 +1,92 @@
+//! Transactions built, signed and sent in separate steps, so that an authority can sign on an
+//! air-gapped machine, following the `solana` CLI's offline signing:
+//!
+//! 1. Both machines build the same message, from a blockhash given up front or a durable nonce.
+//! 2. The offline machine signs it and reports its signature as a [`SignOnly`].
+//! 3. The online machine adds that signature with a [`Presigner`](solana_sdk::signer::presigner::Presigner)
+//!    and sends the transaction, which a durable nonce keeps valid until it is used.
+use crate::error::{ClientError, Result};
+use solana_sdk::account::Account;
+use solana_sdk::account_utils::StateMut;
+use solana_sdk::hash::Hash;
+use solana_sdk::nonce::state::{State as NonceState, Versions};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+
+/// Where a transaction's recent blockhash comes from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockhashQuery {
+    /// The cluster's latest blockhash, fetched when the transaction is built
+    #[default]
+    Latest,
+    /// A blockhash given up front, e.g. to an offline signer
+    Static(Hash),
+    /// The nonce stored in a durable nonce account, fetched unless given. The transaction
+    /// first advances the nonce, which `authority` must sign for.
+    Nonce {
+        account: Pubkey,
+        authority: Pubkey,
+        blockhash: Option<Hash>,
+    },
+}
+
+/// The nonce stored in the durable nonce account `address`
+pub fn nonce_blockhash(address: &Pubkey, account: &Account) -> Result<Hash> {
+    if account.owner != system_program::ID {
+        return Err(ClientError::InvalidNonceAccount(*address));
+    }
+    let versions: Versions = account
+        .state()
+        .map_err(|_| ClientError::InvalidNonceAccount(*address))?;
+    match versions.state() {
+        NonceState::Initialized(data) => Ok(data.blockhash()),
+        NonceState::Uninitialized => Err(ClientError::InvalidNonceAccount(*address)),
+    }
+}
+
+/// The signatures a transaction holds, as the `solana` CLI reports them with `--sign-only`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignOnly {
+    pub blockhash: Hash,
+    pub signers: Vec<(Pubkey, Signature)>,
+    // required signers that have not signed
+    pub absent: Vec<Pubkey>,
+    // required signers whose signature does not verify
+    pub bad_sig: Vec<Pubkey>,
+}
+
+impl SignOnly {
+    pub fn new(transaction: &Transaction) -> Self {
+        let message = transaction.message_data();
+        let mut sign_only = SignOnly {
+            blockhash: transaction.message.recent_blockhash,
+            signers: vec![],
+            absent: vec![],
+            bad_sig: vec![],
+        };
+        let required = transaction.message.header.num_required_signatures as usize;
+        for (key, signature) in transaction
+            .message
+            .account_keys
+            .iter()
+            .zip(&transaction.signatures)
+            .take(required)
+        {
+            if *signature == Signature::default() {
+                sign_only.absent.push(*key);
+            } else if signature.verify(key.as_ref(), &message) {
+                sign_only.signers.push((*key, *signature));
+            } else {
+                sign_only.bad_sig.push(*key);
+            }
+        }
+        sign_only
+    }
+
+    /// Whether every required signer has signed, with a valid signature
+    pub fn is_complete(&self) -> bool {
+        self.absent.is_empty() && self.bad_sig.is_empty()
+    }
+}
@@ -0,0 +1,87 @@
+use solana_sdk::pubkey::Pubkey;
+use yield_controller_common::seeds::{
+    ADJUSTMENT_LOG, CERTIFICATE_RECORD, CERTIFICATE_VAULT, INPUT_ACCOUNT, INPUT_YIELD_ACCOUNT,
+    RETIREMENT_RECEIPT, STATE, YIELD_ACCOUNT,
+};
+
+/// The yield-router state routing the yield of a Sunrise state.
+pub fn yield_router_state(sunrise_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[STATE, sunrise_state.as_ref()], &yield_router::ID).0
+}
+
+/// The system-owned PDA holding the yield to be allocated by a yield-router state.
+pub fn input_yield_account(state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[INPUT_YIELD_ACCOUNT, state.as_ref()], &yield_router::ID).0
+}
+
+/// The fund-sender state sending funds to the destination `destination_name`.
+pub fn fund_sender_state(destination_name: &str, sunrise_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[STATE, destination_name.as_bytes(), sunrise_state.as_ref()],
+        &fund_sender::ID,
+    )
+    .0
+}
+
+/// The system-owned PDA holding the funds and certificates to be sent by a fund-sender state.
+pub fn input_account(state: &Pubkey) -> Pubkey {
+    input_account_with_bump(state).0
+}
+
+/// The input account of a fund-sender state with its bump, which the state records.
+pub fn input_account_with_bump(state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INPUT_ACCOUNT, state.as_ref()], &fund_sender::ID)
+}
+
+/// The program-owned vault certificates are locked in, when the fund-sender state locks certificates.
+pub fn certificate_vault(state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CERTIFICATE_VAULT, state.as_ref()], &fund_sender::ID).0
+}
+
+/// The `index`th certificate record of a fund-sender state.
+pub fn certificate_record(state: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[CERTIFICATE_RECORD, state.as_ref(), &index.to_le_bytes()],
+        &fund_sender::ID,
+    )
+    .0
+}
+
+/// The buy-burn state of `mint` at `index`, for either buy-burn program.
+pub fn buy_burn_state(program_id: &Pubkey, mint: &Pubkey, index: u8) -> Pubkey {
+    buy_burn_state_with_bump(program_id, mint, index).0
+}
+
+/// The buy-burn state of `mint` at `index` with its bump, which the state records.
+pub fn buy_burn_state_with_bump(program_id: &Pubkey, mint: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATE, mint.as_ref(), &index.to_le_bytes()], program_id)
+}
+
+/// The buy-burn-fixed state of `mint` registered before states were indexed.
+pub fn legacy_buy_burn_fixed_state(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[STATE, mint.as_ref()], &buy_burn_fixed::ID).0
+}
+
+/// The system-owned PDA holding the yield to be allocated by a buy-burn state.
+pub fn yield_account(program_id: &Pubkey, state: &Pubkey) -> Pubkey {
+    yield_account_with_bump(program_id, state).0
+}
+
+/// The yield account of a buy-burn state with its bump, which buy-burn-switchboard states are given.
+pub fn yield_account_with_bump(program_id: &Pubkey, state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[YIELD_ACCOUNT, state.as_ref()], program_id)
+}
+
+/// The `index`th retirement receipt of a buy-burn state.
+pub fn retirement_receipt(program_id: &Pubkey, state: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[RETIREMENT_RECEIPT, state.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// The log of manual adjustments to a buy-burn-switchboard state's total tokens purchased.
+pub fn adjustment_log(state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ADJUSTMENT_LOG, state.as_ref()], &buy_burn_switchboard::ID).0
+}
@@ -0,0 +1,137 @@
+use crate::error::{ClientError, Result};
+use async_trait::async_trait;
+use solana_sdk::account::Account;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+/// The outcome of simulating a transaction
+#[derive(Clone, Debug, Default)]
+pub struct Simulation {
+    // the error the transaction would fail with, if any
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+/// The requests the client makes of a cluster, so that it can run against
+/// a JSON RPC node or a local bank alike.
+#[async_trait]
+pub trait Rpc: Send + Sync {
+    async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>>;
+
+    async fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    /// Send a signed transaction and wait for it to be confirmed.
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+
+    /// Simulate a transaction, which need not be signed.
+    async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation>;
+}
+
+#[cfg(feature = "rpc-client")]
+mod rpc_client {
+    use super::*;
+    use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+    use solana_rpc_client_api::client_error::Error as RpcClientError;
+    use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+
+    impl From<RpcClientError> for ClientError {
+        fn from(err: RpcClientError) -> Self {
+            match err.get_transaction_error() {
+                Some(err) => ClientError::Transaction(err),
+                None => ClientError::Rpc(err.to_string()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Rpc for RpcClient {
+        async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+            Ok(self
+                .get_account_with_commitment(address, self.commitment())
+                .await?
+                .value)
+        }
+
+        async fn get_latest_blockhash(&self) -> Result<Hash> {
+            Ok(RpcClient::get_latest_blockhash(self).await?)
+        }
+
+        async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+            Ok(self.send_and_confirm_transaction(transaction).await?)
+        }
+
+        async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation> {
+            let config = RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(self.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            };
+            let result = self
+                .simulate_transaction_with_config(transaction, config)
+                .await?
+                .value;
+            Ok(Simulation {
+                err: result.err,
+                logs: result.logs.unwrap_or_default(),
+                units_consumed: result.units_consumed,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "banks")]
+mod banks {
+    use super::*;
+    use solana_banks_client::{BanksClient, BanksClientError};
+
+    impl From<BanksClientError> for ClientError {
+        fn from(err: BanksClientError) -> Self {
+            match err {
+                BanksClientError::TransactionError(err)
+                | BanksClientError::SimulationError { err, .. } => ClientError::Transaction(err),
+                err => ClientError::Rpc(err.to_string()),
+            }
+        }
+    }
+
+    // the banks client takes `&mut self` for every request, but is cheap to clone,
+    // and its methods are called by path as they share the names of `Rpc`'s
+    #[async_trait]
+    impl Rpc for BanksClient {
+        async fn get_account(&self, address: &Pubkey) -> Result<Option<Account>> {
+            Ok(BanksClient::get_account(&mut self.clone(), *address).await?)
+        }
+
+        async fn get_latest_blockhash(&self) -> Result<Hash> {
+            Ok(BanksClient::get_latest_blockhash(&mut self.clone()).await?)
+        }
+
+        // processed directly against the working bank, as the banks server's confirmation
+        // polling only knows recent blockhashes, not durable nonces
+        async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+            self.clone()
+                .process_transaction_with_metadata(transaction.clone())
+                .await?
+                .result?;
+            Ok(transaction.signatures[0])
+        }
+
+        async fn simulate_transaction(&self, transaction: &Transaction) -> Result<Simulation> {
+            let result =
+                BanksClient::simulate_transaction(&mut self.clone(), transaction.clone()).await?;
+            let details = result.simulation_details;
+            Ok(Simulation {
+                err: result.result.and_then(|result| result.err()),
+                logs: details
+                    .as_ref()
+                    .map(|details| details.logs.clone())
+                    .unwrap_or_default(),
+                units_consumed: details.map(|details| details.units_consumed),
+            })
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{pda, Client, Result, Rpc};
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+pub use ::yield_router::{accounts, instruction, GenericStateInput, State, ID};
+
+pub fn register_state(
+    payer: &Pubkey,
+    sunrise_state: &Pubkey,
+    state_in: GenericStateInput,
+) -> Instruction {
+    let state = pda::yield_router_state(sunrise_state);
+    Instruction {
+        program_id: ID,
+        accounts: accounts::RegisterState {
+            payer: *payer,
+            state,
+            input_yield_account: pda::input_yield_account(&state),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RegisterState {
+            sunrise_state: *sunrise_state,
+            state_in,
+        }
+        .data(),
+    }
+}
+
+/// `payer` must be the state's update authority.
+pub fn update_state(payer: &Pubkey, state: &Pubkey, state_in: GenericStateInput) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts::UpdateState {
+            payer: *payer,
+            state: *state,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::UpdateState { state_in }.data(),
+    }
+}
+
+/// Allocate `amount` lamports from the input yield account to the state's output yield accounts.
+pub fn allocate_yield(
+    payer: &Pubkey,
+    state_address: &Pubkey,
+    state: &State,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = accounts::AllocateYield {
+        payer: *payer,
+        state: *state_address,
+        input_yield_account: pda::input_yield_account(state_address),
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    // the output yield accounts, in the order of the state's proportions
+    accounts.extend(
+        state
+            .output_yield_accounts
+            .iter()
+            .map(|output_yield_account| AccountMeta::new(*output_yield_account, false)),
+    );
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data: instruction::AllocateYield { amount }.data(),
+    }
+}
+
+/// Requests against the yield-router program
+pub struct YieldRouter<'a, R> {
+    client: &'a Client<R>,
+}
+
+impl<R: Rpc> Client<R> {
+    pub fn yield_router(&self) -> YieldRouter<'_, R> {
+        YieldRouter { client: self }
+    }
+}
+
+impl<R: Rpc> YieldRouter<'_, R> {
+    pub async fn state(&self, state: &Pubkey) -> Result<State> {
+        self.client.fetch(state).await
+    }
+
+    /// The state routing the yield of `sunrise_state`, along with its address.
+    pub async fn state_for_sunrise_state(&self, sunrise_state: &Pubkey) -> Result<(Pubkey, State)> {
+        let address = pda::yield_router_state(sunrise_state);
+        Ok((address, self.state(&address).await?))
+    }
+
+    pub async fn allocate_yield(
+        &self,
+        payer: &Pubkey,
+        state: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction> {
+        let state_account = self.state(state).await?;
+        Ok(allocate_yield(payer, state, &state_account, amount))
+    }
+}
@@ -0,0 +1,381 @@
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{Mint, TokenAccount};
+use common::SwapPool;
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::TransactionError;
+use spl_token_swap::error::SwapError;
+use yield_controller_client::buy_burn_fixed::{
+    self, ErrorCode, GenericStateInput, RetirementBackend, RetirementReceipt, State,
+    SwapAndBurnInput, PRICE_CHANGE_WINDOW,
+};
+use yield_controller_client::{pda, Client, ClientError};
+
+mod common;
+
+// the pool trades 100 SOL against 100 tokens of 6 decimals: 1000 lamports per token base unit
+const POOL_LAMPORTS: u64 = 100 * LAMPORTS_PER_SOL;
+const POOL_TOKENS: u64 = 100_000_000;
+
+struct SwapSetup {
+    client: Client<BanksClient>,
+    payer: Keypair,
+    mint: Pubkey,
+    state: Pubkey,
+    treasury: Pubkey,
+    retirement_vault: Pubkey,
+    pool: SwapPool,
+}
+
+// register a state burning tokens bought at `price` through a fresh pool, with 1 SOL of yield
+// to spend and a retirement vault to switch to
+async fn setup(price: u64) -> SwapSetup {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "buy_burn_fixed",
+        buy_burn_fixed::ID,
+        processor!(common::buy_burn_fixed_entry),
+    );
+    let mint = common::add_mint(&mut program_test, &Pubkey::new_unique(), 6, POOL_TOKENS);
+    let pool = SwapPool::add(&mut program_test, &mint, POOL_LAMPORTS, POOL_TOKENS);
+    let state = pda::buy_burn_state(&buy_burn_fixed::ID, &mint, 0);
+    for token_mint in [native_mint::ID, mint] {
+        common::add_token_account(
+            &mut program_test,
+            get_associated_token_address(&state, &token_mint),
+            &token_mint,
+            &state,
+            0,
+        );
+    }
+    let retirement_vault = Pubkey::new_unique();
+    common::add_token_account(
+        &mut program_test,
+        retirement_vault,
+        &mint,
+        &Pubkey::new_unique(),
+        0,
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+    pool.initialize(&client, &payer).await;
+
+    let treasury = Pubkey::new_unique();
+    let register = buy_burn_fixed::register_state(
+        &payer.pubkey(),
+        GenericStateInput {
+            mint,
+            update_authority: payer.pubkey(),
+            treasury,
+            holding_account: Pubkey::new_unique(),
+            holding_token_account: Pubkey::new_unique(),
+            price,
+            purchase_threshold: 0,
+            purchase_proportion: 0.5,
+            price_staleness_threshold: 3600,
+            max_price_change_bps: 0,
+            swap_program: pool.program,
+            swap_pool: pool.address.pubkey(),
+            max_slippage_bps: 100,
+            retirement_backend: Default::default(),
+            retirement_vault,
+            retirement_beneficiary: Pubkey::default(),
+            index: 0,
+        },
+    );
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &pda::yield_account(&buy_burn_fixed::ID, &state),
+        LAMPORTS_PER_SOL + Rent::default().minimum_balance(0),
+    );
+    client
+        .send(&[register, fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    SwapSetup {
+        client,
+        payer,
+        mint,
+        state,
+        treasury,
+        retirement_vault,
+        pool,
+    }
+}
+
+#[tokio::test]
+async fn swap_and_burn_retires_the_swap_output() {
+    let SwapSetup {
+        client,
+        payer,
+        mint,
+        state,
+        treasury,
+        pool,
+        ..
+    } = setup(1_000).await;
+
+    let swap = client
+        .buy_burn_fixed()
+        .swap_and_burn(
+            &payer.pubkey(),
+            &state,
+            &pool.accounts,
+            SwapAndBurnInput {
+                sol_amount: LAMPORTS_PER_SOL,
+                minimum_amount_out: 0,
+            },
+        )
+        .await
+        .unwrap();
+    client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // half of the yield buys 0.5 SOL * 100 tokens / 100.5 SOL from the pool, all of which is burned
+    let bought = POOL_TOKENS * (LAMPORTS_PER_SOL / 2) / (POOL_LAMPORTS + LAMPORTS_PER_SOL / 2);
+    let pool_tokens: TokenAccount = client.fetch(&pool.accounts.destination).await.unwrap();
+    assert_eq!(pool_tokens.amount, POOL_TOKENS - bought);
+    let purchased: TokenAccount = client
+        .fetch(&get_associated_token_address(&state, &mint))
+        .await
+        .unwrap();
+    assert_eq!(purchased.amount, 0);
+    let mint_account: Mint = client.fetch(&mint).await.unwrap();
+    assert_eq!(mint_account.supply, POOL_TOKENS - bought);
+
+    // the other half is paid to the treasury
+    assert_eq!(
+        common::lamports(&client, &treasury).await,
+        LAMPORTS_PER_SOL / 2
+    );
+    let state_account = client.buy_burn_fixed().state(&state).await.unwrap();
+    assert_eq!(state_account.total_spent, LAMPORTS_PER_SOL / 2);
+}
+
+#[tokio::test]
+async fn swap_and_burn_refuses_output_below_the_price() {
+    // at a price of 500 lamports, 0.5 SOL should buy 1 token, but the pool only gives half of that
+    let SwapSetup {
+        client,
+        payer,
+        state,
+        pool,
+        ..
+    } = setup(500).await;
+
+    // a caller asking for no minimum is still held to the price, less the 1% maximum slippage
+    let swap = client
+        .buy_burn_fixed()
+        .swap_and_burn(
+            &payer.pubkey(),
+            &state,
+            &pool.accounts,
+            SwapAndBurnInput {
+                sol_amount: LAMPORTS_PER_SOL,
+                minimum_amount_out: 0,
+            },
+        )
+        .await
+        .unwrap();
+    let err = client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        )) if code == SwapError::ExceededSlippage as u32
+    ));
+
+    let pool_tokens: TokenAccount = client.fetch(&pool.accounts.destination).await.unwrap();
+    assert_eq!(pool_tokens.amount, POOL_TOKENS);
+}
+
+#[tokio::test]
+async fn swap_and_burn_refuses_a_receipt_while_burning() {
+    let SwapSetup {
+        client,
+        payer,
+        state,
+        retirement_vault,
+        pool,
+        ..
+    } = setup(1_000).await;
+    let args = SwapAndBurnInput {
+        sol_amount: LAMPORTS_PER_SOL,
+        minimum_amount_out: 0,
+    };
+
+    // a receipt passed while the state burns would take the address of its first vault retirement
+    let state_account = client.buy_burn_fixed().state(&state).await.unwrap();
+    let as_vault = State {
+        retirement_backend: RetirementBackend::Vault,
+        ..state_account.clone()
+    };
+    let swap = buy_burn_fixed::swap_and_burn(
+        &payer.pubkey(),
+        &state,
+        &as_vault,
+        &pool.accounts,
+        SwapAndBurnInput { ..args },
+    );
+    let err = client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        )) if code == u32::from(ErrorCode::UnexpectedRetirementAccounts)
+    ));
+    let receipt = pda::retirement_receipt(&buy_burn_fixed::ID, &state, 0);
+    assert!(client
+        .fetch_optional::<RetirementReceipt>(&receipt)
+        .await
+        .unwrap()
+        .is_none());
+
+    // so once the state switches to the vault, its first retirement is recorded there
+    let update = buy_burn_fixed::update_state(
+        &payer.pubkey(),
+        GenericStateInput {
+            mint: state_account.mint,
+            update_authority: state_account.update_authority,
+            treasury: state_account.treasury,
+            holding_account: state_account.holding_account,
+            holding_token_account: state_account.holding_token_account,
+            price: state_account.price,
+            purchase_threshold: state_account.purchase_threshold,
+            purchase_proportion: state_account.purchase_proportion,
+            price_staleness_threshold: state_account.price_staleness_threshold,
+            max_price_change_bps: state_account.max_price_change_bps,
+            swap_program: state_account.swap_program,
+            swap_pool: state_account.swap_pool,
+            max_slippage_bps: state_account.max_slippage_bps,
+            retirement_backend: RetirementBackend::Vault,
+            retirement_vault,
+            retirement_beneficiary: state_account.retirement_beneficiary,
+            index: state_account.index,
+        },
+    );
+    client
+        .send(&[update], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let swap = client
+        .buy_burn_fixed()
+        .swap_and_burn(&payer.pubkey(), &state, &pool.accounts, args)
+        .await
+        .unwrap();
+    client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let bought = POOL_TOKENS * (LAMPORTS_PER_SOL / 2) / (POOL_LAMPORTS + LAMPORTS_PER_SOL / 2);
+    let vault: TokenAccount = client.fetch(&retirement_vault).await.unwrap();
+    assert_eq!(vault.amount, bought);
+    let receipt: RetirementReceipt = client.fetch(&receipt).await.unwrap();
+    assert_eq!(receipt.amount, bought);
+    assert_eq!(receipt.index, 0);
+}
+
+#[tokio::test]
+async fn update_price_limits_the_change_within_the_window() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "buy_burn_fixed",
+        buy_burn_fixed::ID,
+        processor!(common::buy_burn_fixed_entry),
+    );
+    let mint = common::add_mint(&mut program_test, &Pubkey::new_unique(), 6, 0);
+    let mut context = program_test.start_with_context().await;
+    let client = Client::new(context.banks_client.clone());
+    let payer = context.payer.insecure_clone();
+
+    // allow at most a 10% change within the window
+    let state = pda::buy_burn_state(&buy_burn_fixed::ID, &mint, 0);
+    let register = buy_burn_fixed::register_state(
+        &payer.pubkey(),
+        GenericStateInput {
+            mint,
+            update_authority: payer.pubkey(),
+            treasury: Pubkey::new_unique(),
+            holding_account: Pubkey::new_unique(),
+            holding_token_account: Pubkey::new_unique(),
+            price: 1_000,
+            purchase_threshold: 0,
+            purchase_proportion: 0.5,
+            price_staleness_threshold: 0,
+            max_price_change_bps: 1_000,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            max_slippage_bps: 100,
+            retirement_backend: Default::default(),
+            retirement_vault: Pubkey::default(),
+            retirement_beneficiary: Pubkey::default(),
+            index: 0,
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let update_price = |price: u64| {
+        let client = &client;
+        let payer = &payer;
+        async move {
+            let update = buy_burn_fixed::update_price(&payer.pubkey(), &state, price);
+            client.send(&[update], &payer.pubkey(), &[payer]).await
+        }
+    };
+    let refused_with = |result: Result<_, ClientError>, error: ErrorCode| {
+        matches!(
+            result,
+            Err(ClientError::Transaction(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(code),
+            ))) if code == u32::from(error)
+        )
+    };
+
+    // a second step within the window is measured against the registered price too
+    update_price(1_100).await.unwrap();
+    assert!(refused_with(
+        update_price(1_210).await,
+        ErrorCode::PriceChangeTooLarge
+    ));
+
+    // once the window has passed, only the price in effect at its start bounds the change
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += PRICE_CHANGE_WINDOW + 1;
+    context.set_sysvar(&clock);
+    update_price(1_200).await.unwrap();
+    let state_account = client.buy_burn_fixed().state(&state).await.unwrap();
+    assert_eq!(state_account.price, 1_200);
+
+    // the history holds the registered and first prices from before the window,
+    // after which it cannot tell which prices were in effect within the window
+    for price in 1_191..1_206 {
+        update_price(price).await.unwrap();
+    }
+    assert!(refused_with(
+        update_price(1_206).await,
+        ErrorCode::TooManyPriceUpdates
+    ));
+}
@@ -0,0 +1,421 @@
+use anchor_lang::{AnchorSerialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::{Mint, TokenAccount};
+use common::SwapPool;
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::TransactionError;
+use spl_token_swap::error::SwapError;
+use yield_controller_client::buy_burn_switchboard::{
+    self, ErrorCode, GenericStateInput, LegacyState, RetirementBackend, State,
+};
+use yield_controller_client::{pda, Client, ClientError};
+
+mod common;
+
+// the NCT price in SOL given by the fixture feeds, whose rounds are from March 2023
+const NCT_SOL: f64 = 2.0475649887674585 / 21.7042154;
+const FEED_STALENESS_THRESHOLD: u64 = 100 * 365 * 24 * 60 * 60;
+const POOL_LAMPORTS: u64 = 100 * LAMPORTS_PER_SOL;
+const HOLDING_TOKENS: u64 = 1_000_000_000;
+
+// the tokens of 6 decimals that `lamports` buy at the oracle price
+fn oracle_tokens(lamports: u64) -> u64 {
+    (lamports as f64 / (1_000.0 * NCT_SOL)) as u64
+}
+
+struct SwapSetup {
+    client: Client<BanksClient>,
+    payer: Keypair,
+    mint: Pubkey,
+    state: Pubkey,
+    treasury: Pubkey,
+    pool: SwapPool,
+    pool_tokens: u64,
+    receipt_merkle_tree: Pubkey,
+    receipt_vault: Pubkey,
+}
+
+// register a state swapping through a pool priced at `pool_price` times the oracle price,
+// with 1 SOL of yield to spend, minting burn receipts into a mock tree if `receipts` is set
+async fn setup(pool_price: f64, receipts: bool) -> SwapSetup {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "buy_burn_switchboard",
+        buy_burn_switchboard::ID,
+        processor!(common::buy_burn_switchboard_entry),
+    );
+    let sol_usd_price_feed = common::add_fixture_account(&mut program_test, "switchboard_sol_usd");
+    let nct_usd_price_feed = common::add_fixture_account(&mut program_test, "switchboard_nct_usd");
+    common::add_bubblegum_mocks(&mut program_test);
+    let pool_tokens = (oracle_tokens(POOL_LAMPORTS) as f64 / pool_price) as u64;
+    let mint = common::add_mint(
+        &mut program_test,
+        &Pubkey::new_unique(),
+        6,
+        pool_tokens + HOLDING_TOKENS,
+    );
+    let pool = SwapPool::add(&mut program_test, &mint, POOL_LAMPORTS, pool_tokens);
+    let state = pda::buy_burn_state(&buy_burn_switchboard::ID, &mint, 0);
+    // allocate_yield retires tokens from the holding token account, which the state is a delegate of
+    let holding_account = Pubkey::new_unique();
+    let holding_token_account = common::add_delegated_token_account(
+        &mut program_test,
+        Pubkey::new_unique(),
+        &mint,
+        &holding_account,
+        HOLDING_TOKENS,
+        Some(&state),
+    );
+    for token_mint in [native_mint::ID, mint] {
+        common::add_token_account(
+            &mut program_test,
+            get_associated_token_address(&state, &token_mint),
+            &token_mint,
+            &state,
+            0,
+        );
+    }
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+    pool.initialize(&client, &payer).await;
+
+    let treasury = Pubkey::new_unique();
+    let (receipt_merkle_tree, receipt_vault) = if receipts {
+        (Pubkey::new_unique(), Pubkey::new_unique())
+    } else {
+        (Pubkey::default(), Pubkey::default())
+    };
+    let (yield_account, yield_account_bump) =
+        pda::yield_account_with_bump(&buy_burn_switchboard::ID, &state);
+    let register = buy_burn_switchboard::register_state(
+        &payer.pubkey(),
+        GenericStateInput {
+            mint,
+            update_authority: payer.pubkey(),
+            treasury,
+            holding_account,
+            holding_token_account,
+            sol_usd_price_feed,
+            nct_usd_price_feed,
+            feed_staleness_threshold: FEED_STALENESS_THRESHOLD,
+            purchase_threshold: 0,
+            purchase_proportion: 0.5,
+            index: 0,
+            yield_account_bump,
+            swap_program: pool.program,
+            swap_pool: pool.address.pubkey(),
+            max_slippage_bps: 100,
+            retirement_backend: Default::default(),
+            retirement_vault: Pubkey::default(),
+            retirement_beneficiary: Pubkey::default(),
+            receipt_merkle_tree,
+            receipt_vault,
+        },
+    );
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &yield_account,
+        LAMPORTS_PER_SOL + Rent::default().minimum_balance(0),
+    );
+    client
+        .send(&[register, fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    SwapSetup {
+        client,
+        payer,
+        mint,
+        state,
+        treasury,
+        pool,
+        pool_tokens,
+        receipt_merkle_tree,
+        receipt_vault,
+    }
+}
+
+#[tokio::test]
+async fn swap_and_burn_retires_the_swap_output() {
+    let SwapSetup {
+        client,
+        payer,
+        mint,
+        state,
+        treasury,
+        pool,
+        pool_tokens,
+        ..
+    } = setup(1.0, false).await;
+
+    let swap = client
+        .buy_burn_switchboard()
+        .swap_and_burn(&payer.pubkey(), &state, &pool.accounts, 0)
+        .await
+        .unwrap();
+    client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // half of the yield is swapped, losing only the pool's price impact against the oracle
+    let pool_account: TokenAccount = client.fetch(&pool.accounts.destination).await.unwrap();
+    let bought = pool_tokens - pool_account.amount;
+    assert_eq!(
+        bought,
+        pool_tokens * (LAMPORTS_PER_SOL / 2) / (POOL_LAMPORTS + LAMPORTS_PER_SOL / 2)
+    );
+    assert!(bought > oracle_tokens(LAMPORTS_PER_SOL / 2) * 99 / 100);
+    let mint_account: Mint = client.fetch(&mint).await.unwrap();
+    assert_eq!(mint_account.supply, pool_tokens + HOLDING_TOKENS - bought);
+
+    assert_eq!(
+        common::lamports(&client, &treasury).await,
+        LAMPORTS_PER_SOL / 2
+    );
+    let state_account = client.buy_burn_switchboard().state(&state).await.unwrap();
+    assert_eq!(state_account.total_tokens_purchased, bought);
+}
+
+#[tokio::test]
+async fn swap_and_burn_refuses_output_below_the_oracle_price() {
+    // the pool charges twice the oracle price
+    let SwapSetup {
+        client,
+        payer,
+        state,
+        pool,
+        pool_tokens,
+        ..
+    } = setup(2.0, false).await;
+
+    // a caller asking for no minimum is still held to the oracle price, less the 1% maximum slippage
+    let swap = client
+        .buy_burn_switchboard()
+        .swap_and_burn(&payer.pubkey(), &state, &pool.accounts, 0)
+        .await
+        .unwrap();
+    let err = client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        )) if code == SwapError::ExceededSlippage as u32
+    ));
+
+    let pool_account: TokenAccount = client.fetch(&pool.accounts.destination).await.unwrap();
+    assert_eq!(pool_account.amount, pool_tokens);
+}
+
+#[tokio::test]
+async fn allocate_yield_and_swap_and_burn_mint_receipts() {
+    let SwapSetup {
+        client,
+        payer,
+        state,
+        pool,
+        receipt_merkle_tree,
+        receipt_vault,
+        ..
+    } = setup(1.0, true).await;
+    let program = client.buy_burn_switchboard();
+
+    // both ways of retiring tokens mint a receipt of the amount retired
+    let allocate = program
+        .allocate_yield(&payer.pubkey(), &state)
+        .await
+        .unwrap();
+    let allocate_logs = client
+        .simulate(std::slice::from_ref(&allocate), &payer.pubkey())
+        .await
+        .unwrap()
+        .logs;
+    client
+        .send(&[allocate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let allocated = program.state(&state).await.unwrap().total_tokens_purchased;
+
+    // the allocation spent the yield, which is topped up for the swap
+    let (yield_account, _) = pda::yield_account_with_bump(&buy_burn_switchboard::ID, &state);
+    let fund = system_instruction::transfer(&payer.pubkey(), &yield_account, LAMPORTS_PER_SOL);
+    client
+        .send(&[fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let swap = program
+        .swap_and_burn(&payer.pubkey(), &state, &pool.accounts, 0)
+        .await
+        .unwrap();
+    let swap_logs = client
+        .simulate(std::slice::from_ref(&swap), &payer.pubkey())
+        .await
+        .unwrap()
+        .logs;
+    client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let swapped = program.state(&state).await.unwrap().total_tokens_purchased - allocated;
+
+    for (logs, amount) in [(allocate_logs, allocated), (swap_logs, swapped)] {
+        let receipt = format!(
+            "mint_v1 {} to {}: data:application/json,{{\"amount\":{},",
+            receipt_merkle_tree, receipt_vault, amount
+        );
+        assert!(
+            logs.iter().any(|log| log.contains(&receipt)),
+            "no receipt of {} in {:?}",
+            amount,
+            logs
+        );
+    }
+}
+
+#[tokio::test]
+async fn migrate_legacy_state() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "buy_burn_switchboard",
+        buy_burn_switchboard::ID,
+        processor!(common::buy_burn_switchboard_entry),
+    );
+    let sol_usd_price_feed = common::add_fixture_account(&mut program_test, "switchboard_sol_usd");
+    let nct_usd_price_feed = common::add_fixture_account(&mut program_test, "switchboard_nct_usd");
+    let mint = common::add_mint(&mut program_test, &Pubkey::new_unique(), 6, HOLDING_TOKENS);
+    let (state, bump) = pda::buy_burn_state_with_bump(&buy_burn_switchboard::ID, &mint, 0);
+    let holding_account = Pubkey::new_unique();
+    let holding_token_account = common::add_delegated_token_account(
+        &mut program_test,
+        Pubkey::new_unique(),
+        &mint,
+        &holding_account,
+        HOLDING_TOKENS,
+        Some(&state),
+    );
+
+    // a state registered with the original layout
+    let update_authority = Keypair::new();
+    let treasury = Pubkey::new_unique();
+    let (yield_account, yield_account_bump) =
+        pda::yield_account_with_bump(&buy_burn_switchboard::ID, &state);
+    let legacy_state = LegacyState {
+        update_authority: update_authority.pubkey(),
+        treasury,
+        mint,
+        sol_usd_price_feed,
+        nct_usd_price_feed,
+        holding_account,
+        holding_token_account,
+        feed_staleness_threshold: FEED_STALENESS_THRESHOLD,
+        purchase_threshold: 0,
+        purchase_proportion: 0.5,
+        total_tokens_purchased: 1_000,
+        index: 0,
+        bump,
+        yield_account_bump,
+    };
+    let mut data = State::DISCRIMINATOR.to_vec();
+    legacy_state.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), LegacyState::SPACE);
+    program_test.add_account(
+        state,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: buy_burn_switchboard::ID,
+            ..Account::default()
+        },
+    );
+    // the update authority tops up the state's rent
+    program_test.add_account(
+        update_authority.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            ..Account::default()
+        },
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+    let program = client.buy_burn_switchboard();
+
+    // a legacy state cannot be loaded
+    assert!(program.state(&state).await.is_err());
+
+    // only the update authority can migrate it
+    let migrate = buy_burn_switchboard::migrate_state(&payer.pubkey(), &state);
+    let err = client
+        .send(&[migrate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::Unauthorized)
+    ));
+
+    let migrate = buy_burn_switchboard::migrate_state(&update_authority.pubkey(), &state);
+    client
+        .send(&[migrate], &payer.pubkey(), &[&payer, &update_authority])
+        .await
+        .unwrap();
+
+    let state_account = program.state(&state).await.unwrap();
+    assert_eq!(state_account.update_authority, update_authority.pubkey());
+    assert_eq!(state_account.holding_token_account, holding_token_account);
+    assert_eq!(state_account.total_tokens_purchased, 1_000);
+    assert_eq!(state_account.bump, bump);
+    assert_eq!(state_account.yield_account_bump, yield_account_bump);
+    assert!(state_account.retirement_backend == RetirementBackend::Burn);
+    assert_eq!(state_account.receipt_merkle_tree, Pubkey::default());
+    assert_eq!(state_account.total_retirements, 0);
+
+    // a migrated state cannot be migrated again
+    let migrate = buy_burn_switchboard::migrate_state(&update_authority.pubkey(), &state);
+    let err = client
+        .send(&[migrate], &update_authority.pubkey(), &[&update_authority])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::InvalidLegacyState)
+    ));
+
+    // and its yield is allocated as before
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &yield_account,
+        LAMPORTS_PER_SOL + Rent::default().minimum_balance(0),
+    );
+    client
+        .send(&[fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let allocate = program
+        .allocate_yield(&payer.pubkey(), &state)
+        .await
+        .unwrap();
+    client
+        .send(&[allocate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    assert!(program.state(&state).await.unwrap().total_tokens_purchased > 1_000);
+    assert_eq!(
+        common::lamports(&client, &treasury).await,
+        LAMPORTS_PER_SOL / 2
+    );
+}
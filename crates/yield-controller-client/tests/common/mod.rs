@@ -0,0 +1,507 @@
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use mpl_bubblegum::instructions::{MintV1InstructionArgs, TransferInstructionArgs};
+use mpl_bubblegum::programs::{MPL_BUBBLEGUM_ID, SPL_ACCOUNT_COMPRESSION_ID};
+use mpl_bubblegum::types::LeafSchema;
+use mpl_bubblegum::utils::get_asset_id;
+use mpl_core::accounts::BaseAssetV1;
+use mpl_core::instructions::{TransferV1, TransferV1InstructionArgs};
+use mpl_core::types::{Key, UpdateAuthority};
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::account::Account;
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::entrypoint::ProgramResult;
+use solana_sdk::hash::hash;
+use solana_sdk::keccak;
+use solana_sdk::msg;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::program_option::COption;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use spl_token_swap::curve::base::{CurveType, SwapCurve};
+use spl_token_swap::curve::constant_product::ConstantProductCurve;
+use spl_token_swap::curve::fees::Fees;
+use std::str::FromStr;
+use std::sync::Arc;
+use yield_controller_client::fund_sender::CNFTCertificate;
+use yield_controller_client::{Client, Rpc, SwapPoolAccounts};
+
+// Anchor entrypoints tie the lifetime of the account slice to that of the accounts,
+// which the program test processor signature does not, so the slice is leaked to satisfy both
+macro_rules! anchor_entry {
+    ($name:ident, $program:ident) => {
+        #[allow(dead_code)]
+        pub fn $name(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+            let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+            $program::entry(program_id, accounts, data)
+        }
+    };
+}
+
+anchor_entry!(buy_burn_fixed_entry, buy_burn_fixed);
+anchor_entry!(buy_burn_switchboard_entry, buy_burn_switchboard);
+anchor_entry!(yield_router_entry, yield_router);
+anchor_entry!(fund_sender_entry, fund_sender);
+
+/// The balance of `address`, or zero if it does not exist
+#[allow(dead_code)]
+pub async fn lamports<R: Rpc>(client: &Client<R>, address: &Pubkey) -> u64 {
+    client
+        .rpc()
+        .get_account(address)
+        .await
+        .unwrap()
+        .map_or(0, |account| account.lamports)
+}
+
+/// Add an account from `packages/tests/fixtures/<name>.json`, as dumped by `solana account --output json`
+#[allow(dead_code)]
+pub fn add_fixture_account(program_test: &mut ProgramTest, name: &str) -> Pubkey {
+    let path = format!(
+        "{}/../../packages/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let fixture: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let pubkey = |value: &serde_json::Value| Pubkey::from_str(value.as_str().unwrap()).unwrap();
+    let address = pubkey(&fixture["pubkey"]);
+    let account = &fixture["account"];
+    program_test.add_account_with_base64_data(
+        address,
+        account["lamports"].as_u64().unwrap(),
+        pubkey(&account["owner"]),
+        account["data"][0].as_str().unwrap(),
+    );
+    address
+}
+
+/// Add a mint with `decimals` and `supply` to the bank
+#[allow(dead_code)]
+pub fn add_mint(
+    program_test: &mut ProgramTest,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    supply: u64,
+) -> Pubkey {
+    let address = Pubkey::new_unique();
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(
+        Mint {
+            mint_authority: COption::Some(*mint_authority),
+            supply,
+            decimals,
+            is_initialized: true,
+            ..Mint::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    add_token_program_account(program_test, address, data);
+    address
+}
+
+/// Add a token account of `mint` at `address`, owned by `owner` and holding `amount`.
+/// Wrapped SOL accounts hold `amount` lamports on top of their rent-exempt minimum.
+#[allow(dead_code)]
+pub fn add_token_account(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    add_delegated_token_account(program_test, address, mint, owner, amount, None)
+}
+
+/// Add a token account as [`add_token_account`] does, whose whole balance `delegate` may spend
+#[allow(dead_code)]
+pub fn add_delegated_token_account(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    delegate: Option<&Pubkey>,
+) -> Pubkey {
+    let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: delegate.copied().into(),
+            delegated_amount: if delegate.is_some() { amount } else { 0 },
+            state: AccountState::Initialized,
+            is_native: if *mint == native_mint::ID {
+                COption::Some(rent)
+            } else {
+                COption::None
+            },
+            ..TokenAccount::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    let lamports = if *mint == native_mint::ID {
+        rent + amount
+    } else {
+        rent
+    };
+    program_test.add_account(
+        address,
+        Account {
+            lamports,
+            data,
+            owner: anchor_spl::token::ID,
+            ..Account::default()
+        },
+    );
+    address
+}
+
+fn add_token_program_account(program_test: &mut ProgramTest, address: Pubkey, data: Vec<u8>) {
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: anchor_spl::token::ID,
+            ..Account::default()
+        },
+    );
+}
+
+/// A fee-less constant-product pool of the SPL token-swap program, trading wrapped SOL for `mint`
+#[allow(dead_code)]
+pub struct SwapPool {
+    pub program: Pubkey,
+    pub address: Keypair,
+    pub accounts: SwapPoolAccounts,
+}
+
+impl SwapPool {
+    /// Add the token-swap program and the vaults of a pool holding `lamports` and `tokens` to the bank.
+    /// The pool itself is created by [`SwapPool::initialize`] once the bank has started.
+    #[allow(dead_code)]
+    pub fn add(program_test: &mut ProgramTest, mint: &Pubkey, lamports: u64, tokens: u64) -> Self {
+        let program = spl_token_swap::id();
+        program_test.add_program(
+            "spl_token_swap",
+            program,
+            processor!(spl_token_swap::processor::Processor::process),
+        );
+
+        let address = Keypair::new();
+        let (authority, _) = Pubkey::find_program_address(&[address.pubkey().as_ref()], &program);
+        let pool_mint = add_mint(program_test, &authority, 9, 0);
+        let fee_account = add_token_account(
+            program_test,
+            Pubkey::new_unique(),
+            &pool_mint,
+            &Pubkey::new_unique(),
+            0,
+        );
+        let accounts = SwapPoolAccounts {
+            authority,
+            source: add_token_account(
+                program_test,
+                Pubkey::new_unique(),
+                &native_mint::ID,
+                &authority,
+                lamports,
+            ),
+            destination: add_token_account(
+                program_test,
+                Pubkey::new_unique(),
+                mint,
+                &authority,
+                tokens,
+            ),
+            pool_mint,
+            fee_account,
+        };
+        Self {
+            program,
+            address,
+            accounts,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn initialize<R: Rpc>(&self, client: &Client<R>, payer: &Keypair) {
+        let size = spl_token_swap::state::SwapVersion::LATEST_LEN;
+        let create = solana_sdk::system_instruction::create_account(
+            &payer.pubkey(),
+            &self.address.pubkey(),
+            Rent::default().minimum_balance(size),
+            size as u64,
+            &self.program,
+        );
+        // the initial pool tokens go to the fee account, which is not owned by the pool
+        let initialize = spl_token_swap::instruction::initialize(
+            &self.program,
+            &anchor_spl::token::ID,
+            &self.address.pubkey(),
+            &self.accounts.authority,
+            &self.accounts.source,
+            &self.accounts.destination,
+            &self.accounts.pool_mint,
+            &self.accounts.fee_account,
+            &self.accounts.fee_account,
+            Fees::default(),
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: Arc::new(ConstantProductCurve),
+            },
+        )
+        .unwrap();
+        client
+            .send(
+                &[create, initialize],
+                &payer.pubkey(),
+                &[payer, &self.address],
+            )
+            .await
+            .unwrap();
+    }
+}
+
+// the discriminator of an Anchor instruction
+fn sighash(name: &str) -> [u8; 8] {
+    hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+// the hash of a leaf as Bubblegum stores it in the tree
+fn leaf_hash(
+    merkle_tree: &Pubkey,
+    leaf_owner: &Pubkey,
+    leaf_delegate: &Pubkey,
+    nonce: u64,
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+) -> [u8; 32] {
+    LeafSchema::V1 {
+        id: get_asset_id(merkle_tree, nonce),
+        owner: *leaf_owner,
+        delegate: *leaf_delegate,
+        nonce,
+        data_hash,
+        creator_hash,
+    }
+    .hash()
+}
+
+// the root of a tree holding `leaf` at `index`, from the proof of its siblings up to the root,
+// hashing nodes as spl-account-compression does
+fn merkle_root(leaf: [u8; 32], index: u32, proof: &[[u8; 32]]) -> [u8; 32] {
+    proof
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| {
+            if index >> level & 1 == 0 {
+                keccak::hashv(&[&node, sibling]).to_bytes()
+            } else {
+                keccak::hashv(&[sibling, &node]).to_bytes()
+            }
+        })
+}
+
+/// Set `certificate`'s root to that of a tree of `depth` holding it, owned by `leaf_owner`
+/// and delegated to `leaf_delegate`, returning its proof.
+/// The mock trees have no canopy, so the proof runs all the way to the root.
+#[allow(dead_code)]
+pub fn prove_cnft(
+    merkle_tree: &Pubkey,
+    leaf_owner: &Pubkey,
+    leaf_delegate: &Pubkey,
+    certificate: &mut CNFTCertificate,
+    depth: usize,
+) -> Vec<Pubkey> {
+    // the rest of the tree is made of arbitrary nodes
+    let proof: Vec<[u8; 32]> = (0..depth)
+        .map(|_| Pubkey::new_unique().to_bytes())
+        .collect();
+    let leaf = leaf_hash(
+        merkle_tree,
+        leaf_owner,
+        leaf_delegate,
+        certificate.nonce,
+        certificate.data_hash,
+        certificate.creator_hash,
+    );
+    certificate.root = merkle_root(leaf, certificate.index, &proof);
+    proof.into_iter().map(Pubkey::new_from_array).collect()
+}
+
+// Bubblegum stand-in, logging each leaf minted or transferred so that tests can check the CPIs
+// made to it. The tree delegate must sign a mint, and the leaf owner or delegate a transfer,
+// as they must for Bubblegum. A transfer must prove the leaf, of the owner and delegate passed
+// and the hashes and nonce given, against the root given, with a full proof as the mock trees
+// have no canopy. The root is not checked against the tree account, which the mock does not keep.
+fn mock_bubblegum(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (discriminator, mut args) = data.split_at(8);
+    if discriminator == sighash("transfer") {
+        let [_tree_config, leaf_owner, leaf_delegate, new_leaf_owner, merkle_tree, _log_wrapper, _compression_program, _system_program, proof @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if !leaf_owner.is_signer && !leaf_delegate.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let args = TransferInstructionArgs::deserialize(&mut args)?;
+        let leaf = leaf_hash(
+            merkle_tree.key,
+            leaf_owner.key,
+            leaf_delegate.key,
+            args.nonce,
+            args.data_hash,
+            args.creator_hash,
+        );
+        let proof_nodes: Vec<[u8; 32]> = proof.iter().map(|node| node.key.to_bytes()).collect();
+        if merkle_root(leaf, args.index, &proof_nodes) != args.root {
+            msg!("invalid proof for leaf {}", args.index);
+            return Err(ProgramError::InvalidArgument);
+        }
+        msg!(
+            "transfer {} leaf {} from {} (delegate {}) to {} with {} proof nodes",
+            merkle_tree.key,
+            args.index,
+            leaf_owner.key,
+            leaf_delegate.key,
+            new_leaf_owner.key,
+            proof.len()
+        );
+        return Ok(());
+    }
+    if discriminator == sighash("mint_v1") {
+        let [_tree_config, leaf_owner, _leaf_delegate, merkle_tree, _payer, tree_delegate, ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if !tree_delegate.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let args = MintV1InstructionArgs::deserialize(&mut args)?;
+        msg!(
+            "mint_v1 {} to {}: {}",
+            merkle_tree.key,
+            leaf_owner.key,
+            args.metadata.uri
+        );
+        return Ok(());
+    }
+    Err(ProgramError::InvalidInstructionData)
+}
+
+fn mock_noop(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+/// Stand in for the Bubblegum and account compression programs, which are not available to the bank
+#[allow(dead_code)]
+pub fn add_bubblegum_mocks(program_test: &mut ProgramTest) {
+    program_test.add_program(
+        "mpl_bubblegum",
+        MPL_BUBBLEGUM_ID,
+        processor!(mock_bubblegum),
+    );
+    program_test.add_program(
+        "spl_account_compression",
+        SPL_ACCOUNT_COMPRESSION_ID,
+        processor!(mock_noop),
+    );
+}
+
+// mpl-core stand-in, moving an asset to its new owner when its owner signs a TransferV1
+fn mock_core(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let transfer = TransferV1 {
+        asset: Pubkey::default(),
+        collection: None,
+        payer: Pubkey::default(),
+        authority: None,
+        new_owner: Pubkey::default(),
+        system_program: None,
+        log_wrapper: None,
+    }
+    .instruction(TransferV1InstructionArgs {
+        compression_proof: None,
+    });
+    if data != transfer.data {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let [asset, _collection, _payer, authority, new_owner, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut base = BaseAssetV1::from_bytes(&asset.try_borrow_data()?)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if base.owner != *authority.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+    base.owner = *new_owner.key;
+    asset
+        .try_borrow_mut_data()?
+        .copy_from_slice(&base.try_to_vec()?);
+    msg!(
+        "transfer_v1 {} from {} to {}",
+        asset.key,
+        authority.key,
+        new_owner.key
+    );
+    Ok(())
+}
+
+/// Stand in for the mpl-core program, which is not available to the bank
+#[allow(dead_code)]
+pub fn add_core_mock(program_test: &mut ProgramTest) {
+    program_test.add_program("mpl_core", mpl_core::ID, processor!(mock_core));
+}
+
+/// Add a Core asset held by `owner`, in `collection` if given, returning its address
+#[allow(dead_code)]
+pub fn add_core_asset(
+    program_test: &mut ProgramTest,
+    owner: &Pubkey,
+    collection: Option<Pubkey>,
+) -> Pubkey {
+    let address = Pubkey::new_unique();
+    let data = BaseAssetV1 {
+        key: Key::AssetV1,
+        owner: *owner,
+        update_authority: collection.map_or(UpdateAuthority::None, UpdateAuthority::Collection),
+        name: "Certificate".to_string(),
+        uri: "https://example.com/certificate.json".to_string(),
+        seq: None,
+    }
+    .try_to_vec()
+    .unwrap();
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: mpl_core::ID,
+            ..Account::default()
+        },
+    );
+    address
+}
+
+/// Read the owner of a Core asset
+#[allow(dead_code)]
+pub async fn core_asset_owner<R: Rpc>(client: &Client<R>, asset: &Pubkey) -> Pubkey {
+    let account = client.rpc().get_account(asset).await.unwrap().unwrap();
+    BaseAssetV1::from_bytes(&account.data).unwrap().owner
+}
@@ -0,0 +1,1314 @@
+use anchor_lang::{AnchorSerialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token::TokenAccount;
+use common::SwapPool;
+use mpl_bubblegum::hash::hash_creators;
+use mpl_bubblegum::types::Creator;
+use mpl_bubblegum::utils::get_asset_id;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::TransactionError;
+use spl_token_swap::error::SwapError;
+use yield_controller_client::fund_sender::{
+    self, CNFTCertificate, CNFTTreeAccounts, CertificateCollection, CertificateCreator,
+    CertificateMetadata, CertificateRecord, ErrorCode, GenericStateInput, LegacyState, State,
+    StoreCertificateAccounts, MPL_CORE_ID,
+};
+use yield_controller_client::{pda, Client, ClientError};
+
+mod common;
+
+// the pool trades 100 SOL against 100 tokens of 6 decimals: 1000 lamports per token base unit
+const POOL_LAMPORTS: u64 = 100 * LAMPORTS_PER_SOL;
+const POOL_TOKENS: u64 = 100_000_000;
+
+#[tokio::test]
+async fn register_and_send_all_funds() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let sunrise_state = Pubkey::new_unique();
+    let destination_accounts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: destination_accounts[0],
+            certificate_vault: Pubkey::new_unique(),
+            spend_threshold: 0,
+            destination_accounts: destination_accounts.clone(),
+            destination_proportions: vec![25, 75],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            lock_certificates: false,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let (state, state_account) = client
+        .fund_sender()
+        .state_for_destination("ecotoken", &sunrise_state)
+        .await
+        .unwrap();
+    assert_eq!(state_account.destination_accounts, destination_accounts);
+
+    // everything above the input account's rent-exempt minimum is sent
+    let rent = Rent::default().minimum_balance(0);
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &pda::input_account(&state),
+        LAMPORTS_PER_SOL + rent,
+    );
+    client
+        .send(&[fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let send = client
+        .fund_sender()
+        .send_all_funds(&payer.pubkey(), &state)
+        .await
+        .unwrap();
+    client
+        .send(&[send], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        common::lamports(&client, &destination_accounts[0]).await,
+        LAMPORTS_PER_SOL / 4
+    );
+    assert_eq!(
+        common::lamports(&client, &destination_accounts[1]).await,
+        LAMPORTS_PER_SOL * 3 / 4
+    );
+    assert_eq!(
+        client
+            .fund_sender()
+            .state(&state)
+            .await
+            .unwrap()
+            .total_spent,
+        LAMPORTS_PER_SOL
+    );
+}
+
+#[tokio::test]
+async fn migrate_legacy_state() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+
+    // a state registered with the original layout
+    let update_authority = Keypair::new();
+    let sunrise_state = Pubkey::new_unique();
+    let destination_account = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let (_, input_account_bump) = pda::input_account_with_bump(&state);
+    let legacy_state = LegacyState {
+        sunrise_state,
+        update_authority: update_authority.pubkey(),
+        destination_name: "ecotoken".to_string(),
+        destination_account,
+        certificate_vault: Pubkey::new_unique(),
+        spend_threshold: 0,
+        total_spent: 5 * LAMPORTS_PER_SOL,
+        input_account_bump,
+    };
+    let mut data = State::DISCRIMINATOR.to_vec();
+    legacy_state.serialize(&mut data).unwrap();
+    assert_eq!(data.len(), LegacyState::space(8));
+    program_test.add_account(
+        state,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: fund_sender::ID,
+            ..Account::default()
+        },
+    );
+    // the update authority tops up the state's rent
+    program_test.add_account(
+        update_authority.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            ..Account::default()
+        },
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    // a legacy state cannot be loaded
+    assert!(client.fund_sender().state(&state).await.is_err());
+
+    // only the update authority can migrate it
+    let migrate = fund_sender::migrate_state(&payer.pubkey(), &state);
+    let err = client
+        .send(&[migrate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::Unauthorized)
+    ));
+
+    let migrate = fund_sender::migrate_state(&update_authority.pubkey(), &state);
+    client
+        .send(&[migrate], &payer.pubkey(), &[&payer, &update_authority])
+        .await
+        .unwrap();
+
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    assert_eq!(state_account.sunrise_state, sunrise_state);
+    assert_eq!(state_account.update_authority, update_authority.pubkey());
+    assert_eq!(state_account.destination_account, destination_account);
+    assert_eq!(state_account.total_spent, 5 * LAMPORTS_PER_SOL);
+    assert_eq!(state_account.input_account_bump, input_account_bump);
+    assert!(state_account.destination_accounts.is_empty());
+    assert_eq!(state_account.total_certificates, 0);
+
+    // a migrated state cannot be migrated again
+    let migrate = fund_sender::migrate_state(&update_authority.pubkey(), &state);
+    let err = client
+        .send(&[migrate], &update_authority.pubkey(), &[&update_authority])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::InvalidLegacyState)
+    ));
+
+    // and its funds can be sent as before
+    let rent = Rent::default().minimum_balance(0);
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &pda::input_account(&state),
+        LAMPORTS_PER_SOL + rent,
+    );
+    client
+        .send(&[fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let send = client
+        .fund_sender()
+        .send_all_funds(&payer.pubkey(), &state)
+        .await
+        .unwrap();
+    client
+        .send(&[send], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    assert_eq!(
+        common::lamports(&client, &destination_account).await,
+        LAMPORTS_PER_SOL
+    );
+}
+
+#[tokio::test]
+async fn swap_and_send_delivers_the_swap_output() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    let mint = common::add_mint(&mut program_test, &Pubkey::new_unique(), 6, POOL_TOKENS);
+    let pool = SwapPool::add(&mut program_test, &mint, POOL_LAMPORTS, POOL_TOKENS);
+    // anyone but the update authority, with enough SOL to pay for the token accounts
+    let caller = Keypair::new();
+    program_test.add_account(
+        caller.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            ..Account::default()
+        },
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+    pool.initialize(&client, &payer).await;
+
+    let sunrise_state = Pubkey::new_unique();
+    let destination_account = Pubkey::new_unique();
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account,
+            certificate_vault: Pubkey::new_unique(),
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: mint,
+            token_spend_threshold: 0,
+            swap_program: pool.program,
+            swap_pool: pool.address.pubkey(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            lock_certificates: false,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &pda::input_account(&state),
+        LAMPORTS_PER_SOL + Rent::default().minimum_balance(0),
+    );
+    client
+        .send(&[register, fund], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+
+    // only the update authority can swap, as it chooses the minimum output
+    let swap = fund_sender::swap_and_send(
+        &caller.pubkey(),
+        &state,
+        &state_account,
+        &pool.accounts,
+        LAMPORTS_PER_SOL,
+        0,
+    );
+    let err = client
+        .send(&[swap], &caller.pubkey(), &[&caller])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::Unauthorized)
+    ));
+
+    // 1 SOL buys 1 SOL * 100 tokens / 101 SOL from the pool
+    let bought = POOL_TOKENS * LAMPORTS_PER_SOL / (POOL_LAMPORTS + LAMPORTS_PER_SOL);
+
+    // which the pool will not give for more than that
+    let swap = fund_sender::swap_and_send(
+        &payer.pubkey(),
+        &state,
+        &state_account,
+        &pool.accounts,
+        LAMPORTS_PER_SOL,
+        bought + 1,
+    );
+    let err = client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == SwapError::ExceededSlippage as u32
+    ));
+
+    let swap = fund_sender::swap_and_send(
+        &payer.pubkey(),
+        &state,
+        &state_account,
+        &pool.accounts,
+        LAMPORTS_PER_SOL,
+        bought,
+    );
+    client
+        .send(&[swap], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let delivered: TokenAccount = client
+        .fetch(&get_associated_token_address(&destination_account, &mint))
+        .await
+        .unwrap();
+    assert_eq!(delivered.amount, bought);
+    let pool_tokens: TokenAccount = client.fetch(&pool.accounts.destination).await.unwrap();
+    assert_eq!(pool_tokens.amount, POOL_TOKENS - bought);
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    assert_eq!(state_account.total_spent, LAMPORTS_PER_SOL);
+    assert_eq!(state_account.total_tokens_spent, bought);
+}
+
+#[tokio::test]
+async fn migrate_destination_refuses_to_strand_funds() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    let sunrise_state = Pubkey::new_unique();
+    let old_state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let new_state = pda::fund_sender_state("renamed", &sunrise_state);
+    let stored_state = pda::fund_sender_state("stored", &sunrise_state);
+    let (old_input_account, new_input_account) = (
+        pda::input_account(&old_state),
+        pda::input_account(&new_state),
+    );
+    // the state's token awaiting sending, and a certificate awaiting storing by another state
+    let token_mint = common::add_mint(&mut program_test, &Pubkey::new_unique(), 6, 100);
+    let certificate = common::add_mint(&mut program_test, &Pubkey::new_unique(), 0, 1);
+    let old_token_account = common::add_token_account(
+        &mut program_test,
+        get_associated_token_address(&old_input_account, &token_mint),
+        &token_mint,
+        &old_input_account,
+        100,
+    );
+    let new_token_account = common::add_token_account(
+        &mut program_test,
+        get_associated_token_address(&new_input_account, &token_mint),
+        &token_mint,
+        &new_input_account,
+        0,
+    );
+    let stored_input_account = pda::input_account(&stored_state);
+    common::add_token_account(
+        &mut program_test,
+        get_associated_token_address(&stored_input_account, &certificate),
+        &certificate,
+        &stored_input_account,
+        1,
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let certificate_vault = Pubkey::new_unique();
+    for (destination_name, token_mint) in [("ecotoken", token_mint), ("stored", Pubkey::default())]
+    {
+        let register = fund_sender::register_state(
+            &payer.pubkey(),
+            &sunrise_state,
+            GenericStateInput {
+                destination_name: destination_name.to_string(),
+                update_authority: payer.pubkey(),
+                destination_account: Pubkey::new_unique(),
+                certificate_vault,
+                spend_threshold: 0,
+                destination_accounts: vec![],
+                destination_proportions: vec![],
+                token_mint,
+                token_spend_threshold: 0,
+                swap_program: Pubkey::default(),
+                swap_pool: Pubkey::default(),
+                certificate_collection: Pubkey::default(),
+                certificate_creator: Pubkey::default(),
+                allowed_certificate_mints: vec![],
+                quarantine_vault: Pubkey::default(),
+                lock_certificates: false,
+                certificate_beneficiary: Pubkey::default(),
+            },
+        );
+        client
+            .send(&[register], &payer.pubkey(), &[&payer])
+            .await
+            .unwrap();
+    }
+
+    // a state whose certificate records are derived from it cannot be migrated
+    let store = client
+        .fund_sender()
+        .store_certificates(
+            &payer.pubkey(),
+            &stored_state,
+            &certificate,
+            StoreCertificateAccounts {
+                certificate_vault,
+                beneficiary: None,
+            },
+        )
+        .await
+        .unwrap();
+    client
+        .send(&[store], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let stored_state_account = client.fund_sender().state(&stored_state).await.unwrap();
+    let migrate = fund_sender::migrate_destination(
+        &payer.pubkey(),
+        &stored_state,
+        &stored_state_account,
+        "restored".to_string(),
+        &[],
+    );
+    let err = client
+        .send(&[migrate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::CertificatesStored)
+    ));
+
+    // nor can one whose token balance would be left behind
+    let old_state_account = client.fund_sender().state(&old_state).await.unwrap();
+    let migrate = fund_sender::migrate_destination(
+        &payer.pubkey(),
+        &old_state,
+        &old_state_account,
+        "renamed".to_string(),
+        &[],
+    );
+    let err = client
+        .send(&[migrate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::TokensNotMigrated)
+    ));
+
+    // passing the balance moves it, closing the old token account into the new input account
+    let token_account_rent = common::lamports(&client, &old_token_account).await;
+    let migrate = fund_sender::migrate_destination(
+        &payer.pubkey(),
+        &old_state,
+        &old_state_account,
+        "renamed".to_string(),
+        &[(old_token_account, new_token_account)],
+    );
+    client
+        .send(&[migrate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let new_tokens: TokenAccount = client.fetch(&new_token_account).await.unwrap();
+    assert_eq!(new_tokens.amount, 100);
+    assert!(client
+        .fetch_optional::<TokenAccount>(&old_token_account)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        common::lamports(&client, &new_input_account).await,
+        token_account_rent
+    );
+    assert!(client
+        .fetch_optional::<State>(&old_state)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn store_certificates_refuses_the_state_funds() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    let sunrise_state = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let input_account = pda::input_account(&state);
+    // the state's token, and wrapped SOL left by a swap, awaiting sending from the input account
+    let token_mint = common::add_mint(&mut program_test, &Pubkey::new_unique(), 6, 100);
+    for (mint, amount) in [(token_mint, 100), (native_mint::ID, 0)] {
+        common::add_token_account(
+            &mut program_test,
+            get_associated_token_address(&input_account, &mint),
+            &mint,
+            &input_account,
+            amount,
+        );
+    }
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let certificate_vault = Pubkey::new_unique();
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: Pubkey::new_unique(),
+            certificate_vault,
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint,
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            lock_certificates: false,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // with no certificate filter, neither fund can be moved to the vault as a certificate
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    for mint in [token_mint, native_mint::ID] {
+        let store = fund_sender::store_certificates(
+            &payer.pubkey(),
+            &state,
+            &state_account,
+            &mint,
+            &anchor_spl::token::ID,
+            false,
+            StoreCertificateAccounts {
+                certificate_vault,
+                beneficiary: None,
+            },
+        );
+        let err = client
+            .send(&[store], &payer.pubkey(), &[&payer])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+                if code == u32::from(ErrorCode::InvalidCertificateMint)
+        ));
+    }
+
+    let input_tokens: TokenAccount = client
+        .fetch(&get_associated_token_address(&input_account, &token_mint))
+        .await
+        .unwrap();
+    assert_eq!(input_tokens.amount, 100);
+}
+
+#[tokio::test]
+async fn store_cnft_certificates_filters_each_certificate() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    common::add_bubblegum_mocks(&mut program_test);
+    let sunrise_state = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    // someone funds the first record's address in advance, which must not block the ledger
+    program_test.add_account(
+        pda::certificate_record(&state, 0),
+        Account {
+            lamports: 1_000_000,
+            ..Account::default()
+        },
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    // the state accepts certificates with its verified creator, or of the allowed asset
+    let merkle_tree = Pubkey::new_unique();
+    let creator = Pubkey::new_unique();
+    let allowed_asset = get_asset_id(&merkle_tree, 1);
+    let certificate_vault = Pubkey::new_unique();
+    let quarantine_vault = Pubkey::new_unique();
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: Pubkey::new_unique(),
+            certificate_vault,
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: creator,
+            allowed_certificate_mints: vec![allowed_asset],
+            quarantine_vault,
+            lock_certificates: false,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let input_account = pda::input_account(&state);
+    let leaf = |nonce: u64, verified: bool| {
+        let creators = vec![CertificateCreator {
+            address: creator,
+            verified,
+            share: 100,
+        }];
+        let leaf_creators: Vec<Creator> = creators.iter().map(Into::into).collect();
+        let mut certificate = CNFTCertificate {
+            root: [0; 32],
+            data_hash: [nonce as u8; 32],
+            creator_hash: hash_creators(&leaf_creators),
+            nonce,
+            index: nonce as u32,
+            creators,
+            metadata: None,
+            proof_len: 0,
+        };
+        let proof = common::prove_cnft(
+            &merkle_tree,
+            &input_account,
+            &input_account,
+            &mut certificate,
+            2,
+        );
+        (certificate, proof)
+    };
+    let tree = CNFTTreeAccounts {
+        merkle_tree,
+        leaf_delegate: None,
+    };
+    let store = |state_account: &State, vault: Pubkey, leaves| {
+        fund_sender::store_cnft_certificates(
+            &payer.pubkey(),
+            &state,
+            state_account,
+            tree,
+            StoreCertificateAccounts {
+                certificate_vault: vault,
+                beneficiary: None,
+            },
+            leaves,
+        )
+    };
+
+    // a certificate by the verified creator, and the allowed asset by an unverified one
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    let batch = store(
+        &state_account,
+        certificate_vault,
+        vec![leaf(0, true), leaf(1, false)],
+    );
+    let logs = client
+        .simulate(std::slice::from_ref(&batch), &payer.pubkey())
+        .await
+        .unwrap()
+        .logs;
+    client
+        .send(&[batch], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    for index in 0..2 {
+        let transfer = format!(
+            "transfer {} leaf {} from {} (delegate {}) to {} with 2 proof nodes",
+            merkle_tree, index, input_account, input_account, certificate_vault
+        );
+        assert!(
+            logs.iter().any(|log| log.ends_with(&transfer)),
+            "{:?}",
+            logs
+        );
+    }
+
+    // any other certificate cannot go to the certificate vault
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    let batch = store(&state_account, certificate_vault, vec![leaf(2, false)]);
+    let err = client
+        .send(&[batch], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::IncorrectHoldAccount)
+    ));
+
+    // so is quarantined instead
+    let batch = store(&state_account, quarantine_vault, vec![leaf(2, false)]);
+    client
+        .send(&[batch], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let records = client
+        .fund_sender()
+        .certificate_records(&state)
+        .await
+        .unwrap();
+    let recorded: Vec<_> = records
+        .iter()
+        .map(|(_, record)| (record.certificate, record.quarantined))
+        .collect();
+    assert_eq!(
+        recorded,
+        vec![
+            (get_asset_id(&merkle_tree, 0), false),
+            (allowed_asset, false),
+            (get_asset_id(&merkle_tree, 2), true),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn store_core_certificate_transfers_the_asset() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    common::add_core_mock(&mut program_test);
+    let sunrise_state = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let input_account = pda::input_account(&state);
+    // a certificate in the state's collection, and one outside of it
+    let collection = Pubkey::new_unique();
+    program_test.add_account(
+        collection,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![0; 8],
+            owner: MPL_CORE_ID,
+            ..Account::default()
+        },
+    );
+    let certificate = common::add_core_asset(&mut program_test, &input_account, Some(collection));
+    let other = common::add_core_asset(&mut program_test, &input_account, None);
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let certificate_vault = Pubkey::new_unique();
+    let quarantine_vault = Pubkey::new_unique();
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: Pubkey::new_unique(),
+            certificate_vault,
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: collection,
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault,
+            lock_certificates: false,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    for (asset, asset_collection, vault) in [
+        (certificate, Some(collection), certificate_vault),
+        (other, None, quarantine_vault),
+    ] {
+        let state_account = client.fund_sender().state(&state).await.unwrap();
+        let store = fund_sender::store_core_certificate(
+            &payer.pubkey(),
+            &state,
+            &state_account,
+            &asset,
+            asset_collection,
+            StoreCertificateAccounts {
+                certificate_vault: vault,
+                beneficiary: None,
+            },
+        );
+        client
+            .send(&[store], &payer.pubkey(), &[&payer])
+            .await
+            .unwrap();
+        assert_eq!(common::core_asset_owner(&client, &asset).await, vault);
+    }
+
+    let records = client
+        .fund_sender()
+        .certificate_records(&state)
+        .await
+        .unwrap();
+    let recorded: Vec<_> = records
+        .iter()
+        .map(|(_, record)| (record.certificate, record.quarantined))
+        .collect();
+    assert_eq!(recorded, vec![(certificate, false), (other, true)]);
+}
+
+#[tokio::test]
+async fn store_cnft_certificate_checks_the_leaf_metadata() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    common::add_bubblegum_mocks(&mut program_test);
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    // the state accepts certificates in its collection
+    let sunrise_state = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let collection = Pubkey::new_unique();
+    let certificate_vault = Pubkey::new_unique();
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: Pubkey::new_unique(),
+            certificate_vault,
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: collection,
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            lock_certificates: false,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let metadata = |verified: bool| CertificateMetadata {
+        name: "Certificate".to_string(),
+        symbol: "CERT".to_string(),
+        uri: "https://example.com/certificate.json".to_string(),
+        seller_fee_basis_points: 0,
+        primary_sale_happened: false,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(0),
+        collection: Some(CertificateCollection {
+            verified,
+            key: collection,
+        }),
+        uses: None,
+        token_program_version: 0,
+        creators: vec![],
+    };
+    // a leaf with the metadata, delegated to another account
+    let merkle_tree = Pubkey::new_unique();
+    let leaf_delegate = Pubkey::new_unique();
+    let tree = CNFTTreeAccounts {
+        merkle_tree,
+        leaf_delegate: Some(leaf_delegate),
+    };
+    let input_account = pda::input_account(&state);
+    let leaf = |metadata: &CertificateMetadata, data_hash: [u8; 32], delegate: &Pubkey| {
+        let mut certificate = CNFTCertificate {
+            root: [0; 32],
+            data_hash,
+            creator_hash: hash_creators(&[]),
+            nonce: 0,
+            index: 0,
+            creators: vec![],
+            metadata: Some(metadata.clone()),
+            proof_len: 0,
+        };
+        let proof = common::prove_cnft(&merkle_tree, &input_account, delegate, &mut certificate, 1);
+        (certificate, proof)
+    };
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    let store = |(certificate, proof): (CNFTCertificate, Vec<Pubkey>)| {
+        fund_sender::store_cnft_certificate(
+            &payer.pubkey(),
+            &state,
+            &state_account,
+            tree,
+            StoreCertificateAccounts {
+                certificate_vault,
+                beneficiary: None,
+            },
+            certificate,
+            &proof,
+        )
+    };
+    let assert_fails = |err: ClientError, error_code: ErrorCode| {
+        assert!(matches!(
+            err,
+            ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+                if code == u32::from(error_code)
+        ));
+    };
+
+    // metadata that does not hash to the leaf's data hash is refused
+    let in_collection = metadata(true);
+    let err = client
+        .send(
+            &[store(leaf(&in_collection, [0; 32], &leaf_delegate))],
+            &payer.pubkey(),
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+    assert_fails(err, ErrorCode::DataHashMismatch);
+
+    // as is a leaf whose collection is not verified
+    let unverified = metadata(false);
+    let err = client
+        .send(
+            &[store(leaf(
+                &unverified,
+                unverified.data_hash().unwrap(),
+                &leaf_delegate,
+            ))],
+            &payer.pubkey(),
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+    assert_fails(err, ErrorCode::InvalidCertificate);
+
+    // the transfer must prove the leaf with the delegate passed
+    let data_hash = in_collection.data_hash().unwrap();
+    let err = client
+        .send(
+            &[store(leaf(&in_collection, data_hash, &input_account))],
+            &payer.pubkey(),
+            &[&payer],
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidArgument
+        ))
+    ));
+
+    // a leaf in the collection is transferred, the input account signing for its delegate
+    let certificate = store(leaf(&in_collection, data_hash, &leaf_delegate));
+    let logs = client
+        .simulate(std::slice::from_ref(&certificate), &payer.pubkey())
+        .await
+        .unwrap()
+        .logs;
+    client
+        .send(&[certificate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let transfer = format!(
+        "transfer {} leaf 0 from {} (delegate {}) to {} with 1 proof nodes",
+        merkle_tree, input_account, leaf_delegate, certificate_vault
+    );
+    assert!(
+        logs.iter().any(|log| log.ends_with(&transfer)),
+        "{:?}",
+        logs
+    );
+    let records = client
+        .fund_sender()
+        .certificate_records(&state)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].1.certificate, get_asset_id(&merkle_tree, 0));
+    assert!(!records[0].1.quarantined);
+}
+
+#[tokio::test]
+async fn release_core_certificate_from_the_locked_vault() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    common::add_core_mock(&mut program_test);
+    let sunrise_state = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let certificate = common::add_core_asset(&mut program_test, &pda::input_account(&state), None);
+    // an account other than the update authority, which can store but not attribute certificates
+    let other = Keypair::new();
+    program_test.add_account(
+        other.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            ..Account::default()
+        },
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let certificate_vault = pda::certificate_vault(&state);
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: Pubkey::new_unique(),
+            certificate_vault,
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            lock_certificates: true,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let beneficiary = Pubkey::new_unique();
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    let store = |payer: &Pubkey, beneficiary: Option<Pubkey>| {
+        fund_sender::store_core_certificate(
+            payer,
+            &state,
+            &state_account,
+            &certificate,
+            None,
+            StoreCertificateAccounts {
+                certificate_vault,
+                beneficiary,
+            },
+        )
+    };
+
+    // only the update authority can record a certificate against a beneficiary
+    let err = client
+        .send(
+            &[store(&other.pubkey(), Some(beneficiary))],
+            &other.pubkey(),
+            &[&other],
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::Unauthorized)
+    ));
+    client
+        .send(&[store(&other.pubkey(), None)], &other.pubkey(), &[&other])
+        .await
+        .unwrap();
+    assert_eq!(
+        common::core_asset_owner(&client, &certificate).await,
+        certificate_vault
+    );
+
+    // the update authority releases it from the vault, attributing its record to the beneficiary
+    let certificate_record = pda::certificate_record(&state, 0);
+    let release = fund_sender::release_core_certificate(
+        &payer.pubkey(),
+        &state,
+        &certificate,
+        None,
+        &beneficiary,
+        Some(certificate_record),
+    );
+    client
+        .send(&[release], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    assert_eq!(
+        common::core_asset_owner(&client, &certificate).await,
+        beneficiary
+    );
+    let record: CertificateRecord = client.fetch(&certificate_record).await.unwrap();
+    assert_eq!(record.beneficiary, beneficiary);
+}
+
+#[tokio::test]
+async fn release_cnft_certificate_from_the_locked_vault() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "fund_sender",
+        fund_sender::ID,
+        processor!(common::fund_sender_entry),
+    );
+    common::add_bubblegum_mocks(&mut program_test);
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let sunrise_state = Pubkey::new_unique();
+    let state = pda::fund_sender_state("ecotoken", &sunrise_state);
+    let certificate_vault = pda::certificate_vault(&state);
+    let register = fund_sender::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            destination_name: "ecotoken".to_string(),
+            update_authority: payer.pubkey(),
+            destination_account: Pubkey::new_unique(),
+            certificate_vault,
+            spend_threshold: 0,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            lock_certificates: true,
+            certificate_beneficiary: Pubkey::default(),
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let merkle_tree = Pubkey::new_unique();
+    let input_account = pda::input_account(&state);
+    let mut certificate = CNFTCertificate {
+        root: [0; 32],
+        data_hash: [0; 32],
+        creator_hash: hash_creators(&[]),
+        nonce: 0,
+        index: 0,
+        creators: vec![],
+        metadata: None,
+        proof_len: 0,
+    };
+    let proof = common::prove_cnft(
+        &merkle_tree,
+        &input_account,
+        &input_account,
+        &mut certificate,
+        1,
+    );
+    let state_account = client.fund_sender().state(&state).await.unwrap();
+    let store = fund_sender::store_cnft_certificate(
+        &payer.pubkey(),
+        &state,
+        &state_account,
+        CNFTTreeAccounts {
+            merkle_tree,
+            leaf_delegate: None,
+        },
+        StoreCertificateAccounts {
+            certificate_vault,
+            beneficiary: None,
+        },
+        certificate.clone(),
+        &proof,
+    );
+    client
+        .send(&[store], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // the leaf is now the vault's
+    let proof = common::prove_cnft(
+        &merkle_tree,
+        &certificate_vault,
+        &certificate_vault,
+        &mut certificate,
+        1,
+    );
+    let beneficiary = Pubkey::new_unique();
+    let certificate_record = pda::certificate_record(&state, 0);
+    let release = |nonce: u64| {
+        fund_sender::release_cnft_certificate(
+            &payer.pubkey(),
+            &state,
+            &merkle_tree,
+            &beneficiary,
+            Some(certificate_record),
+            CNFTCertificate {
+                nonce,
+                index: nonce as u32,
+                ..certificate.clone()
+            },
+            &proof,
+        )
+    };
+
+    // the record must be the released certificate's
+    let err = client
+        .send(&[release(1)], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code)))
+            if code == u32::from(ErrorCode::IncorrectCertificateRecord)
+    ));
+
+    // the vault signs as the leaf's owner and delegate
+    let release = release(0);
+    let logs = client
+        .simulate(std::slice::from_ref(&release), &payer.pubkey())
+        .await
+        .unwrap()
+        .logs;
+    client
+        .send(&[release], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let transfer = format!(
+        "transfer {} leaf 0 from {} (delegate {}) to {} with 1 proof nodes",
+        merkle_tree, certificate_vault, certificate_vault, beneficiary
+    );
+    assert!(
+        logs.iter().any(|log| log.ends_with(&transfer)),
+        "{:?}",
+        logs
+    );
+    let record: CertificateRecord = client.fetch(&certificate_record).await.unwrap();
+    assert_eq!(record.certificate, get_asset_id(&merkle_tree, 0));
+    assert_eq!(record.beneficiary, beneficiary);
+}
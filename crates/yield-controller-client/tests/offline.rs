@@ -0,0 +1,124 @@
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::nonce::State as NonceState;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::presigner::Presigner;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use yield_controller_client::offline::SignOnly;
+use yield_controller_client::yield_router::{self, GenericStateInput};
+use yield_controller_client::{BlockhashQuery, Client};
+
+mod common;
+
+#[tokio::test]
+async fn update_state_signed_offline_with_durable_nonce() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "yield_router",
+        yield_router::ID,
+        processor!(common::yield_router_entry),
+    );
+    let mut context = program_test.start_with_context().await;
+    let client = Client::new(context.banks_client.clone());
+    let fee_payer = context.payer.insecure_clone();
+    let authority = Keypair::new();
+
+    let sunrise_state = Pubkey::new_unique();
+    let state_in = GenericStateInput {
+        update_authority: authority.pubkey(),
+        output_yield_accounts: vec![Pubkey::new_unique()],
+        spend_proportions: vec![100],
+        spend_threshold: 0,
+    };
+    let register = yield_router::register_state(&fee_payer.pubkey(), &sunrise_state, state_in);
+    let nonce = Keypair::new();
+    let create_nonce = system_instruction::create_nonce_account(
+        &fee_payer.pubkey(),
+        &nonce.pubkey(),
+        &fee_payer.pubkey(),
+        Rent::default().minimum_balance(NonceState::size()),
+    );
+    client
+        .send(
+            &[&[register], create_nonce.as_slice()].concat(),
+            &fee_payer.pubkey(),
+            &[&fee_payer, &nonce],
+        )
+        .await
+        .unwrap();
+    // a nonce can only be advanced once the cluster has moved past the blockhash it stores
+    context.warp_to_slot(2).unwrap();
+
+    let (state, _) = client
+        .yield_router()
+        .state_for_sunrise_state(&sunrise_state)
+        .await
+        .unwrap();
+    let new_output = Pubkey::new_unique();
+    let update = yield_router::update_state(
+        &authority.pubkey(),
+        &state,
+        GenericStateInput {
+            update_authority: authority.pubkey(),
+            output_yield_accounts: vec![new_output],
+            spend_proportions: vec![100],
+            spend_threshold: 0,
+        },
+    );
+    let query = BlockhashQuery::Nonce {
+        account: nonce.pubkey(),
+        authority: fee_payer.pubkey(),
+        blockhash: None,
+    };
+    let unsigned = client
+        .transaction(std::slice::from_ref(&update), &fee_payer.pubkey(), &query)
+        .await
+        .unwrap();
+    let nonce_blockhash = unsigned.message.recent_blockhash;
+
+    // the offline authority builds the same transaction from the nonce it is given, without the cluster
+    let offline_query = BlockhashQuery::Nonce {
+        account: nonce.pubkey(),
+        authority: fee_payer.pubkey(),
+        blockhash: Some(nonce_blockhash),
+    };
+    let mut offline = client
+        .transaction(
+            std::slice::from_ref(&update),
+            &fee_payer.pubkey(),
+            &offline_query,
+        )
+        .await
+        .unwrap();
+    assert_eq!(offline.message, unsigned.message);
+    offline
+        .try_partial_sign(&[&authority], nonce_blockhash)
+        .unwrap();
+    let sign_only = SignOnly::new(&offline);
+    assert_eq!(sign_only.blockhash, nonce_blockhash);
+    assert_eq!(sign_only.absent, vec![fee_payer.pubkey()]);
+    assert!(!sign_only.is_complete());
+    let (signer, signature) = sign_only.signers[0];
+    assert_eq!(signer, authority.pubkey());
+
+    // a signature of a different message is rejected
+    let mut tampered = unsigned.clone();
+    tampered.signatures[1] = Keypair::new().sign_message(&tampered.message_data());
+    assert_eq!(SignOnly::new(&tampered).bad_sig, vec![authority.pubkey()]);
+
+    let mut online = client
+        .transaction(&[update], &fee_payer.pubkey(), &query)
+        .await
+        .unwrap();
+    let presigner = Presigner::new(&signer, &signature);
+    online
+        .try_sign(&[&fee_payer as &dyn Signer, &presigner], nonce_blockhash)
+        .unwrap();
+    assert!(SignOnly::new(&online).is_complete());
+    client.send_transaction(&online).await.unwrap();
+
+    let state_account = client.yield_router().state(&state).await.unwrap();
+    assert_eq!(state_account.output_yield_accounts, vec![new_output]);
+}
@@ -0,0 +1,143 @@
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use yield_controller_client::yield_router::{self, GenericStateInput};
+use yield_controller_client::{pda, Client};
+
+mod common;
+
+#[tokio::test]
+async fn register_and_allocate_yield() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "yield_router",
+        yield_router::ID,
+        processor!(common::yield_router_entry),
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let sunrise_state = Pubkey::new_unique();
+    let output_yield_accounts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let register = yield_router::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            update_authority: payer.pubkey(),
+            output_yield_accounts: output_yield_accounts.clone(),
+            spend_proportions: vec![30, 70],
+            spend_threshold: 0,
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let (state, state_account) = client
+        .yield_router()
+        .state_for_sunrise_state(&sunrise_state)
+        .await
+        .unwrap();
+    assert_eq!(state_account.output_yield_accounts, output_yield_accounts);
+    assert_eq!(state_account.spend_proportions, vec![30, 70]);
+
+    // the input yield account keeps its rent-exempt minimum
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &pda::input_yield_account(&state),
+        LAMPORTS_PER_SOL + Rent::default().minimum_balance(0),
+    );
+    // the output yield accounts are created by the yield they receive
+    let amount = 10 * Rent::default().minimum_balance(0);
+    let allocate = client
+        .yield_router()
+        .allocate_yield(&payer.pubkey(), &state, amount)
+        .await
+        .unwrap();
+
+    let simulation = client
+        .simulate(&[fund.clone(), allocate.clone()], &payer.pubkey())
+        .await
+        .unwrap();
+    assert!(simulation.err.is_none(), "{:?}", simulation.logs);
+
+    client
+        .send(&[fund, allocate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        common::lamports(&client, &output_yield_accounts[1]).await,
+        amount * 70 / 100
+    );
+}
+
+#[tokio::test]
+async fn allocate_yield_leaves_rounding_dust_in_the_input_account() {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "yield_router",
+        yield_router::ID,
+        processor!(common::yield_router_entry),
+    );
+    let (banks_client, payer, _) = program_test.start().await;
+    let client = Client::new(banks_client);
+
+    let sunrise_state = Pubkey::new_unique();
+    let output_yield_accounts = vec![
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let register = yield_router::register_state(
+        &payer.pubkey(),
+        &sunrise_state,
+        GenericStateInput {
+            update_authority: payer.pubkey(),
+            output_yield_accounts: output_yield_accounts.clone(),
+            spend_proportions: vec![33, 33, 34],
+            spend_threshold: 0,
+        },
+    );
+    client
+        .send(&[register], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let (state, _) = client
+        .yield_router()
+        .state_for_sunrise_state(&sunrise_state)
+        .await
+        .unwrap();
+
+    let input_yield_account = pda::input_yield_account(&state);
+    let amount = 10 * LAMPORTS_PER_SOL + 99;
+    let fund = system_instruction::transfer(
+        &payer.pubkey(),
+        &input_yield_account,
+        amount + Rent::default().minimum_balance(0),
+    );
+    let allocate = client
+        .yield_router()
+        .allocate_yield(&payer.pubkey(), &state, amount)
+        .await
+        .unwrap();
+    client
+        .send(&[fund, allocate], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // each share is rounded down, none receiving the remainder
+    let mut received = vec![];
+    for output_yield_account in &output_yield_accounts {
+        received.push(common::lamports(&client, output_yield_account).await);
+    }
+    assert_eq!(received, vec![3_300_000_032, 3_300_000_032, 3_400_000_033]);
+    assert_eq!(
+        common::lamports(&client, &input_yield_account).await,
+        Rent::default().minimum_balance(0) + 2
+    );
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::instructions::{
+    MintV1Cpi, MintV1CpiAccounts, MintV1InstructionArgs, TransferCpiBuilder,
+};
+use mpl_bubblegum::types::MetadataArgs;
+
+pub struct AccountsCNFTMint<'a> {
+    pub tree_config: AccountInfo<'a>,
+    pub leaf_owner: AccountInfo<'a>,
+    pub leaf_delegate: AccountInfo<'a>,
+    pub merkle_tree: AccountInfo<'a>,
+    pub payer: AccountInfo<'a>,
+    // the PDA that created the tree or is its delegate, which signs for it
+    pub tree_creator_or_delegate: AccountInfo<'a>,
+    pub log_wrapper: AccountInfo<'a>,
+    pub compression_program: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub bubblegum_program: AccountInfo<'a>,
+}
+
+pub struct AccountsCNFTTransfer<'a> {
+    pub tree_authority: AccountInfo<'a>,
+    // the PDA owning the leaf, which signs for it
+    pub leaf_owner: AccountInfo<'a>,
+    // the leaf's delegate, which is the leaf owner unless delegated to another account
+    pub leaf_delegate: AccountInfo<'a>,
+    pub new_leaf_owner: AccountInfo<'a>,
+    pub merkle_tree: AccountInfo<'a>,
+    pub log_wrapper: AccountInfo<'a>,
+    pub compression_program: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub bubblegum_program: AccountInfo<'a>,
+}
+
+/// Mint a compressed NFT with `metadata` to a tree, signed by the seeds of
+/// its PDA creator or delegate.
+pub fn mint_cnft_signed(
+    accounts: &AccountsCNFTMint,
+    metadata: MetadataArgs,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    MintV1Cpi::new(
+        &accounts.bubblegum_program,
+        MintV1CpiAccounts {
+            tree_config: &accounts.tree_config,
+            leaf_owner: &accounts.leaf_owner,
+            leaf_delegate: &accounts.leaf_delegate,
+            merkle_tree: &accounts.merkle_tree,
+            payer: &accounts.payer,
+            tree_creator_or_delegate: &accounts.tree_creator_or_delegate,
+            log_wrapper: &accounts.log_wrapper,
+            compression_program: &accounts.compression_program,
+            system_program: &accounts.system_program,
+        },
+        MintV1InstructionArgs { metadata },
+    )
+    .invoke_signed(&[signer_seeds])
+    .map_err(Into::into)
+}
+
+/// Transfer a compressed NFT owned by a PDA, signed by its seeds, with `proof` being the
+/// (canopy-truncated) merkle proof of the leaf.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_cnft_signed<'a>(
+    accounts: &AccountsCNFTTransfer<'a>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+    proof: &[AccountInfo<'a>],
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    // the "accounts" (hashes) that make up the merkle proof
+    let proof_accounts: Vec<(&AccountInfo<'a>, bool, bool)> =
+        proof.iter().map(|node| (node, false, false)).collect();
+
+    TransferCpiBuilder::new(&accounts.bubblegum_program)
+        .tree_config(&accounts.tree_authority)
+        // the PDA signs as the leaf owner, so the delegate does not need to sign
+        .leaf_owner(&accounts.leaf_owner, true)
+        .leaf_delegate(&accounts.leaf_delegate, false)
+        .new_leaf_owner(&accounts.new_leaf_owner)
+        .merkle_tree(&accounts.merkle_tree)
+        .log_wrapper(&accounts.log_wrapper)
+        .compression_program(&accounts.compression_program)
+        .system_program(&accounts.system_program)
+        .root(root)
+        .data_hash(data_hash)
+        .creator_hash(creator_hash)
+        .nonce(nonce)
+        .index(index)
+        .add_remaining_accounts(&proof_accounts)
+        .invoke_signed(&[signer_seeds])
+        .map_err(Into::into)
+}
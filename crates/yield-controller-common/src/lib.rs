@@ -0,0 +1,19 @@
+//! Helpers shared by the yield controller programs: PDA seeds, rent-aware balances,
+//! proportion splitting, PDA-signed SOL and SPL token transfers, AMM swaps, token retirement
+//! and Bubblegum compressed NFT minting and transfers.
+//!
+//! Errors stay with each program, so that their error codes and messages are unchanged:
+//! helpers that can fail on a program-level condition take the error to return as an argument.
+#![allow(clippy::result_large_err)]
+
+#[cfg(feature = "bubblegum")]
+pub mod bubblegum;
+pub mod proportions;
+pub mod rent;
+#[cfg(feature = "token")]
+pub mod retirement;
+pub mod seeds;
+pub mod swap;
+#[cfg(feature = "token")]
+pub mod token;
+pub mod transfer;
@@ -0,0 +1,117 @@
+// proportions are percentages, and must sum to this
+pub const TOTAL_PROPORTION: u16 = 100;
+
+/// The sum of `proportions`, which cannot overflow for any number of destinations a state can hold.
+pub fn proportions_total(proportions: &[u8]) -> u16 {
+    proportions
+        .iter()
+        .map(|proportion| *proportion as u16)
+        .sum()
+}
+
+/// Whether `proportions` sum to 100.
+pub fn is_valid_proportions(proportions: &[u8]) -> bool {
+    proportions_total(proportions) == TOTAL_PROPORTION
+}
+
+/// `proportion` percent of `amount`, rounded down.
+pub fn proportion_of(amount: u64, proportion: u8) -> u64 {
+    (amount as u128 * proportion as u128 / TOTAL_PROPORTION as u128) as u64
+}
+
+/// Split `amount` between destinations by their `proportions`, rounding each share down.
+/// Any rounding remainder is not allocated, so stays with the source.
+pub fn split_by_proportions(amount: u64, proportions: &[u8]) -> Vec<u64> {
+    proportions
+        .iter()
+        .map(|proportion| proportion_of(amount, *proportion))
+        .collect()
+}
+
+/// Split `amount` between destinations by their `proportions`, rounding each share down.
+/// The last destination receives any rounding remainder, so the shares always sum to `amount`.
+pub fn split_all_by_proportions(amount: u64, proportions: &[u8]) -> Vec<u64> {
+    let mut remaining = amount;
+    proportions
+        .iter()
+        .enumerate()
+        .map(|(i, proportion)| {
+            let share = if i == proportions.len() - 1 {
+                remaining
+            } else {
+                proportion_of(amount, *proportion).min(remaining)
+            };
+            remaining -= share;
+            share
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proportions_must_sum_to_100() {
+        assert!(is_valid_proportions(&[100]));
+        assert!(is_valid_proportions(&[30, 70]));
+        assert!(is_valid_proportions(&[25, 25, 25, 25]));
+        assert!(!is_valid_proportions(&[]));
+        assert!(!is_valid_proportions(&[30, 60]));
+        assert!(!is_valid_proportions(&[50, 51]));
+    }
+
+    #[test]
+    fn proportions_total_does_not_overflow() {
+        // would wrap to 100 if summed as u8
+        assert_eq!(proportions_total(&[200, 156]), 356);
+        assert!(!is_valid_proportions(&[200, 156]));
+    }
+
+    #[test]
+    fn proportion_of_rounds_down() {
+        assert_eq!(proportion_of(1_000, 30), 300);
+        assert_eq!(proportion_of(999, 30), 299);
+        assert_eq!(proportion_of(u64::MAX, 100), u64::MAX);
+        assert_eq!(proportion_of(u64::MAX, 0), 0);
+    }
+
+    #[test]
+    fn split_leaves_the_remainder_unallocated() {
+        assert_eq!(split_by_proportions(1_000, &[30, 70]), vec![300, 700]);
+        assert_eq!(split_by_proportions(999, &[30, 70]), vec![299, 699]);
+        assert_eq!(split_by_proportions(10, &[33, 33, 34]), vec![3, 3, 3]);
+        assert_eq!(split_by_proportions(1, &[50, 50]), vec![0, 0]);
+    }
+
+    #[test]
+    fn split_all_gives_remainder_to_last_destination() {
+        assert_eq!(split_all_by_proportions(1_000, &[30, 70]), vec![300, 700]);
+        assert_eq!(split_all_by_proportions(999, &[30, 70]), vec![299, 700]);
+        assert_eq!(split_all_by_proportions(10, &[33, 33, 34]), vec![3, 3, 4]);
+        assert_eq!(split_all_by_proportions(1, &[50, 50]), vec![0, 1]);
+    }
+
+    #[test]
+    fn split_all_sums_to_amount() {
+        for amount in [0, 1, 7, 99, 1_000_000_007, u64::MAX] {
+            for proportions in [&[100][..], &[1, 99], &[33, 33, 34], &[10, 20, 30, 40]] {
+                let shares = split_all_by_proportions(amount, proportions);
+                assert_eq!(shares.len(), proportions.len());
+                assert_eq!(
+                    shares.iter().map(|s| *s as u128).sum::<u128>(),
+                    amount as u128
+                );
+                // each share but the last is rounded down as split_by_proportions does
+                let floored = split_by_proportions(amount, proportions);
+                assert_eq!(shares[..shares.len() - 1], floored[..floored.len() - 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn split_between_no_destinations_is_empty() {
+        assert!(split_by_proportions(1_000, &[]).is_empty());
+        assert!(split_all_by_proportions(1_000, &[]).is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+/// The lamports above `minimum_balance` out of `lamports`.
+pub fn spendable_lamports(lamports: u64, minimum_balance: u64) -> u64 {
+    lamports.saturating_sub(minimum_balance)
+}
+
+/// The lamports an account can pay out while staying rent-exempt for its current data size,
+/// under the given rent parameters.
+pub fn spendable_balance_with_rent(account: &AccountInfo, rent: &Rent) -> u64 {
+    spendable_lamports(account.lamports(), rent.minimum_balance(account.data_len()))
+}
+
+/// The lamports an account can pay out while staying rent-exempt for its current data size.
+pub fn spendable_balance(account: &AccountInfo) -> Result<u64> {
+    Ok(spendable_balance_with_rent(account, &Rent::get()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spendable_lamports_saturates_below_minimum() {
+        assert_eq!(spendable_lamports(1_000, 400), 600);
+        assert_eq!(spendable_lamports(400, 400), 0);
+        assert_eq!(spendable_lamports(100, 400), 0);
+    }
+
+    #[test]
+    fn spendable_balance_keeps_rent_for_data() {
+        let rent = Rent::default();
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut data = vec![0u8; 100];
+        let mut lamports = rent.minimum_balance(100) + 5_000;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(spendable_balance_with_rent(&account, &rent), 5_000);
+    }
+
+    #[test]
+    fn spendable_balance_of_empty_account_keeps_base_rent() {
+        let rent = Rent::default();
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut data = vec![];
+        let mut lamports = rent.minimum_balance(0) - 1;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(spendable_balance_with_rent(&account, &rent), 0);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::token::{burn_signed, transfer_token_signed};
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RetirementBackend {
+    /// Burn purchased tokens with the SPL token program
+    #[default]
+    Burn,
+    /// Transfer purchased tokens to `retirement_vault` (e.g. a retirement or escrow program's vault)
+    /// and record a `RetirementReceipt`
+    Vault,
+}
+
+/// A program's record of tokens retired into its retirement vault, declared with [`retirement_receipt!`]
+pub trait RetirementRecord {
+    fn record(
+        &mut self,
+        state: Pubkey,
+        mint: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        index: u64,
+    ) -> Result<()>;
+}
+
+/// Declare the `RetirementReceipt` account in the calling program, which owns it.
+/// Expects `anchor_lang::prelude::*` to be in scope.
+#[macro_export]
+macro_rules! retirement_receipt {
+    () => {
+        /// A record of tokens retired into the retirement vault, one per retirement
+        #[account]
+        pub struct RetirementReceipt {
+            pub state: Pubkey,
+            pub mint: Pubkey,
+            pub beneficiary: Pubkey,
+            pub amount: u64,
+            pub timestamp: i64,
+            pub index: u64,
+        }
+
+        impl RetirementReceipt {
+            pub const SPACE: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 /* Discriminator */;
+        }
+
+        impl $crate::retirement::RetirementRecord for RetirementReceipt {
+            fn record(
+                &mut self,
+                state: Pubkey,
+                mint: Pubkey,
+                beneficiary: Pubkey,
+                amount: u64,
+                index: u64,
+            ) -> Result<()> {
+                self.state = state;
+                self.mint = mint;
+                self.beneficiary = beneficiary;
+                self.amount = amount;
+                self.timestamp = Clock::get()?.unix_timestamp;
+                self.index = index;
+                Ok(())
+            }
+        }
+    };
+}
+
+/// The accounts to retire tokens held in `token_account`, owned by the PDA `authority`
+pub struct RetirementAccounts<'a> {
+    pub token_program: AccountInfo<'a>,
+    pub mint: AccountInfo<'a>,
+    pub token_account: AccountInfo<'a>,
+    pub authority: AccountInfo<'a>,
+    // required when retiring to a vault
+    pub retirement_vault: Option<AccountInfo<'a>>,
+}
+
+/// Take `amount` tokens out of circulation using `backend`, signed by the seeds of `authority`.
+/// Retiring to a vault records a receipt against `authority` at index `total_retirements`,
+/// which is incremented, and returns `missing_accounts` without a vault and receipt.
+/// Burning returns `unexpected_accounts` if a vault or receipt is passed, so that a receipt
+/// cannot be created at the next index while burning, blocking the switch to a vault.
+#[allow(clippy::too_many_arguments)]
+pub fn retire_signed<'a, R: RetirementRecord>(
+    backend: RetirementBackend,
+    accounts: &RetirementAccounts<'a>,
+    retirement_receipt: Option<&mut R>,
+    beneficiary: Pubkey,
+    total_retirements: &mut u64,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+    missing_accounts: impl Into<Error>,
+    unexpected_accounts: impl Into<Error>,
+) -> Result<()> {
+    match backend {
+        RetirementBackend::Burn => {
+            if accounts.retirement_vault.is_some() || retirement_receipt.is_some() {
+                return Err(unexpected_accounts.into());
+            }
+            burn_signed(
+                &accounts.token_program,
+                accounts.mint.clone(),
+                accounts.token_account.clone(),
+                accounts.authority.clone(),
+                amount,
+                signer_seeds,
+            )
+        }
+        RetirementBackend::Vault => {
+            let (Some(retirement_vault), Some(retirement_receipt)) =
+                (&accounts.retirement_vault, retirement_receipt)
+            else {
+                return Err(missing_accounts.into());
+            };
+
+            transfer_token_signed(
+                &accounts.token_program,
+                accounts.token_account.clone(),
+                retirement_vault.clone(),
+                accounts.authority.clone(),
+                amount,
+                signer_seeds,
+            )?;
+
+            retirement_receipt.record(
+                accounts.authority.key(),
+                accounts.mint.key(),
+                beneficiary,
+                amount,
+                *total_retirements,
+            )?;
+            *total_retirements += 1;
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,17 @@
+// seeds shared by all programs
+pub const STATE: &[u8] = b"state";
+
+// yield-router
+pub const INPUT_YIELD_ACCOUNT: &[u8] = b"input_yield_account";
+
+// fund-sender
+pub const INPUT_ACCOUNT: &[u8] = b"input_account";
+pub const CERTIFICATE_RECORD: &[u8] = b"certificate_record";
+pub const CERTIFICATE_VAULT: &[u8] = b"certificate_vault";
+
+// buy-burn-fixed and buy-burn-switchboard
+pub const YIELD_ACCOUNT: &[u8] = b"yield_account";
+pub const RETIREMENT_RECEIPT: &[u8] = b"retirement_receipt";
+
+// buy-burn-switchboard
+pub const ADJUSTMENT_LOG: &[u8] = b"adjustment_log";
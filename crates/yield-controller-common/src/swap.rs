@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+// tag of the `Swap` instruction in the SPL token-swap program and its forks
+const SWAP_INSTRUCTION_TAG: u8 = 1;
+
+/// Accounts needed to swap through a constant-product pool using the SPL token-swap interface.
+/// Any AMM exposing the same instruction layout can be plugged in via a state's `swap_program`.
+pub struct SwapAccounts<'a> {
+    pub swap_program: AccountInfo<'a>,
+    pub pool: AccountInfo<'a>,
+    pub pool_authority: AccountInfo<'a>,
+    pub user_transfer_authority: AccountInfo<'a>,
+    pub source: AccountInfo<'a>,
+    pub pool_source: AccountInfo<'a>,
+    pub pool_destination: AccountInfo<'a>,
+    pub destination: AccountInfo<'a>,
+    pub pool_mint: AccountInfo<'a>,
+    pub pool_fee_account: AccountInfo<'a>,
+    pub token_program: AccountInfo<'a>,
+}
+
+pub fn swap<'a>(
+    accounts: &SwapAccounts<'a>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut data: Vec<u8> = vec![SWAP_INSTRUCTION_TAG];
+    data.extend(amount_in.to_le_bytes());
+    data.extend(minimum_amount_out.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: accounts.swap_program.key(),
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.pool.key(), false),
+            AccountMeta::new_readonly(accounts.pool_authority.key(), false),
+            AccountMeta::new_readonly(accounts.user_transfer_authority.key(), true),
+            AccountMeta::new(accounts.source.key(), false),
+            AccountMeta::new(accounts.pool_source.key(), false),
+            AccountMeta::new(accounts.pool_destination.key(), false),
+            AccountMeta::new(accounts.destination.key(), false),
+            AccountMeta::new(accounts.pool_mint.key(), false),
+            AccountMeta::new(accounts.pool_fee_account.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        data,
+    };
+
+    invoke_signed(
+        &ix,
+        &[
+            accounts.pool.clone(),
+            accounts.pool_authority.clone(),
+            accounts.user_transfer_authority.clone(),
+            accounts.source.clone(),
+            accounts.pool_source.clone(),
+            accounts.pool_destination.clone(),
+            accounts.destination.clone(),
+            accounts.pool_mint.clone(),
+            accounts.pool_fee_account.clone(),
+            accounts.token_program.clone(),
+            accounts.swap_program.clone(),
+        ],
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
@@ -0,0 +1,114 @@
+use crate::transfer::transfer_native_signed;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, CloseAccount, SyncNative, Transfer};
+use anchor_spl::token_interface::{self, TransferChecked};
+
+/// Burn tokens from `from`, signed by the seeds of its PDA `authority`.
+pub fn burn_signed<'a>(
+    token_program: &AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        token_program.clone(),
+        Burn {
+            mint,
+            from,
+            authority,
+        },
+    );
+    token::burn(cpi_ctx.with_signer(&[signer_seeds]), amount)
+}
+
+/// Transfer tokens of the legacy token program, signed by the seeds of the PDA `authority`.
+pub fn transfer_token_signed<'a>(
+    token_program: &AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        token_program.clone(),
+        Transfer {
+            from,
+            to,
+            authority,
+        },
+    );
+    token::transfer(cpi_ctx.with_signer(&[signer_seeds]), amount)
+}
+
+/// Close an empty token account of the legacy token program, moving its rent to `destination`,
+/// signed by the seeds of its PDA `authority`.
+pub fn close_token_account_signed<'a>(
+    token_program: &AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        token_program.clone(),
+        CloseAccount {
+            account,
+            destination,
+            authority,
+        },
+    );
+    token::close_account(cpi_ctx.with_signer(&[signer_seeds]))
+}
+
+/// Transfer tokens of either the legacy token program or Token-2022, which requires
+/// the mint and its decimals, signed by the seeds of the PDA `authority`.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_token_checked_signed<'a>(
+    token_program: &AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        token_program.clone(),
+        TransferChecked {
+            from,
+            mint,
+            to,
+            authority,
+        },
+    );
+    token_interface::transfer_checked(cpi_ctx.with_signer(&[signer_seeds]), amount, decimals)
+}
+
+/// Move `amount` lamports from a system-owned PDA into a wrapped SOL account and sync its
+/// token balance, returning `insufficient_funds` rather than take the PDA below its rent-exempt minimum.
+pub fn wrap_native_signed<'a>(
+    token_program: &AccountInfo<'a>,
+    source: &AccountInfo<'a>,
+    wrapped_sol_account: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+    insufficient_funds: impl Into<Error>,
+) -> Result<()> {
+    transfer_native_signed(
+        source,
+        wrapped_sol_account,
+        amount,
+        signer_seeds,
+        insufficient_funds,
+    )?;
+    token::sync_native(CpiContext::new(
+        token_program.clone(),
+        SyncNative {
+            account: wrapped_sol_account.clone(),
+        },
+    ))
+}
@@ -0,0 +1,105 @@
+use crate::rent::spendable_balance;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+
+/// Transfer lamports out of a system-owned PDA, signed by its seeds,
+/// returning `insufficient_funds` rather than take it below its rent-exempt minimum.
+pub fn transfer_native_signed<'a>(
+    source: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+    insufficient_funds: impl Into<Error>,
+) -> Result<()> {
+    if amount > spendable_balance(source)? {
+        return Err(insufficient_funds.into());
+    }
+    transfer_native_signed_unchecked(source, destination, amount, signer_seeds)
+}
+
+/// Transfer lamports out of a system-owned PDA, signed by its seeds, with no rent check,
+/// e.g. to empty an account that is being abandoned.
+pub fn transfer_native_signed_unchecked<'a>(
+    source: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let ix = system_instruction::transfer(source.key, destination.key, amount);
+    invoke_signed(&ix, &[source.clone(), destination.clone()], &[signer_seeds]).map_err(Into::into)
+}
+
+/// Move lamports by debiting `source` directly, returning `insufficient_funds` if it holds less than `amount`.
+/// The system program cannot debit accounts it does not own, so this is how lamports leave
+/// accounts owned by the calling program, such as a state holding stray yield.
+pub fn move_lamports(
+    source: &AccountInfo,
+    destination: &AccountInfo,
+    amount: u64,
+    insufficient_funds: impl Into<Error>,
+) -> Result<()> {
+    let source_lamports = source
+        .lamports()
+        .checked_sub(amount)
+        .ok_or_else(|| insufficient_funds.into())?;
+    let destination_lamports = destination
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **source.try_borrow_mut_lamports()? = source_lamports;
+    **destination.try_borrow_mut_lamports()? = destination_lamports;
+    Ok(())
+}
+
+/// Move any lamports a program-owned account holds above its rent-exempt minimum to `destination`,
+/// returning the amount moved.
+pub fn sweep_excess_lamports(source: &AccountInfo, destination: &AccountInfo) -> Result<u64> {
+    let excess = spendable_balance(source)?;
+    if excess > 0 {
+        move_lamports(source, destination, excess, ProgramError::InsufficientFunds)?;
+    }
+    Ok(excess)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_lamports_debits_source_and_credits_destination() {
+        let program_id = Pubkey::new_unique();
+        let (source_key, destination_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut source_lamports, mut destination_lamports) = (1_000, 50);
+        let (mut source_data, mut destination_data) = (vec![0u8; 8], vec![]);
+        let source = AccountInfo::new(
+            &source_key,
+            false,
+            true,
+            &mut source_lamports,
+            &mut source_data,
+            &program_id,
+            false,
+            0,
+        );
+        let destination = AccountInfo::new(
+            &destination_key,
+            false,
+            true,
+            &mut destination_lamports,
+            &mut destination_data,
+            &program_id,
+            false,
+            0,
+        );
+
+        move_lamports(&source, &destination, 400, ProgramError::InsufficientFunds).unwrap();
+
+        assert_eq!(source.lamports(), 600);
+        assert_eq!(destination.lamports(), 450);
+
+        let err = move_lamports(&source, &destination, 601, ProgramError::InsufficientFunds);
+        assert_eq!(err, Err(ProgramError::InsufficientFunds.into()));
+        assert_eq!(source.lamports(), 600);
+        assert_eq!(destination.lamports(), 450);
+    }
+}
@@ -2,6 +2,10 @@ import { AnchorProvider, Program } from "@coral-xyz/anchor";
 import * as anchor from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, Connection } from "@solana/web3.js";
 import BN from "bn.js";
+import {
+  getAssociatedTokenAddressSync,
+  NATIVE_MINT,
+} from "@solana/spl-token";
 import { BuyBurnFixed } from "../../types/buy_burn_fixed";
 import IDL from "../../idl/buy_burn_fixed.json";
 
@@ -23,6 +27,31 @@ export const confirm = (connection: Connection) => async (txSig: string) =>
     ...(await connection.getLatestBlockhash()),
   });
 
+export type RetirementBackend = { burn: {} } | { vault: {} };
+
+export interface RetirementConfig {
+  backend: RetirementBackend;
+  // the token account receiving retired tokens when using the vault backend
+  vault: PublicKey;
+  beneficiary: PublicKey;
+}
+
+export const DEFAULT_RETIREMENT_CONFIG: RetirementConfig = {
+  backend: { burn: {} },
+  vault: PublicKey.default,
+  beneficiary: PublicKey.default,
+};
+
+export interface SwapPoolAccounts {
+  authority: PublicKey;
+  // the pool's wrapped SOL vault
+  source: PublicKey;
+  // the pool's vault of the token to be burned
+  destination: PublicKey;
+  poolMint: PublicKey;
+  feeAccount: PublicKey;
+}
+
 export interface BuyBurnFixedConfig {
   updateAuthority: PublicKey;
   treasury: PublicKey;
@@ -58,9 +87,57 @@ export class BuyBurnFixedClient {
     this.yieldAccountAddress = yieldAccountAddress;
   }
 
+  /**
+   * The optional retirement accounts to pass to allocateYield and swapAndBurn,
+   * only set when the state retires to a vault.
+   */
+  public async getRetirementAccounts(state: PublicKey): Promise<{
+    retirementVault: PublicKey | null;
+    retirementReceipt: PublicKey | null;
+  }> {
+    const stateAccount = await this.program.account.state.fetch(state);
+    if (stateAccount.retirementBackend.vault === undefined) {
+      return { retirementVault: null, retirementReceipt: null };
+    }
+
+    return {
+      retirementVault: stateAccount.retirementVault,
+      retirementReceipt: BuyBurnFixedClient.getRetirementReceipt(
+        state,
+        stateAccount.totalRetirements
+      ),
+    };
+  }
+
+  public static getRetirementReceipt(state: PublicKey, index: BN): PublicKey {
+    const [retirementReceipt] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("retirement_receipt"),
+        state.toBuffer(),
+        index.toArrayLike(Buffer, "le", 8),
+      ],
+      PROGRAM_ID
+    );
+
+    return retirementReceipt;
+  }
+
   public static async getYieldAccount(
-    mint: PublicKey
+    mint: PublicKey,
+    index = 0
   ): Promise<anchor.web3.PublicKey> {
+    const [yieldAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("state"), mint.toBuffer(), Buffer.from([index])],
+      PROGRAM_ID
+    );
+
+    return yieldAccount;
+  }
+
+  /**
+   * The address of a state registered before states were indexed.
+   */
+  public static getLegacyYieldAccount(mint: PublicKey): PublicKey {
     const [yieldAccount] = PublicKey.findProgramAddressSync(
       [Buffer.from("state"), mint.toBuffer()],
       PROGRAM_ID
@@ -69,6 +146,18 @@ export class BuyBurnFixedClient {
     return yieldAccount;
   }
 
+  /**
+   * The system-owned PDA that receives the yield allocated by the state.
+   */
+  public static calculateYieldAccount(state: PublicKey): PublicKey {
+    const [yieldAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from("yield_account"), state.toBuffer()],
+      PROGRAM_ID
+    );
+
+    return yieldAccount;
+  }
+
   public static async fetch(yieldAccountAddress: PublicKey): Promise<any> {
     const client = new BuyBurnFixedClient(setUpAnchor());
     return client.program.account.state.fetch(yieldAccountAddress);
@@ -82,10 +171,17 @@ export class BuyBurnFixedClient {
     holdingTokenAccount: PublicKey,
     price: BN,
     purchaseProportion: number,
-    purchaseThreshold: BN
+    purchaseThreshold: BN,
+    priceStalenessThreshold: BN,
+    maxPriceChangeBps: number,
+    swapProgram: PublicKey = PublicKey.default,
+    swapPool: PublicKey = PublicKey.default,
+    maxSlippageBps = 100,
+    retirement: RetirementConfig = DEFAULT_RETIREMENT_CONFIG,
+    index = 0
   ): Promise<BuyBurnFixedClient> {
     // find state address
-    const yieldAccount = await this.getYieldAccount(mint);
+    const yieldAccount = await this.getYieldAccount(mint, index);
 
     const client = new BuyBurnFixedClient(setUpAnchor());
 
@@ -106,6 +202,15 @@ export class BuyBurnFixedClient {
         price,
         purchaseProportion,
         purchaseThreshold,
+        priceStalenessThreshold,
+        maxPriceChangeBps,
+        swapProgram,
+        swapPool,
+        maxSlippageBps,
+        retirementBackend: retirement.backend,
+        retirementVault: retirement.vault,
+        retirementBeneficiary: retirement.beneficiary,
+        index,
       })
       .accounts(accounts)
       .rpc()
@@ -127,7 +232,14 @@ export class BuyBurnFixedClient {
     holdingTokenAccount: PublicKey,
     price: BN,
     purchaseProportion: number,
-    purchaseThreshold: BN
+    purchaseThreshold: BN,
+    priceStalenessThreshold: BN,
+    maxPriceChangeBps: number,
+    swapProgram: PublicKey = PublicKey.default,
+    swapPool: PublicKey = PublicKey.default,
+    maxSlippageBps = 100,
+    retirement: RetirementConfig = DEFAULT_RETIREMENT_CONFIG,
+    index = 0
   ): Promise<BuyBurnFixedClient> {
     const client = new BuyBurnFixedClient(setUpAnchor());
 
@@ -146,6 +258,15 @@ export class BuyBurnFixedClient {
         price,
         purchaseProportion,
         purchaseThreshold,
+        priceStalenessThreshold,
+        maxPriceChangeBps,
+        swapProgram,
+        swapPool,
+        maxSlippageBps,
+        retirementBackend: retirement.backend,
+        retirementVault: retirement.vault,
+        retirementBeneficiary: retirement.beneficiary,
+        index,
       })
       .accounts(accounts)
       .rpc()
@@ -156,6 +277,42 @@ export class BuyBurnFixedClient {
     return client;
   }
 
+  /**
+   * Move a state registered before states were indexed to the state at `index`.
+   * The holding token account delegate must then be re-approved to the new state.
+   */
+  public static async migrateState(
+    mint: PublicKey,
+    index: number,
+    priceStalenessThreshold: BN,
+    maxPriceChangeBps: number,
+    maxSlippageBps = 100
+  ): Promise<BuyBurnFixedClient> {
+    const client = new BuyBurnFixedClient(setUpAnchor());
+    const state = await BuyBurnFixedClient.getYieldAccount(mint, index);
+
+    await client.program.methods
+      .migrateState({
+        index,
+        priceStalenessThreshold,
+        maxPriceChangeBps,
+        maxSlippageBps,
+      })
+      .accounts({
+        payer: client.provider.publicKey,
+        legacyState: BuyBurnFixedClient.getLegacyYieldAccount(mint),
+        state,
+        yieldAccount: BuyBurnFixedClient.calculateYieldAccount(state),
+        mint,
+      })
+      .rpc()
+      .then(confirm(client.provider.connection));
+
+    await client.init(state);
+
+    return client;
+  }
+
   public static async allocateYield(
     payer: PublicKey,
     state: PublicKey,
@@ -163,10 +320,16 @@ export class BuyBurnFixedClient {
     tokenAmount: BN
   ): Promise<BuyBurnFixedClient> {
     const client = new BuyBurnFixedClient(setUpAnchor());
+    const retirementAccounts = await client.getRetirementAccounts(state);
 
     await client.program.methods
       .allocateYield({ solAmount, tokenAmount })
-      .accounts({ state, payer })
+      .accounts({
+        state,
+        payer,
+        yieldAccount: BuyBurnFixedClient.calculateYieldAccount(state),
+        ...retirementAccounts,
+      })
       .rpc()
       .then(confirm(client.provider.connection));
 
@@ -191,4 +354,54 @@ export class BuyBurnFixedClient {
 
     return client;
   }
+
+  /**
+   * Swap `solAmount` (minus the treasury share) through the configured pool and burn the output.
+   * The pool accounts follow the SPL token-swap layout.
+   */
+  public static async swapAndBurn(
+    payer: PublicKey,
+    state: PublicKey,
+    solAmount: BN,
+    minimumAmountOut: BN,
+    pool: SwapPoolAccounts
+  ): Promise<BuyBurnFixedClient> {
+    const client = new BuyBurnFixedClient(setUpAnchor());
+    const stateAccount = await client.program.account.state.fetch(state);
+
+    const wrappedSolAccount = getAssociatedTokenAddressSync(
+      NATIVE_MINT,
+      state,
+      true
+    );
+    const purchasedTokenAccount = getAssociatedTokenAddressSync(
+      stateAccount.mint,
+      state,
+      true
+    );
+
+    await client.program.methods
+      .swapAndBurn({ solAmount, minimumAmountOut })
+      .accounts({
+        payer,
+        state,
+        yieldAccount: BuyBurnFixedClient.calculateYieldAccount(state),
+        mint: stateAccount.mint,
+        treasury: stateAccount.treasury,
+        wrappedSolAccount,
+        purchasedTokenAccount,
+        swapProgram: stateAccount.swapProgram,
+        swapPool: stateAccount.swapPool,
+        swapPoolAuthority: pool.authority,
+        swapPoolSource: pool.source,
+        swapPoolDestination: pool.destination,
+        swapPoolMint: pool.poolMint,
+        swapPoolFeeAccount: pool.feeAccount,
+        ...(await client.getRetirementAccounts(state)),
+      })
+      .rpc()
+      .then(confirm(client.provider.connection));
+
+    return client;
+  }
 }
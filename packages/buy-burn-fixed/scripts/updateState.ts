@@ -7,6 +7,8 @@ import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 const PRICE = 1;
 const PURCHASE_THRESHOLD = 100;
 const PURCHASE_PROPORTION = 0;
+const PRICE_STALENESS_THRESHOLD = 7 * 24 * 60 * 60; // one week
+const MAX_PRICE_CHANGE_BPS = 2_000; // 20%
 
 const defaultTreasuryKey = "ALhQPLkXvbLKsH5Bm9TC3CTabKFSmnXFmzjqpTXYBPpu";
 const treasuryKey = new PublicKey(
@@ -50,7 +52,9 @@ const stateAddress = new PublicKey(
     holdingAccountTokenAddress,
     new anchor.BN(PRICE),
     PURCHASE_PROPORTION,
-    new anchor.BN(PURCHASE_THRESHOLD)
+    new anchor.BN(PURCHASE_THRESHOLD),
+    new anchor.BN(PRICE_STALENESS_THRESHOLD),
+    MAX_PRICE_CHANGE_BPS
   );
 
   console.log("updated state:", client.stateAddress);
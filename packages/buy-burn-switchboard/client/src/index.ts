@@ -3,7 +3,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, Connection } from "@solana/web3.js";
 import BN from "bn.js";
 import { BuyBurnSwitchboard } from "../../../types/buy_burn_switchboard";
-// import IDL from "../../../idl/buy_burn_switchboard.json";
+import IDL from "../../../idl/buy_burn_switchboard.json";
 
 import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { BuyBurnSwitchboardState } from "./types";
@@ -28,6 +28,38 @@ export const confirm = (connection: Connection) => async (txSig: string) =>
     ...(await connection.getLatestBlockhash()),
   });
 
+export type AdjustmentReason =
+  | { correction: {} }
+  | { reconciliation: {} }
+  | { migration: {} }
+  | { other: {} };
+
+/**
+ * Optional state configuration: the swap pool used by swapAndBurn and its
+ * slippage tolerance, the retirement backend and the burn receipt tree.
+ */
+export interface StateExtensions {
+  swapProgram: PublicKey;
+  swapPool: PublicKey;
+  maxSlippageBps: number;
+  retirementBackend: { burn: {} } | { vault: {} };
+  retirementVault: PublicKey;
+  retirementBeneficiary: PublicKey;
+  receiptMerkleTree: PublicKey;
+  receiptVault: PublicKey;
+}
+
+export const DEFAULT_STATE_EXTENSIONS: StateExtensions = {
+  swapProgram: PublicKey.default,
+  swapPool: PublicKey.default,
+  maxSlippageBps: 100,
+  retirementBackend: { burn: {} },
+  retirementVault: PublicKey.default,
+  retirementBeneficiary: PublicKey.default,
+  receiptMerkleTree: PublicKey.default,
+  receiptVault: PublicKey.default,
+};
+
 export interface TreasuryControllerConfig {
   updateAuthority: PublicKey;
   treasury: PublicKey;
@@ -44,7 +76,7 @@ export class YieldControllerClient {
   state: BuyBurnSwitchboardState | undefined;
 
   constructor(readonly provider: AnchorProvider) {
-    this.program = new Program<BuyBurnSwitchboard>(IDL, provider);
+    this.program = new Program<BuyBurnSwitchboard>(IDL as BuyBurnSwitchboard, provider);
   }
 
   private async init(stateAddress: PublicKey): Promise<void> {
@@ -114,7 +146,8 @@ export class YieldControllerClient {
     feedStalenessThreshold: BN,
     purchaseProportion: number,
     purchaseThreshold: BN,
-    index: number
+    index: number,
+    extensions: StateExtensions = DEFAULT_STATE_EXTENSIONS
   ): Promise<YieldControllerClient> {
     // find state address
     const state = this.getStateAddress(mint, index);
@@ -144,6 +177,7 @@ export class YieldControllerClient {
       purchaseThreshold,
       index,
       yieldAccountBump,
+      ...extensions,
     };
 
     console.log({ accounts, stateInput });
@@ -161,6 +195,36 @@ export class YieldControllerClient {
     return client;
   }
 
+  /**
+   * Upgrade a state registered before swaps, vault retirement and burn receipts were added,
+   * which cannot be fetched until it is. The new settings start disabled.
+   * The provider's wallet must be the state's update authority.
+   *
+   * @param mint - The mint of the token the state buys and retires
+   * @param index - The index of the state for the mint
+   * @returns Initialised client
+   */
+  public static async migrateState(
+    mint: PublicKey,
+    index: number
+  ): Promise<YieldControllerClient> {
+    const state = this.getStateAddress(mint, index);
+    const client = new YieldControllerClient(setUpAnchor());
+
+    await client.program.methods
+      .migrateState()
+      .accounts({
+        payer: client.provider.wallet.publicKey,
+        state,
+      })
+      .rpc()
+      .then(confirm(client.provider.connection));
+
+    await client.init(state);
+
+    return client;
+  }
+
   public async updateController(
     updateAuthority: PublicKey,
     treasury: PublicKey,
@@ -172,7 +236,8 @@ export class YieldControllerClient {
     feedStalenessThreshold: BN,
     purchaseProportion: number,
     purchaseThreshold: BN,
-    index: number
+    index: number,
+    extensions: StateExtensions = DEFAULT_STATE_EXTENSIONS
   ): Promise<YieldControllerClient> {
     if (!this.stateAddress || !this.state)
       throw new Error("Client not initialised");
@@ -199,6 +264,7 @@ export class YieldControllerClient {
         purchaseThreshold,
         index,
         yieldAccountBump,
+        ...extensions,
       })
       .accounts(accounts)
       .rpc()
@@ -209,15 +275,30 @@ export class YieldControllerClient {
     return this;
   }
 
-  public async setTotalTokensPurchased(value: BN): Promise<string> {
+  public static getAdjustmentLogAddress(stateAddress: PublicKey): PublicKey {
+    const [adjustmentLog] = PublicKey.findProgramAddressSync(
+      [Buffer.from("adjustment_log"), stateAddress.toBuffer()],
+      PROGRAM_ID
+    );
+    return adjustmentLog;
+  }
+
+  public async adjustTotalTokensPurchased(
+    delta: BN,
+    reason: AdjustmentReason
+  ): Promise<string> {
     if (!this.stateAddress || !this.state)
       throw new Error("Client not initialised");
     const accounts = {
       payer: this.provider.publicKey,
       state: this.stateAddress,
+      adjustmentLog: YieldControllerClient.getAdjustmentLogAddress(
+        this.stateAddress
+      ),
+      systemProgram: SystemProgram.programId,
     };
     const txSig = await this.program.methods
-      .setTotalTokensPurchased(value)
+      .adjustTotalTokensPurchased(delta, reason)
       .accounts(accounts)
       .rpc();
     await confirm(this.provider.connection)(txSig);
@@ -245,6 +326,15 @@ export class YieldControllerClient {
       yieldAccount,
       solUsdPriceFeed: this.state.solUsdPriceFeed,
       nctUsdPriceFeed: this.state.nctUsdPriceFeed,
+      // retirement to a vault and burn receipts are not used by default
+      retirementVault: null,
+      retirementReceipt: null,
+      receiptTreeConfig: null,
+      receiptMerkleTree: null,
+      receiptVault: null,
+      logWrapper: null,
+      compressionProgram: null,
+      bubblegumProgram: null,
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: SystemProgram.programId,
     };
@@ -18,4 +18,14 @@ export interface BuyBurnSwitchboardState {
   index: number;
   bump: number;
   yieldAccountBump: number;
+
+  swapProgram: PublicKey;
+  swapPool: PublicKey;
+  retirementBackend: { burn?: {}; vault?: {} };
+  retirementVault: PublicKey;
+  retirementBeneficiary: PublicKey;
+  totalRetirements: BN;
+  receiptMerkleTree: PublicKey;
+  receiptVault: PublicKey;
+  maxSlippageBps: number;
 }
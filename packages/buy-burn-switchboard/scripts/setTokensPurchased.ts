@@ -18,7 +18,12 @@ const newTokensPurchased = new BN(process.argv[2], 10);
     (await client.getState()).totalTokensPurchased.toString()
   );
   console.log("changing to tokens purchased", newTokensPurchased.toNumber());
-  const txSig = await client.setTotalTokensPurchased(newTokensPurchased);
+  const delta = newTokensPurchased.sub(
+    (await client.getState()).totalTokensPurchased
+  );
+  const txSig = await client.adjustTotalTokensPurchased(delta, {
+    correction: {},
+  });
 
   console.log("updated tokens purchased - txSig", txSig);
 })().catch(console.error);
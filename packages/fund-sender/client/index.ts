@@ -1,10 +1,11 @@
-import { AnchorProvider, Program } from "@coral-xyz/anchor";
+import { AnchorProvider, IdlTypes, Program } from "@coral-xyz/anchor";
 import * as anchor from "@coral-xyz/anchor";
 import {
   PublicKey,
   SystemProgram,
   Connection,
   AccountMeta,
+  AddressLookupTableAccount,
   AddressLookupTableProgram,
   TransactionMessage,
   TransactionInstruction,
@@ -14,6 +15,7 @@ import BN from "bn.js";
 import { FundSender } from "../../types/fund_sender";
 import IDL from "../../idl/fund_sender.json";
 import base58 from "bs58";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 import {
   AssetProof,
   getAsset,
@@ -35,6 +37,9 @@ const BUBBLEGUM_PROGRAM_ID = new PublicKey(
 const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID = new PublicKey(
   "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
 );
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
 
 /**
  * Sets up an anchor provider read from the environment variable.
@@ -84,22 +89,125 @@ const getInputAccountForState = (stateAddress: PublicKey): PublicKey => {
   return inputAccount;
 };
 
+/**
+ * Returns the Metaplex metadata address of a mint.
+ *
+ *
+ * @param mint - Public key of the mint
+ * @returns Public Key of the metadata account
+ *
+ */
+const getMetadataAddress = (mint: PublicKey): PublicKey => {
+  const [metadata] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("metadata"),
+      TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      mint.toBuffer(),
+    ],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+
+  return metadata;
+};
+
+/**
+ * Returns the collection of a Metaplex Core asset, if it has one.
+ * The asset's update authority is set to the collection when it belongs to one.
+ *
+ *
+ * @param assetData - The data of the asset account
+ * @returns Public Key of the collection, or null
+ *
+ */
+const getCoreCollection = (assetData: Buffer): PublicKey | null => {
+  // key (1 byte), owner (32 bytes), update authority variant (1 byte)
+  const UPDATE_AUTHORITY_COLLECTION = 2;
+  if (assetData[33] !== UPDATE_AUTHORITY_COLLECTION) return null;
+  return new PublicKey(assetData.subarray(34, 66));
+};
+
 const decodeBase58 = (base58Input: string): number[] => {
   const buffer = base58.decode(base58Input);
   return Array.from(buffer);
 };
 
-export const mapProof = (assetProof: AssetProof): AccountMeta[] => {
+// the top canopyDepth nodes of the proof are stored in the tree's canopy and can be omitted
+export const mapProof = (
+  assetProof: AssetProof,
+  canopyDepth = 0
+): AccountMeta[] => {
   if (assetProof.proof === undefined || assetProof.proof.length === 0) {
     throw new Error("Proof is empty");
   }
-  return assetProof.proof.map((node) => ({
+  const proof = assetProof.proof.slice(
+    0,
+    Math.max(assetProof.proof.length - canopyDepth, 0)
+  );
+  return proof.map((node) => ({
     pubkey: new PublicKey(node),
     isSigner: false,
     isWritable: false,
   }));
 };
 
+// the maximum number of CNFTs stored in one storeCnftCertificates instruction
+const MAX_CNFT_BATCH_SIZE = 8;
+// the maximum size of a serialized transaction
+const MAX_TRANSACTION_SIZE = 1232;
+
+/**
+ * The metadata of a CNFT, laid out as Bubblegum's MetadataArgs,
+ * which the program checks against the leaf's data hash
+ */
+export type CertificateMetadata = IdlTypes<FundSender>["certificateMetadata"];
+
+/**
+ * A CNFT certificate, with its proof, ready to be stored by storeCnftCertificates
+ */
+export interface CNFTCertificateLeaf {
+  assetId: string;
+  merkleTree: PublicKey;
+  // the leaf's delegate, if it is not the input account
+  leafDelegate: PublicKey | null;
+  certificate: {
+    root: number[];
+    dataHash: number[];
+    creatorHash: number[];
+    nonce: BN;
+    index: number;
+    creators: Array<{ address: PublicKey; verified: boolean; share: number }>;
+    metadata: CertificateMetadata | null;
+    proofLen: number;
+  };
+  proof: AccountMeta[];
+}
+
+/**
+ * Returns whether the instructions fit in a single v0 transaction signed by the payer.
+ *
+ */
+const fitsInTransaction = (
+  txInstructions: TransactionInstruction[],
+  payer: PublicKey,
+  addressLookupTable?: AddressLookupTableAccount
+): boolean => {
+  try {
+    const messageV0 = new TransactionMessage({
+      payerKey: payer,
+      // only used to measure the transaction
+      recentBlockhash: PublicKey.default.toBase58(),
+      instructions: txInstructions,
+    }).compileToV0Message(addressLookupTable ? [addressLookupTable] : []);
+    return (
+      new VersionedTransaction(messageV0).serialize().length <=
+      MAX_TRANSACTION_SIZE
+    );
+  } catch (e) {
+    // compiling or serializing fails if the transaction is too large
+    return false;
+  }
+};
+
 async function createV0Tx(
   connection: Connection,
   txInstructions: TransactionInstruction[],
@@ -124,6 +232,40 @@ export interface FundSenderConfig {
   destinationAccount: PublicKey;
   certificateVault: PublicKey;
   spendThreshold: BN;
+  // the number of records in the state's certificate ledger
+  totalCertificates: BN;
+  // optional weighted destinations, the first being destinationAccount
+  destinationAccounts: PublicKey[];
+  destinationProportions: number[];
+  // an SPL token forwarded to the destination, PublicKey.default if disabled
+  tokenMint: PublicKey;
+  tokenSpendThreshold: BN;
+  // the AMM used to swap SOL to tokenMint, PublicKey.default if disabled
+  swapProgram: PublicKey;
+  swapPool: PublicKey;
+  // certificates are stored only if they match one of these, PublicKey.default or empty to skip
+  certificateCollection: PublicKey;
+  certificateCreator: PublicKey;
+  allowedCertificateMints: PublicKey[];
+  // where certificates not matching the filter go, PublicKey.default to reject them instead
+  quarantineVault: PublicKey;
+  // certificates are stored in the state's program-owned vault, and only leave through the release instructions
+  lockCertificates: boolean;
+  // who certificates are recorded against by default, PublicKey.default for none
+  certificateBeneficiary: PublicKey;
+}
+
+/**
+ * The accounts of an SPL token-swap compatible pool, swapping wrapped SOL to the state's token.
+ */
+export interface SwapPoolAccounts {
+  authority: PublicKey;
+  // the pool's wrapped SOL vault
+  source: PublicKey;
+  // the pool's vault of the state's token
+  destination: PublicKey;
+  poolMint: PublicKey;
+  feeAccount: PublicKey;
 }
 
 type InitialisedClient = FundSenderClient & {
@@ -154,6 +296,19 @@ export class FundSenderClient {
       destinationAccount: state.destinationAccount,
       certificateVault: state.certificateVault,
       spendThreshold: state.spendThreshold,
+      totalCertificates: state.totalCertificates,
+      destinationAccounts: state.destinationAccounts,
+      destinationProportions: [...state.destinationProportions],
+      tokenMint: state.tokenMint,
+      tokenSpendThreshold: state.tokenSpendThreshold,
+      swapProgram: state.swapProgram,
+      swapPool: state.swapPool,
+      certificateCollection: state.certificateCollection,
+      certificateCreator: state.certificateCreator,
+      allowedCertificateMints: state.allowedCertificateMints,
+      quarantineVault: state.quarantineVault,
+      lockCertificates: state.lockCertificates,
+      certificateBeneficiary: state.certificateBeneficiary,
     };
   }
 
@@ -193,6 +348,67 @@ export class FundSenderClient {
     return getInputAccountForState(this.stateAddress);
   }
 
+  /**
+   * Returns the address of the state's program-owned certificate vault,
+   * used when certificates are locked.
+   *
+   *
+   * @param stateAddress - Public key of state
+   * @returns Public Key of the certificate vault
+   *
+   */
+  public static getCertificateVault(stateAddress: PublicKey): PublicKey {
+    const [certificateVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_vault"), stateAddress.toBuffer()],
+      PROGRAM_ID
+    );
+
+    return certificateVault;
+  }
+
+  /**
+   * Returns the address of the certificate record at `index` in the state's certificate ledger.
+   *
+   *
+   * @param stateAddress - Public key of state
+   * @param index - Position of the record in the ledger
+   * @returns Public Key of the certificate record
+   *
+   */
+  public static getCertificateRecord(
+    stateAddress: PublicKey,
+    index: BN
+  ): PublicKey {
+    const [certificateRecord] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("certificate_record"),
+        stateAddress.toBuffer(),
+        index.toArrayLike(Buffer, "le", 8),
+      ],
+      PROGRAM_ID
+    );
+
+    return certificateRecord;
+  }
+
+  /**
+   * Returns the state's certificate ledger, linking stored certificates to the funds sent
+   * by the state when they were stored, ordered by index.
+   *
+   *
+   * @returns Certificate records
+   *
+   */
+  public async getCertificateRecords() {
+    const records = await this.program.account.certificateRecord.all([
+      { memcmp: { offset: 8, bytes: this.stateAddress.toBase58() } },
+    ]);
+
+    return records
+      .map((record) => record.account)
+      .sort((a, b) => a.index.cmp(b.index));
+  }
+
   /**
    * Returns initialised fund sender client.
    *
@@ -229,6 +445,18 @@ export class FundSenderClient {
    * @param destinationAccount - Public key of destination account
    * @param certificateVault - Public key of account holding the NFTs from climate projects
    * @param spendThreshold - Big number
+   * @param destinationAccounts - Optional weighted destinations, the first being destinationAccount
+   * @param destinationProportions - Proportions (summing to 100) of funds sent to each of destinationAccounts
+   * @param tokenMint - Optional SPL token forwarded to the destination
+   * @param tokenSpendThreshold - The minimum amount of tokens that can be sent
+   * @param swapProgram - Optional AMM program used to swap SOL to tokenMint
+   * @param swapPool - The pool of swapProgram used to swap SOL to tokenMint
+   * @param certificateCollection - Optional verified collection certificates must belong to
+   * @param certificateCreator - Optional verified creator certificates must have
+   * @param allowedCertificateMints - Optional certificate mints (or cNFT asset ids) that are always accepted
+   * @param quarantineVault - Optional account receiving certificates that do not match the filter
+   * @param lockCertificates - Store certificates in the state's program-owned vault instead of certificateVault
+   * @param certificateBeneficiary - Optional default beneficiary certificates are recorded against
    * @returns Initialised fund sender client
    */
   public static async register(
@@ -237,7 +465,19 @@ export class FundSenderClient {
     destinationName: string,
     destinationAccount: PublicKey,
     certificateVault: PublicKey,
-    spendThreshold: BN
+    spendThreshold: BN,
+    destinationAccounts: PublicKey[] = [],
+    destinationProportions: number[] = [],
+    tokenMint: PublicKey = PublicKey.default,
+    tokenSpendThreshold: BN = new BN(0),
+    swapProgram: PublicKey = PublicKey.default,
+    swapPool: PublicKey = PublicKey.default,
+    certificateCollection: PublicKey = PublicKey.default,
+    certificateCreator: PublicKey = PublicKey.default,
+    allowedCertificateMints: PublicKey[] = [],
+    quarantineVault: PublicKey = PublicKey.default,
+    lockCertificates = false,
+    certificateBeneficiary: PublicKey = PublicKey.default
   ): Promise<InitialisedClient> {
     // find state address
     const stateAddress = FundSenderClient.getStateAddressFromSunriseAddress(
@@ -262,8 +502,22 @@ export class FundSenderClient {
       destinationName,
       updateAuthority,
       destinationAccount,
-      certificateVault,
+      certificateVault: lockCertificates
+        ? FundSenderClient.getCertificateVault(stateAddress)
+        : certificateVault,
       spendThreshold,
+      destinationAccounts,
+      destinationProportions: Buffer.from(destinationProportions),
+      tokenMint,
+      tokenSpendThreshold,
+      swapProgram,
+      swapPool,
+      certificateCollection,
+      certificateCreator,
+      allowedCertificateMints,
+      quarantineVault,
+      lockCertificates,
+      certificateBeneficiary,
     };
     console.log("Registering state");
     await client.program.methods
@@ -286,6 +540,42 @@ export class FundSenderClient {
     return client as InitialisedClient;
   }
 
+  /**
+   * Upgrade a state registered with the original layout, which cannot be fetched until it is.
+   * Settings added since then are left disabled.
+   * The provider's wallet must be the state's update authority.
+   *
+   * @param sunriseState - Public key
+   * @param destinationName - Seed to specify destination account
+   * @param provider - Optional anchor provider, defaults to anchor provider read from the environment variable
+   * @returns Initialised fund sender client
+   */
+  public static async migrateState(
+    sunriseState: PublicKey,
+    destinationName: string,
+    provider?: AnchorProvider
+  ): Promise<InitialisedClient> {
+    const stateAddress = FundSenderClient.getStateAddressFromSunriseAddress(
+      sunriseState,
+      destinationName
+    );
+    const client = new FundSenderClient(
+      provider ?? setUpAnchor(),
+      stateAddress
+    );
+
+    await client.program.methods
+      .migrateState()
+      .accounts({
+        payer: client.provider.publicKey,
+        state: stateAddress,
+      })
+      .rpc()
+      .then(confirm(client.provider.connection));
+
+    return FundSenderClient.fetch(stateAddress, client.provider);
+  }
+
   /**
    * Updates fund sender accounts.
    *
@@ -313,6 +603,18 @@ export class FundSenderClient {
       destinationAccount,
       certificateVault: this.config.certificateVault,
       spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
     };
     await this.program.methods
       .updateState(args)
@@ -327,6 +629,127 @@ export class FundSenderClient {
     return this;
   }
 
+  /**
+   * Splits funds between weighted destinations.
+   * The first destination becomes the primary destination account.
+   * Pass empty arrays to send everything to the current destination account.
+   *
+   *
+   * @param destinationAccounts - Public keys of the destinations
+   * @param destinationProportions - Proportions (summing to 100) of funds sent to each destination
+   * @returns Fund sender client
+   *
+   */
+  public async updateDestinations(
+    destinationAccounts: PublicKey[],
+    destinationProportions: number[]
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const accounts = {
+      payer: this.provider.wallet.publicKey,
+      state: this.stateAddress,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const args = {
+      destinationName: this.config.destinationName,
+      updateAuthority: this.config.updateAuthority,
+      destinationAccount:
+        destinationAccounts[0] ?? this.config.destinationAccount,
+      certificateVault: this.config.certificateVault,
+      spendThreshold: this.config.spendThreshold,
+      destinationAccounts,
+      destinationProportions: Buffer.from(destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
+    };
+    await this.program.methods
+      .updateState(args)
+      .accounts(accounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * Sets the SPL token forwarded from the input account's ATA to the destination account's ATA.
+   *
+   *
+   * @param tokenMint - Public key of the token mint, PublicKey.default to disable
+   * @param tokenSpendThreshold - The minimum amount of tokens that can be sent
+   * @returns Fund sender client
+   *
+   */
+  public async updateToken(
+    tokenMint: PublicKey,
+    tokenSpendThreshold: BN
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const accounts = {
+      payer: this.provider.wallet.publicKey,
+      state: this.stateAddress,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const args = {
+      destinationName: this.config.destinationName,
+      updateAuthority: this.config.updateAuthority,
+      destinationAccount: this.config.destinationAccount,
+      certificateVault: this.config.certificateVault,
+      spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint,
+      tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
+    };
+    await this.program.methods
+      .updateState(args)
+      .accounts(accounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * The weighted destinations after the primary one, passed as remaining accounts when sending funds.
+   */
+  private getOtherDestinations(): AccountMeta[] {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    return this.config.destinationAccounts.slice(1).map((pubkey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: true,
+    }));
+  }
+
   /**
    * Updates certification vault.
    *
@@ -353,6 +776,18 @@ export class FundSenderClient {
       destinationAccount: this.config.destinationAccount,
       certificateVault,
       spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
     };
     await this.program.methods
       .updateState(args)
@@ -393,6 +828,18 @@ export class FundSenderClient {
       destinationAccount: this.config.destinationAccount,
       certificateVault: this.config.certificateVault,
       spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
     };
     // call the updateState method from the program with the new update authority address
     await this.program.methods
@@ -428,22 +875,33 @@ export class FundSenderClient {
         state: this.stateAddress,
         destinationAccount: this.config.destinationAccount,
       })
+      .remainingAccounts(this.getOtherDestinations())
       .rpc()
       .then(confirm(this.provider.connection));
 
     return this;
   }
 
-  public async sendFromState(): Promise<FundSenderClient> {
+  /**
+   * Sends the whole spendable balance of the input account to the destination account.
+   * Fails if the balance is below the spend threshold.
+   *
+   * @returns Fund sender client
+   *
+   */
+  public async sendAllFunds(): Promise<FundSenderClient> {
     if (!this.config) {
       throw new Error("Client not initialized");
     }
 
     await this.program.methods
-      .sendFromState()
+      .sendAllFunds()
       .accounts({
+        payer: this.provider.publicKey,
         state: this.stateAddress,
+        destinationAccount: this.config.destinationAccount,
       })
+      .remainingAccounts(this.getOtherDestinations())
       .rpc()
       .then(confirm(this.provider.connection));
 
@@ -451,28 +909,24 @@ export class FundSenderClient {
   }
 
   /**
-   * Sends specified amount of NFTs from input account to hold account.
-   *
+   * Sends the whole token balance of the input account's ATA to the destination account's ATA.
+   * Fails if the balance is below the token spend threshold.
    *
    * @returns Fund sender client
    *
    */
-  public async storeCertificates(
-    inputTokenAccount: PublicKey,
-    certificateMint: PublicKey
-  ): Promise<FundSenderClient> {
+  public async sendTokenFund(): Promise<FundSenderClient> {
     if (!this.config) {
       throw new Error("Client not initialized");
     }
 
     await this.program.methods
-      .storeCertificates()
+      .sendTokenFund()
       .accounts({
         payer: this.provider.publicKey,
         state: this.stateAddress,
-        certificateMint,
-        inputTokenAccount,
-        certificateVault: this.config.certificateVault,
+        tokenMint: this.config.tokenMint,
+        destinationAccount: this.config.destinationAccount,
       })
       .rpc()
       .then(confirm(this.provider.connection));
@@ -480,47 +934,631 @@ export class FundSenderClient {
     return this;
   }
 
-  public async getCNFTCertificates() {
-    if (!this.config) throw new Error("Client not initialized");
+  /**
+   * Sets the pool used to swap SOL to the state's token before sending it.
+   *
+   *
+   * @param swapProgram - Public key of an SPL token-swap compatible program, PublicKey.default to disable
+   * @param swapPool - Public key of the pool swapping wrapped SOL to the state's token
+   * @returns Fund sender client
+   *
+   */
+  public async updateSwapPool(
+    swapProgram: PublicKey,
+    swapPool: PublicKey
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const accounts = {
+      payer: this.provider.wallet.publicKey,
+      state: this.stateAddress,
+      systemProgram: SystemProgram.programId,
+    };
 
-    const { items: assets } = await getAssetsByOwner(
-      this.getInputAccount().toBase58()
-    );
+    const args = {
+      destinationName: this.config.destinationName,
+      updateAuthority: this.config.updateAuthority,
+      destinationAccount: this.config.destinationAccount,
+      certificateVault: this.config.certificateVault,
+      spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram,
+      swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
+    };
+    await this.program.methods
+      .updateState(args)
+      .accounts(accounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
 
-    console.log(
-      "Found assets: ",
-      assets.map((asset) => asset.content.metadata.name)
-    );
+    await this.init();
 
-    return assets;
+    return this;
   }
 
-  public async storeCNFTCertificate(
-    assetId: string,
-    addressLookupTable: PublicKey
+  /**
+   * Restricts the certificates this state stores to a verified collection, a verified creator
+   * or a list of allowed mints (or cNFT asset ids). Leave all of them empty to accept any certificate.
+   *
+   *
+   * @param certificateCollection - Public key of the collection, PublicKey.default to skip
+   * @param certificateCreator - Public key of the creator, PublicKey.default to skip
+   * @param allowedCertificateMints - Mints (or cNFT asset ids) that are always accepted
+   * @param quarantineVault - Account receiving non-matching certificates, PublicKey.default to reject them
+   * @returns Fund sender client
+   *
+   */
+  public async updateCertificateFilter(
+    certificateCollection: PublicKey,
+    certificateCreator: PublicKey,
+    allowedCertificateMints: PublicKey[],
+    quarantineVault: PublicKey = PublicKey.default
   ): Promise<FundSenderClient> {
-    if (!this.config) throw new Error("Client not initialized");
-
-    const asset = await getAsset(assetId);
-    const proof = await getAssetProof(assetId);
-    const proofPathAsAccounts = mapProof(proof);
-
-    const root = decodeBase58(proof.root);
-    const dataHash = decodeBase58(asset.compression.data_hash);
-    const creatorHash = decodeBase58(asset.compression.creator_hash);
-    const nonce = new anchor.BN(asset.compression.leaf_id);
-    const index = asset.compression.leaf_id;
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const accounts = {
+      payer: this.provider.wallet.publicKey,
+      state: this.stateAddress,
+      systemProgram: SystemProgram.programId,
+    };
 
-    const ix = await this.program.methods
-      .storeCnftCertificate(root, dataHash, creatorHash, nonce, index)
-      .accounts({
-        payer: this.provider.publicKey,
+    const args = {
+      destinationName: this.config.destinationName,
+      updateAuthority: this.config.updateAuthority,
+      destinationAccount: this.config.destinationAccount,
+      certificateVault: this.config.certificateVault,
+      spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection,
+      certificateCreator,
+      allowedCertificateMints,
+      quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary: this.config.certificateBeneficiary,
+    };
+    await this.program.methods
+      .updateState(args)
+      .accounts(accounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * Sets who stored certificates are recorded against by default.
+   *
+   *
+   * @param certificateBeneficiary - Public key of the beneficiary, PublicKey.default for none
+   * @returns Fund sender client
+   *
+   */
+  public async updateCertificateBeneficiary(
+    certificateBeneficiary: PublicKey
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const accounts = {
+      payer: this.provider.wallet.publicKey,
+      state: this.stateAddress,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const args = {
+      destinationName: this.config.destinationName,
+      updateAuthority: this.config.updateAuthority,
+      destinationAccount: this.config.destinationAccount,
+      certificateVault: this.config.certificateVault,
+      spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: this.config.lockCertificates,
+      certificateBeneficiary,
+    };
+    await this.program.methods
+      .updateState(args)
+      .accounts(accounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * Stores all future certificates in the state's program-owned certificate vault,
+   * from which they can only leave through the release instructions. This cannot be undone.
+   *
+   *
+   * @returns Fund sender client
+   *
+   */
+  public async lockCertificates(): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const accounts = {
+      payer: this.provider.wallet.publicKey,
+      state: this.stateAddress,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const args = {
+      destinationName: this.config.destinationName,
+      updateAuthority: this.config.updateAuthority,
+      destinationAccount: this.config.destinationAccount,
+      certificateVault: FundSenderClient.getCertificateVault(this.stateAddress),
+      spendThreshold: this.config.spendThreshold,
+      destinationAccounts: this.config.destinationAccounts,
+      destinationProportions: Buffer.from(this.config.destinationProportions),
+      tokenMint: this.config.tokenMint,
+      tokenSpendThreshold: this.config.tokenSpendThreshold,
+      swapProgram: this.config.swapProgram,
+      swapPool: this.config.swapPool,
+      certificateCollection: this.config.certificateCollection,
+      certificateCreator: this.config.certificateCreator,
+      allowedCertificateMints: this.config.allowedCertificateMints,
+      quarantineVault: this.config.quarantineVault,
+      lockCertificates: true,
+      certificateBeneficiary: this.config.certificateBeneficiary,
+    };
+    await this.program.methods
+      .updateState(args)
+      .accounts(accounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * Releases certificate tokens from the state's program-owned certificate vault to a beneficiary.
+   *
+   *
+   * @param certificateMint - Mint of the certificates
+   * @param beneficiary - Public key receiving the certificates
+   * @param amount - The amount of certificate tokens to release
+   * @param certificateRecord - Optional ledger record of the certificate, attributed to the beneficiary
+   * @returns Fund sender client
+   *
+   */
+  public async releaseCertificate(
+    certificateMint: PublicKey,
+    beneficiary: PublicKey,
+    amount: BN,
+    certificateRecord: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    const mintAccount =
+      await this.provider.connection.getAccountInfo(certificateMint);
+    if (!mintAccount) throw new Error("Certificate mint not found");
+
+    await this.program.methods
+      .releaseCertificate(amount)
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        certificateMint,
+        beneficiary,
+        certificateRecord,
+        tokenProgram: mintAccount.owner,
+      })
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    return this;
+  }
+
+  /**
+   * Releases a Metaplex Core certificate from the state's program-owned certificate vault to a beneficiary.
+   *
+   *
+   * @param certificateAsset - The Core asset, owned by the certificate vault
+   * @param beneficiary - Public key receiving the certificate
+   * @param certificateRecord - Optional ledger record of the certificate, attributed to the beneficiary
+   * @returns Fund sender client
+   *
+   */
+  public async releaseCoreCertificate(
+    certificateAsset: PublicKey,
+    beneficiary: PublicKey,
+    certificateRecord: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    const assetAccount =
+      await this.provider.connection.getAccountInfo(certificateAsset);
+    if (!assetAccount) throw new Error("Certificate asset not found");
+
+    await this.program.methods
+      .releaseCoreCertificate()
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        certificateAsset,
+        certificateCollection: getCoreCollection(assetAccount.data),
+        beneficiary,
+        certificateRecord,
+      })
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    return this;
+  }
+
+  /**
+   * Releases a CNFT certificate from the state's program-owned certificate vault to a beneficiary.
+   *
+   *
+   * @param assetId - The asset id of the CNFT
+   * @param beneficiary - Public key receiving the certificate
+   * @param addressLookupTable - The lookup table created by createALTForCNFTTransfer
+   * @param certificateRecord - Optional ledger record of the certificate, attributed to the beneficiary
+   * @returns Fund sender client
+   *
+   */
+  public async releaseCNFTCertificate(
+    assetId: string,
+    beneficiary: PublicKey,
+    addressLookupTable: PublicKey,
+    certificateRecord: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    const { certificate, merkleTree, proof } =
+      await this.getCNFTCertificateLeaf(assetId);
+
+    const ix = await this.program.methods
+      .releaseCnftCertificate(
+        certificate.root,
+        certificate.dataHash,
+        certificate.creatorHash,
+        certificate.nonce,
+        certificate.index
+      )
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        beneficiary,
+        certificateRecord,
+        merkleTree,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+      })
+      .remainingAccounts(proof)
+      .instruction();
+
+    const tx = await createV0Tx(
+      this.provider.connection,
+      [ix],
+      this.provider.publicKey,
+      addressLookupTable
+    ).then(async (tx) => this.provider.wallet.signTransaction(tx));
+
+    const txHash = await this.provider.sendAndConfirm(tx);
+    console.log("Released CNFT certificate", txHash);
+
+    return this;
+  }
+
+  /**
+   * Wraps `amount` lamports from the input account, swaps them to the state's token
+   * through the configured pool and delivers the output to the destination account's ATA.
+   * The provider's wallet must be the state's update authority.
+   *
+   *
+   * @param amount - Lamports to swap
+   * @param minimumAmountOut - The swap fails if fewer tokens are received
+   * @param pool - The accounts of the configured pool
+   * @returns Fund sender client
+   *
+   */
+  public async swapAndSend(
+    amount: BN,
+    minimumAmountOut: BN,
+    pool: SwapPoolAccounts
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+
+    await this.program.methods
+      .swapAndSend(amount, minimumAmountOut)
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        tokenMint: this.config.tokenMint,
+        destinationAccount: this.config.destinationAccount,
+        swapProgram: this.config.swapProgram,
+        swapPool: this.config.swapPool,
+        swapPoolAuthority: pool.authority,
+        swapPoolSource: pool.source,
+        swapPoolDestination: pool.destination,
+        swapPoolMint: pool.poolMint,
+        swapPoolFeeAccount: pool.feeAccount,
+      })
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    return this;
+  }
+
+  /**
+   * Renames the destination, moving the state to the address derived from the new name.
+   * SOL in the input account moves to the new input account, as do the balances of
+   * the given token accounts, which are then closed. The old state is closed.
+   * The old input account cannot sign once its state is closed, so every token account it holds,
+   * including that of the state's token if it has a balance, must be passed.
+   * States which have stored certificates cannot be migrated.
+   *
+   *
+   * @param newDestinationName - The new destination name
+   * @param tokenAccounts - Token accounts owned by the current input account to move,
+   * the matching associated token accounts of the new input account must exist
+   * @returns Initialised fund sender client for the new state
+   *
+   */
+  public async migrateDestination(
+    newDestinationName: string,
+    tokenAccounts: { address: PublicKey; mint: PublicKey }[] = []
+  ): Promise<InitialisedClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+    const { sunriseState, tokenMint } = await this.program.account.state.fetch(
+      this.stateAddress
+    );
+    const newStateAddress = FundSenderClient.getStateAddressFromSunriseAddress(
+      sunriseState,
+      newDestinationName
+    );
+    const newInputAccount = getInputAccountForState(newStateAddress);
+
+    const remainingAccounts = tokenAccounts.flatMap(({ address, mint }) => [
+      { pubkey: address, isSigner: false, isWritable: true },
+      {
+        pubkey: getAssociatedTokenAddressSync(mint, newInputAccount, true),
+        isSigner: false,
+        isWritable: true,
+      },
+    ]);
+
+    await this.program.methods
+      .migrateDestination(newDestinationName)
+      .accounts({
+        payer: this.provider.publicKey,
+        oldState: this.stateAddress,
+        oldInputAccount: this.getInputAccount(),
+        oldTokenMintAccount: getAssociatedTokenAddressSync(
+          tokenMint,
+          this.getInputAccount(),
+          true
+        ),
+        newState: newStateAddress,
+        newInputAccount,
+      })
+      .remainingAccounts(remainingAccounts)
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    return FundSenderClient.fetch(newStateAddress, this.provider);
+  }
+
+  public async sendFromState(): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+
+    await this.program.methods
+      .sendFromState()
+      .accounts({
+        state: this.stateAddress,
+      })
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    return this;
+  }
+
+  /**
+   * Sends specified amount of NFTs from input account to hold account.
+   * Certificates not matching the state's filter must be sent to its quarantine vault instead.
+   * The state's token and wrapped SOL are funds, and cannot be stored as certificates.
+   *
+   *
+   * @param inputTokenAccount - Token account of the input account holding the certificates
+   * @param certificateMint - Mint of the certificates
+   * @param quarantine - Send the certificates to the quarantine vault
+   * @param beneficiary - Optional beneficiary to record the certificates against, instead of the state's.
+   * Naming one requires the provider's wallet to be the update authority
+   * @returns Fund sender client
+   *
+   */
+  public async storeCertificates(
+    inputTokenAccount: PublicKey,
+    certificateMint: PublicKey,
+    quarantine = false,
+    beneficiary: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+
+    // the metadata is passed if it exists, so that its collection and creators can be checked
+    const metadataAddress = getMetadataAddress(certificateMint);
+    const metadataAccount =
+      await this.provider.connection.getAccountInfo(metadataAddress);
+    // certificates can be legacy SPL token or Token-2022 mints
+    const mintAccount =
+      await this.provider.connection.getAccountInfo(certificateMint);
+    if (!mintAccount) throw new Error("Certificate mint not found");
+
+    await this.program.methods
+      .storeCertificates()
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        certificateMint,
+        inputTokenAccount,
+        certificateVault: quarantine
+          ? this.config.quarantineVault
+          : this.config.certificateVault,
+        beneficiary,
+        certificateMetadata: metadataAccount ? metadataAddress : null,
+        certificateRecord: FundSenderClient.getCertificateRecord(
+          this.stateAddress,
+          this.config.totalCertificates
+        ),
+        tokenProgram: mintAccount.owner,
+      })
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    // the next certificate record is at the updated total_certificates
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * Sends a Metaplex Core asset from input account to hold account.
+   *
+   *
+   * @param certificateAsset - The Core asset, owned by the input account
+   * @param quarantine - Send the certificate to the quarantine vault
+   * @param beneficiary - Optional beneficiary to record the certificate against, instead of the state's.
+   * Naming one requires the provider's wallet to be the update authority
+   * @returns Fund sender client
+   *
+   */
+  public async storeCoreCertificate(
+    certificateAsset: PublicKey,
+    quarantine = false,
+    beneficiary: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    if (!this.config) {
+      throw new Error("Client not initialized");
+    }
+
+    const assetAccount =
+      await this.provider.connection.getAccountInfo(certificateAsset);
+    if (!assetAccount) throw new Error("Certificate asset not found");
+
+    await this.program.methods
+      .storeCoreCertificate()
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        certificateAsset,
+        certificateCollection: getCoreCollection(assetAccount.data),
+        beneficiary,
+        certificateVault: quarantine
+          ? this.config.quarantineVault
+          : this.config.certificateVault,
+        certificateRecord: FundSenderClient.getCertificateRecord(
+          this.stateAddress,
+          this.config.totalCertificates
+        ),
+      })
+      .rpc()
+      .then(confirm(this.provider.connection));
+
+    await this.init();
+
+    return this;
+  }
+
+  public async getCNFTCertificates() {
+    if (!this.config) throw new Error("Client not initialized");
+
+    const { items: assets } = await getAssetsByOwner(
+      this.getInputAccount().toBase58()
+    );
+
+    console.log(
+      "Found assets: ",
+      assets.map((asset) => asset.content.metadata.name)
+    );
+
+    return assets;
+  }
+
+  /**
+   * Sends a CNFT from input account to hold account.
+   *
+   *
+   * @param assetId - The asset id of the CNFT
+   * @param addressLookupTable - The lookup table created by createALTForCNFTTransfer
+   * @param quarantine - Send the certificate to the quarantine vault
+   * @param metadata - Optional metadata of the CNFT, to check its collection against the data hash
+   * @param beneficiary - Optional beneficiary to record the certificate against, instead of the state's.
+   * Naming one requires the provider's wallet to be the update authority
+   * @returns Fund sender client
+   *
+   */
+  public async storeCNFTCertificate(
+    assetId: string,
+    addressLookupTable: PublicKey,
+    quarantine = false,
+    metadata: CertificateMetadata | null = null,
+    beneficiary: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    if (!this.config) throw new Error("Client not initialized");
+
+    const { certificate, merkleTree, leafDelegate, proof } =
+      await this.getCNFTCertificateLeaf(assetId, 0, metadata);
+
+    const ix = await this.program.methods
+      .storeCnftCertificate(
+        certificate.root,
+        certificate.dataHash,
+        certificate.creatorHash,
+        certificate.nonce,
+        certificate.index,
+        // checked against the creator hash when the state filters certificates
+        certificate.creators,
+        certificate.metadata
+      )
+      .accounts({
+        payer: this.provider.publicKey,
         state: this.stateAddress,
-        certificateVault: this.config.certificateVault,
-        merkleTree: new PublicKey(proof.tree_id),
+        certificateVault: quarantine
+          ? this.config.quarantineVault
+          : this.config.certificateVault,
+        certificateRecord: FundSenderClient.getCertificateRecord(
+          this.stateAddress,
+          this.config.totalCertificates
+        ),
+        leafDelegate,
+        beneficiary,
+        merkleTree,
         logWrapper: SPL_NOOP_PROGRAM_ID,
       })
-      .remainingAccounts(proofPathAsAccounts)
+      .remainingAccounts(proof)
       .instruction();
 
     const tx = await createV0Tx(
@@ -532,6 +1570,232 @@ export class FundSenderClient {
 
     const txHash = await this.provider.sendAndConfirm(tx);
     console.log("Stored CNFT certificate", txHash);
+
+    await this.init();
+
+    return this;
+  }
+
+  /**
+   * Fetches a CNFT certificate and its proof, omitting the nodes stored in the tree's canopy.
+   *
+   *
+   * @param assetId - The asset id of the CNFT
+   * @param canopyDepth - The canopy depth of the CNFT's tree
+   * @param metadata - Optional metadata of the CNFT, to check its collection against the data hash
+   * @returns The certificate leaf
+   *
+   */
+  public async getCNFTCertificateLeaf(
+    assetId: string,
+    canopyDepth = 0,
+    metadata: CertificateMetadata | null = null
+  ): Promise<CNFTCertificateLeaf> {
+    const asset = await getAsset(assetId);
+    const proof = await getAssetProof(assetId);
+    const proofPathAsAccounts = mapProof(proof, canopyDepth);
+
+    return {
+      assetId,
+      merkleTree: new PublicKey(proof.tree_id),
+      leafDelegate:
+        asset.ownership.delegated && asset.ownership.delegate
+          ? new PublicKey(asset.ownership.delegate)
+          : null,
+      certificate: {
+        root: decodeBase58(proof.root),
+        dataHash: decodeBase58(asset.compression.data_hash),
+        creatorHash: decodeBase58(asset.compression.creator_hash),
+        nonce: new anchor.BN(asset.compression.leaf_id),
+        index: asset.compression.leaf_id,
+        creators: asset.creators.map((creator) => ({
+          address: new PublicKey(creator.address),
+          verified: creator.verified,
+          share: creator.share,
+        })),
+        metadata,
+        proofLen: proofPathAsAccounts.length,
+      },
+      proof: proofPathAsAccounts,
+    };
+  }
+
+  /**
+   * Builds a storeCnftCertificates instruction for CNFTs from the same tree.
+   *
+   *
+   * @param leaves - The certificates to store
+   * @param firstRecordIndex - The ledger index of the first certificate's record
+   * @param quarantine - Send the certificates to the quarantine vault
+   * @param beneficiary - Optional beneficiary to record the certificates against, instead of the state's.
+   * Naming one requires the provider's wallet to be the update authority
+   * @returns The instruction
+   *
+   */
+  public async storeCNFTCertificatesInstruction(
+    leaves: CNFTCertificateLeaf[],
+    firstRecordIndex: BN,
+    quarantine = false,
+    beneficiary: PublicKey | null = null
+  ): Promise<TransactionInstruction> {
+    if (!this.config) throw new Error("Client not initialized");
+
+    const certificateRecords = leaves.map((_, i) => ({
+      pubkey: FundSenderClient.getCertificateRecord(
+        this.stateAddress,
+        firstRecordIndex.addn(i)
+      ),
+      isSigner: false,
+      isWritable: true,
+    }));
+
+    return this.program.methods
+      .storeCnftCertificates(leaves.map((leaf) => leaf.certificate))
+      .accounts({
+        payer: this.provider.publicKey,
+        state: this.stateAddress,
+        certificateVault: quarantine
+          ? this.config.quarantineVault
+          : this.config.certificateVault,
+        leafDelegate: leaves[0].leafDelegate,
+        beneficiary,
+        merkleTree: leaves[0].merkleTree,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        ...certificateRecords,
+        ...leaves.flatMap((leaf) => leaf.proof),
+      ])
+      .instruction();
+  }
+
+  /**
+   * Packs CNFT certificates into as few storeCnftCertificates instructions as possible,
+   * each fitting in a single transaction using the address lookup table.
+   * Consecutive certificates from the same tree, with the same leaf delegate, are batched together.
+   *
+   *
+   * @param leaves - The certificates to store
+   * @param addressLookupTable - The lookup table created by createALTForCNFTTransfer
+   * @param quarantine - Send the certificates to the quarantine vault
+   * @param beneficiary - Optional beneficiary to record the certificates against, instead of the state's.
+   * Naming one requires the provider's wallet to be the update authority
+   * @returns One instruction per transaction
+   *
+   */
+  public async packCNFTCertificates(
+    leaves: CNFTCertificateLeaf[],
+    addressLookupTable: PublicKey,
+    quarantine = false,
+    beneficiary: PublicKey | null = null
+  ): Promise<TransactionInstruction[]> {
+    if (!this.config) throw new Error("Client not initialized");
+
+    const lookupTableAccount = (
+      await this.provider.connection.getAddressLookupTable(addressLookupTable)
+    ).value ?? undefined;
+
+    // the instruction storing the batch, if it fits in a transaction
+    const buildIfFits = async (
+      batch: CNFTCertificateLeaf[],
+      firstRecordIndex: BN
+    ): Promise<TransactionInstruction | undefined> => {
+      // a batch shares its tree and leaf delegate
+      const sameTreeAndDelegate = batch.every(
+        (leaf) =>
+          leaf.merkleTree.equals(batch[0].merkleTree) &&
+          String(leaf.leafDelegate) === String(batch[0].leafDelegate)
+      );
+      if (!sameTreeAndDelegate || batch.length > MAX_CNFT_BATCH_SIZE) return undefined;
+      const ix = await this.storeCNFTCertificatesInstruction(
+        batch,
+        firstRecordIndex,
+        quarantine,
+        beneficiary
+      );
+      return fitsInTransaction([ix], this.provider.publicKey, lookupTableAccount)
+        ? ix
+        : undefined;
+    };
+
+    const instructions: TransactionInstruction[] = [];
+    let nextRecordIndex = this.config.totalCertificates;
+    let batch: CNFTCertificateLeaf[] = [];
+    let batchInstruction: TransactionInstruction | undefined;
+
+    for (const leaf of leaves) {
+      const candidateInstruction = await buildIfFits(
+        [...batch, leaf],
+        nextRecordIndex
+      );
+      if (candidateInstruction) {
+        batch.push(leaf);
+        batchInstruction = candidateInstruction;
+        continue;
+      }
+
+      // the batch is full, start a new one with this certificate
+      if (batchInstruction) {
+        instructions.push(batchInstruction);
+        nextRecordIndex = nextRecordIndex.addn(batch.length);
+      }
+      batch = [leaf];
+      batchInstruction = await buildIfFits(batch, nextRecordIndex);
+      if (!batchInstruction) {
+        throw new Error(`CNFT ${leaf.assetId} does not fit in a transaction`);
+      }
+    }
+    if (batchInstruction) instructions.push(batchInstruction);
+
+    return instructions;
+  }
+
+  /**
+   * Stores several CNFT certificates, packing as many as fit in each transaction.
+   *
+   *
+   * @param assetIds - The asset ids of the CNFTs
+   * @param addressLookupTable - The lookup table created by createALTForCNFTTransfer
+   * @param canopyDepth - The canopy depth of the CNFTs' trees
+   * @param quarantine - Send the certificates to the quarantine vault
+   * @param beneficiary - Optional beneficiary to record the certificates against, instead of the state's.
+   * Naming one requires the provider's wallet to be the update authority
+   * @returns Fund sender client
+   *
+   */
+  public async storeCNFTCertificates(
+    assetIds: string[],
+    addressLookupTable: PublicKey,
+    canopyDepth = 0,
+    quarantine = false,
+    beneficiary: PublicKey | null = null
+  ): Promise<FundSenderClient> {
+    const leaves = await Promise.all(
+      assetIds.map(async (assetId) =>
+        this.getCNFTCertificateLeaf(assetId, canopyDepth)
+      )
+    );
+    const instructions = await this.packCNFTCertificates(
+      leaves,
+      addressLookupTable,
+      quarantine,
+      beneficiary
+    );
+
+    for (const ix of instructions) {
+      const tx = await createV0Tx(
+        this.provider.connection,
+        [ix],
+        this.provider.publicKey,
+        addressLookupTable
+      ).then(async (tx) => this.provider.wallet.signTransaction(tx));
+
+      const txHash = await this.provider.sendAndConfirm(tx);
+      console.log("Stored CNFT certificates", txHash);
+    }
+
+    await this.init();
+
     return this;
   }
 
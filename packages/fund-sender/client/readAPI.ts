@@ -49,6 +49,16 @@ export interface Asset {
     address: string;
     scopes: string[];
   }>;
+  creators: Array<{
+    address: string;
+    share: number;
+    verified: boolean;
+  }>;
+  ownership: {
+    owner: string;
+    delegate: string | null;
+    delegated: boolean;
+  };
 }
 
 export interface AssetResponse {
@@ -0,0 +1,28 @@
+import { FundSenderClient } from "../client";
+import { PublicKey } from "@solana/web3.js";
+
+// mainnet Sunrise
+const defaultSunriseStateAddress =
+  "43m66crxGfXSJpmx5wXRoFuHubhHA1GCvtHgmHW6cM1P";
+const sunriseStateAddress = new PublicKey(
+  process.env.STATE_ADDRESS ?? defaultSunriseStateAddress
+);
+
+// USAGE: yarn ts-node packages/fund-sender/migrateDestination.ts destinationName newDestinationName
+const destinationName = process.argv[2];
+const newDestinationName = process.argv[3];
+
+(async () => {
+  const stateAddress = FundSenderClient.getStateAddressFromSunriseAddress(
+    sunriseStateAddress,
+    destinationName
+  );
+  const client = await FundSenderClient.fetch(stateAddress);
+
+  console.log("Migrating", destinationName, "to", newDestinationName);
+  const migrated = await client.migrateDestination(newDestinationName);
+
+  console.log("new state address", migrated.stateAddress.toBase58());
+  console.log("new input address", migrated.getInputAccount().toBase58());
+  console.log("state account data after migrating", migrated.config);
+})().catch(console.error);
@@ -10,9 +10,10 @@ const sunriseStateAddress = new PublicKey(
   process.env.STATE_ADDRESS ?? defaultSunriseStateAddress
 );
 
-// USAGE: yarn ts-node packages/fund-sender/sendFund.ts destinationName amount
+// USAGE: yarn ts-node packages/fund-sender/sendFund.ts destinationName [amount]
+// If no amount is given, the whole spendable balance of the input account is sent
 const destinationName = process.argv[2];
-const amount = parseInt(process.argv[3] ?? "0", 10);
+const amount = process.argv[3] ? parseInt(process.argv[3], 10) : undefined;
 
 (async () => {
   const stateAddress = FundSenderClient.getStateAddressFromSunriseAddress(
@@ -29,7 +30,11 @@ const amount = parseInt(process.argv[3] ?? "0", 10);
   await log("input", client.getInputAccount());
 
   console.log("Sending fund...");
-  await client.sendFunds(new BN(amount));
+  if (amount === undefined) {
+    await client.sendAllFunds();
+  } else {
+    await client.sendFunds(new BN(amount));
+  }
 
   await log("output", client.getInputAccount());
 })().catch(console.error);
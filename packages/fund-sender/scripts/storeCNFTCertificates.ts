@@ -16,6 +16,9 @@ let cnftAddressLookupTable = cnftAddressLookupTableAddress
   ? new PublicKey(cnftAddressLookupTableAddress)
   : undefined;
 
+// the canopy depth of the certificates' tree, shortening the proofs
+const canopyDepth = parseInt(process.env.CANOPY_DEPTH ?? "0", 10);
+
 // USAGE: yarn ts-node packages/fund-sender/storeCertificate.ts destinationName
 const destinationName = process.argv[2];
 
@@ -45,7 +48,9 @@ const destinationName = process.argv[2];
   }
 
   console.log("Storing certificates...");
-  for (const asset of assets) {
-    await client.storeCNFTCertificate(asset.id, cnftAddressLookupTable);
-  }
+  await client.storeCNFTCertificates(
+    assets.map((asset) => asset.id),
+    cnftAddressLookupTable,
+    canopyDepth
+  );
 })().catch(console.error);
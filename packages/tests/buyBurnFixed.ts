@@ -12,10 +12,14 @@ import {
   approveChecked,
   getAccount,
 } from "@solana/spl-token";
-import { expect } from "chai";
+import * as chai from "chai";
+import chaiAsPromised from "chai-as-promised";
 import testAuthority from "./fixtures/id.json";
 const program = anchor.workspace.BuyBurnFixed as Program<BuyBurnFixed>;
 
+chai.use(chaiAsPromised);
+const { expect } = chai;
+
 describe("buy-burn-fixed", () => {
   let client: BuyBurnFixedClient;
   const authority = Keypair.fromSecretKey(Uint8Array.from(testAuthority));
@@ -50,7 +54,7 @@ describe("buy-burn-fixed", () => {
     );
 
     [stateAddress, bump] = await anchor.web3.PublicKey.findProgramAddress(
-      [Buffer.from("state"), mint.toBuffer()],
+      [Buffer.from("state"), mint.toBuffer(), Buffer.from([0])],
       PROGRAM_ID
     );
   });
@@ -64,7 +68,9 @@ describe("buy-burn-fixed", () => {
       holdingTokenAccount.address,
       new BN(1),
       0.5,
-      new BN(1)
+      new BN(1),
+      new BN(3600),
+      0
     );
 
     expect(client.yieldAccountAddress).not.to.be.null;
@@ -82,18 +88,48 @@ describe("buy-burn-fixed", () => {
     expect(state.purchaseProportion).equal(0.5);
     expect(state.bump).equal(bump);
   });
+
+  it("can register a second state for the same mint", async () => {
+    const secondClient = await BuyBurnFixedClient.register(
+      authority.publicKey,
+      treasury.publicKey,
+      mint,
+      holdingAccount.publicKey,
+      holdingTokenAccount.address,
+      new BN(2),
+      0.25,
+      new BN(1),
+      new BN(3600),
+      0,
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+      1
+    );
+
+    const secondStateAddress = secondClient.yieldAccountAddress as PublicKey;
+    expect(secondStateAddress.toBase58()).not.equal(stateAddress.toBase58());
+
+    const state = await program.account.state.fetch(secondStateAddress);
+    expect(state.index).equal(1);
+    expect(state.price.toNumber()).equal(2);
+  });
   it("Can allocate yield", async () => {
-    // state account is PDA target for sunrise
+    // yield account is PDA target for sunrise
+    const yieldAccount = BuyBurnFixedClient.calculateYieldAccount(stateAddress);
     await program.provider.connection
-      .requestAirdrop(stateAddress, 100 * LAMPORTS_PER_SOL)
+      .requestAirdrop(yieldAccount, 100 * LAMPORTS_PER_SOL)
       .then(async (sig) => program.provider.connection.confirmTransaction(sig));
 
     await program.provider.connection
       .requestAirdrop(treasury.publicKey, 100 * LAMPORTS_PER_SOL)
       .then(async (sig) => program.provider.connection.confirmTransaction(sig));
 
-    // check that yieldAccountAddress now has 10 SOL
     const yieldBalanceBefore = await program.provider.connection.getBalance(
+      yieldAccount
+    );
+    const stateBalanceBefore = await program.provider.connection.getBalance(
       stateAddress
     );
     // treasury token account is created and delegate is set to the state account
@@ -157,7 +193,10 @@ describe("buy-burn-fixed", () => {
       new BN(10 * LAMPORTS_PER_SOL)
     );
 
-    const treasuryBalanceAfter = await program.provider.connection.getBalance(
+    const yieldBalanceAfter = await program.provider.connection.getBalance(
+      yieldAccount
+    );
+    const stateBalanceAfter = await program.provider.connection.getBalance(
       stateAddress
     );
 
@@ -169,7 +208,9 @@ describe("buy-burn-fixed", () => {
     const holdingAccountBalanceAfter =
       await program.provider.connection.getBalance(holdingAccount.publicKey);
 
-    expect(treasuryBalanceAfter).equal(yieldBalanceBefore - 10 * 10 ** 9);
+    expect(yieldBalanceAfter).equal(yieldBalanceBefore - 10 * 10 ** 9);
+    // the state keeps its rent, the yield is spent from the yield account only
+    expect(stateBalanceAfter).equal(stateBalanceBefore);
     expect(holdingAccountBalanceAfter).equal(
       holdingAccountBalanceBefore + 5 * 10 ** 9
     );
@@ -191,6 +232,155 @@ describe("buy-burn-fixed", () => {
 
     const yieldAccount = await program.account.state.fetch(stateAddress);
     expect(yieldAccount.price.toNumber()).equal(price.toNumber());
+
+    // the registration price and this update are both in the price history
+    const historyIndex = yieldAccount.priceHistoryIndex;
+    expect(historyIndex).equal(2);
+    expect(yieldAccount.priceHistory[0].price.toNumber()).equal(1);
+    expect(yieldAccount.priceHistory[1].price.toNumber()).equal(
+      price.toNumber()
+    );
+    expect(yieldAccount.lastPriceUpdateTimestamp.toNumber()).equal(
+      yieldAccount.priceHistory[1].timestamp.toNumber()
+    );
+  });
+  it("Rejects a price update beyond the change limit", async () => {
+    const updateLimit = async (maxPriceChangeBps: number) =>
+      BuyBurnFixedClient.updateController(
+        stateAddress,
+        authority.publicKey,
+        treasury.publicKey,
+        mint,
+        holdingAccount.publicKey,
+        holdingTokenAccount.address,
+        new BN(1_000),
+        0.5,
+        new BN(1),
+        new BN(3600),
+        maxPriceChangeBps
+      );
+
+    // allow at most a 10% change within an hour
+    await updateLimit(1_000);
+
+    const shouldFail = BuyBurnFixedClient.updatePrice(
+      stateAddress,
+      authority.publicKey,
+      new BN(2_000)
+    );
+    await expect(shouldFail).to.be.rejectedWith("PriceChangeTooLarge.");
+
+    // the registration price of 1 is still within the hour, so even a small step is refused
+    const shouldAlsoFail = BuyBurnFixedClient.updatePrice(
+      stateAddress,
+      authority.publicKey,
+      new BN(1_100)
+    );
+    await expect(shouldAlsoFail).to.be.rejectedWith("PriceChangeTooLarge.");
+    const yieldAccount = await program.account.state.fetch(stateAddress);
+    expect(yieldAccount.price.toNumber()).equal(1_000);
+
+    // remove the limit again
+    await updateLimit(0);
+  });
+  it("Only refuses stale prices while the staleness check is enabled", async () => {
+    const updateStalenessThreshold = async (priceStalenessThreshold: number) =>
+      BuyBurnFixedClient.updateController(
+        stateAddress,
+        authority.publicKey,
+        treasury.publicKey,
+        mint,
+        holdingAccount.publicKey,
+        holdingTokenAccount.address,
+        new BN(1_000),
+        0.5,
+        new BN(1),
+        new BN(priceStalenessThreshold),
+        0
+      );
+    const allocateYield = async () =>
+      BuyBurnFixedClient.allocateYield(
+        authority.publicKey,
+        stateAddress,
+        new BN(LAMPORTS_PER_SOL),
+        new BN(LAMPORTS_PER_SOL)
+      );
+    const waitForPriceToAge = async () =>
+      new Promise((resolve) => setTimeout(resolve, 2_000));
+
+    await updateStalenessThreshold(1);
+    await waitForPriceToAge();
+    await expect(allocateYield()).to.be.rejectedWith("StalePrice.");
+
+    // a threshold of 0 disables the check, however old the price
+    await updateStalenessThreshold(0);
+    await waitForPriceToAge();
+    await allocateYield();
+
+    await updateStalenessThreshold(3600);
+  });
+  it("Can retire to a vault instead of burning", async () => {
+    const retirementVault = await getOrCreateAssociatedTokenAccount(
+      program.provider.connection,
+      authority,
+      mint,
+      treasury.publicKey
+    );
+
+    await BuyBurnFixedClient.updateController(
+      stateAddress,
+      authority.publicKey,
+      treasury.publicKey,
+      mint,
+      holdingAccount.publicKey,
+      holdingTokenAccount.address,
+      new BN(1_100),
+      0.5,
+      new BN(1),
+      new BN(3600),
+      0,
+      PublicKey.default,
+      PublicKey.default,
+      100,
+      {
+        backend: { vault: {} },
+        vault: retirementVault.address,
+        beneficiary: authority.publicKey,
+      }
+    );
+
+    const supplyBefore = (
+      await program.provider.connection.getTokenSupply(mint)
+    ).value.amount;
+
+    await BuyBurnFixedClient.allocateYield(
+      authority.publicKey,
+      stateAddress,
+      new BN(LAMPORTS_PER_SOL),
+      new BN(1_100_000)
+    );
+
+    // the tokens were moved rather than burned
+    const supplyAfter = (await program.provider.connection.getTokenSupply(mint))
+      .value.amount;
+    expect(supplyAfter).equal(supplyBefore);
+    const vaultAccount = await getAccount(
+      program.provider.connection,
+      retirementVault.address
+    );
+    expect(Number(vaultAccount.amount)).equal(1_000);
+
+    const receipt = await program.account.retirementReceipt.fetch(
+      BuyBurnFixedClient.getRetirementReceipt(stateAddress, new BN(0))
+    );
+    expect(receipt.amount.toNumber()).equal(1_000);
+    expect(receipt.beneficiary.toBase58()).equal(
+      authority.publicKey.toBase58()
+    );
+    expect(receipt.mint.toBase58()).equal(mint.toBase58());
+
+    const state = await program.account.state.fetch(stateAddress);
+    expect(state.totalRetirements.toNumber()).equal(1);
   });
   it("Can update controller state", async () => {
     const newAuthority = Keypair.generate();
@@ -214,7 +404,9 @@ describe("buy-burn-fixed", () => {
         newHoldingTokenAccount.address,
         new BN(10),
         1,
-        new BN(100)
+        new BN(100),
+        new BN(3600),
+        0
       );
     } catch (e) {
       console.log(e);
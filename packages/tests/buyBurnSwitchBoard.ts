@@ -1,6 +1,6 @@
-// import * as anchor from "@coral-xyz/anchor";
-// import { Program } from "@coral-xyz/anchor";
-// import { BuyBurnSwitchboard } from "../types/buy_burn_switchboard";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { BuyBurnSwitchboard } from "../types/buy_burn_switchboard";
 import BN from "bn.js";
 import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
 import {
@@ -18,8 +18,8 @@ import {
 } from "@solana/spl-token";
 import { expect } from "chai";
 import testAuthority from "./fixtures/id.json";
-const program = null; // TODO
-// const program = anchor.workspace.BuyBurnSwitchboard as Program<BuyBurnSwitchboard>;
+const program = anchor.workspace
+  .BuyBurnSwitchboard as Program<BuyBurnSwitchboard>;
 
 export const expectAmount = (
   actualAmount: number | BN,
@@ -51,7 +51,7 @@ const nctUsdPriceFeed = new PublicKey(
 );
 const FEED_STALENESS_THRESHOLD = 60 * 60 * 24 * 365 * 10; // the test fixtures will expire in 10 years
 
-describe.skip("buy-burn-switchboard", () => {
+describe("buy-burn-switchboard", () => {
   let client: YieldControllerClient;
   const authority = Keypair.fromSecretKey(Uint8Array.from(testAuthority));
   const treasury = Keypair.generate();
@@ -212,37 +212,56 @@ describe.skip("buy-burn-switchboard", () => {
     )) as BuyBurnSwitchboardState;
 
     // We expect:
-    // 1. The yield account to have 0 balance
-    // 2. The treasury account to have received 100 SOL * 0.1 = 10 SOL
-    // 3. The SOL holding account to have received 100 SOL * 0.9 = 90 SOL
+    // 1. The yield account to have only its rent-exempt minimum left,
+    //    so that 100 SOL less that minimum is allocated
+    // 2. The SOL holding account to have received 90% of the allocation (as an f32 proportion)
+    // 3. The treasury account to have received the rest
     // 4. The token holding account to have (90 / 0.0945 = 952) fewer tokens
     // 5. The state account to have been updated with the amount of tokens that were burned
+    const rentExemptMinimum =
+      await program.provider.connection.getMinimumBalanceForRentExemption(0);
+    const allocated = 100 * LAMPORTS_PER_SOL - rentExemptMinimum;
+    const purchaseAmount = Math.floor(allocated * Math.fround(0.9));
 
-    const expectedBurnedTokens = 90 / expectedNctSolPrice;
+    const expectedBurnedTokens = Math.floor(
+      purchaseAmount / (10 ** (9 - tokenDecimals) * expectedNctSolPrice)
+    );
 
     console.log("expectedBurnedTokens", expectedBurnedTokens);
 
-    expectAmount(yieldAccountBalanceAfter, 0);
-    expectAmount(treasuryBalanceAfter, 10 * LAMPORTS_PER_SOL, 3000); // TODO fix floating point maths
-    expectAmount(holdingAccountBalanceAfter, 90 * LAMPORTS_PER_SOL, 3000); // TODO fix floating point maths
-    expectAmount(
-      holdingTokenAccountBalanceAfter.value.uiAmount!,
-      tokensToMint - expectedBurnedTokens
-    );
+    expectAmount(yieldAccountBalanceAfter, rentExemptMinimum);
+    expectAmount(treasuryBalanceAfter, allocated - purchaseAmount);
+    expectAmount(holdingAccountBalanceAfter, purchaseAmount);
+    // the feeds' prices are parsed on-chain, so the burned amount may differ by rounding
     expectAmount(
-      state.totalTokensPurchased.toNumber(),
-      expectedBurnedTokens * 10 ** tokenDecimals,
+      new BN(holdingTokenAccountBalanceAfter.value.amount),
+      new BN(tokensToMint)
+        .mul(new BN(10 ** tokenDecimals))
+        .sub(new BN(expectedBurnedTokens)),
       10
     );
+    expectAmount(state.totalTokensPurchased, expectedBurnedTokens, 10);
   });
 
-  it("can set a new value for total tokens purchased", async () => {
-    const newTotalTokens = new BN(5);
-    await client.setTotalTokensPurchased(newTotalTokens);
+  it("can adjust total tokens purchased with an audit entry", async () => {
+    const { totalTokensPurchased } = await client.getState();
+    const delta = new BN(-5);
+    await client.adjustTotalTokensPurchased(delta, { correction: {} });
     const state = await client.getState();
 
     expect(state.totalTokensPurchased.toNumber()).equal(
-      newTotalTokens.toNumber()
+      totalTokensPurchased.add(delta).toNumber()
+    );
+
+    const adjustmentLog = await program.account.adjustmentLog.fetch(
+      YieldControllerClient.getAdjustmentLogAddress(
+        client.stateAddress as PublicKey
+      )
+    );
+    expect(adjustmentLog.adjustments).to.have.length(1);
+    expect(adjustmentLog.adjustments[0].delta.toNumber()).equal(-5);
+    expect(adjustmentLog.adjustments[0].signer.toBase58()).equal(
+      authority.publicKey.toBase58()
     );
   });
 
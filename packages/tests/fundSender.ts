@@ -11,8 +11,10 @@ import { FundSenderClient } from "../fund-sender/client";
 import {
   createMint,
   getAccount,
+  getAssociatedTokenAddressSync,
   getOrCreateAssociatedTokenAccount,
   mintTo,
+  TOKEN_2022_PROGRAM_ID,
 } from "@solana/spl-token";
 
 import * as chai from "chai";
@@ -29,6 +31,15 @@ describe("fund-sender", () => {
   const destinationName = "ecoToken";
 
   const spendThreshold = new BN(1);
+  // input accounts never pay out their rent-exempt minimum
+  let rentExemptMinimum: number;
+
+  before(async () => {
+    rentExemptMinimum =
+      await AnchorProvider.env().connection.getMinimumBalanceForRentExemption(
+        0
+      );
+  });
 
   beforeEach(async () => {
     sunriseState = Keypair.generate().publicKey;
@@ -64,6 +75,40 @@ describe("fund-sender", () => {
       );
     });
 
+    it("should migrate to a new destination name", async () => {
+      const connection = client.provider.connection;
+      const oldStateAddress = client.stateAddress;
+      await client.provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: client.provider.wallet.publicKey,
+            toPubkey: client.getInputAccount(),
+            lamports: LAMPORTS_PER_SOL,
+          })
+        )
+      );
+      const { totalSpent } = await client.program.account.state.fetch(
+        oldStateAddress
+      );
+
+      const migrated = await client.migrateDestination("renamedDestination");
+
+      expect(migrated.config.destinationName).to.equal("renamedDestination");
+      expect(migrated.config.destinationAccount).to.deep.equal(
+        client.config?.destinationAccount
+      );
+      const newState = await migrated.program.account.state.fetch(
+        migrated.stateAddress
+      );
+      expect(newState.totalSpent.toString()).to.equal(totalSpent.toString());
+      expect(await connection.getBalance(migrated.getInputAccount())).to.equal(
+        LAMPORTS_PER_SOL
+      );
+      expect(await connection.getAccountInfo(oldStateAddress)).to.be.null;
+
+      client = migrated;
+    });
+
     it("should not be updateable by others", async () => {
       const anotherUser = Keypair.generate();
       const wallet = new Wallet(anotherUser);
@@ -187,7 +232,8 @@ describe("fund-sender", () => {
           SystemProgram.transfer({
             fromPubkey: client.provider.wallet.publicKey,
             toPubkey: client.getInputAccount(),
-            lamports: amountToSend,
+            // the input account keeps its rent-exempt minimum
+            lamports: amountToSend + rentExemptMinimum,
           })
         )
       );
@@ -199,6 +245,132 @@ describe("fund-sender", () => {
       expect(destinationAccountInfo?.lamports).to.equal(amountToSend);
     });
 
+    it("should be able to send all available funds", async () => {
+      const amountAvailable = LAMPORTS_PER_SOL;
+      await client.provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: client.provider.wallet.publicKey,
+            toPubkey: client.getInputAccount(),
+            lamports: amountAvailable + rentExemptMinimum,
+          })
+        )
+      );
+
+      await client.sendAllFunds();
+
+      const destinationAccountInfo =
+        await client.provider.connection.getAccountInfo(destinationAccount);
+      expect(destinationAccountInfo?.lamports).to.equal(amountAvailable);
+
+      const inputAccountInfo = await client.provider.connection.getAccountInfo(
+        client.getInputAccount()
+      );
+      expect(inputAccountInfo?.lamports).to.equal(rentExemptMinimum);
+    });
+
+    it("should split funds between weighted destinations", async () => {
+      const escrowAccount = Keypair.generate().publicKey;
+      const splitClient = await FundSenderClient.register(
+        sunriseState,
+        authority.publicKey,
+        "split",
+        destinationAccount,
+        certificateVault.publicKey,
+        spendThreshold,
+        [destinationAccount, escrowAccount],
+        [30, 70]
+      );
+
+      const amountToSend = LAMPORTS_PER_SOL;
+      await splitClient.provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: splitClient.provider.wallet.publicKey,
+            toPubkey: splitClient.getInputAccount(),
+            lamports: amountToSend + rentExemptMinimum,
+          })
+        )
+      );
+
+      await splitClient.sendFunds(new BN(amountToSend));
+
+      const connection = splitClient.provider.connection;
+      expect(await connection.getBalance(destinationAccount)).to.equal(
+        (amountToSend * 30) / 100
+      );
+      expect(await connection.getBalance(escrowAccount)).to.equal(
+        (amountToSend * 70) / 100
+      );
+    });
+
+    it("should reject weighted destinations not summing to 100", async () => {
+      const shouldFail = FundSenderClient.register(
+        sunriseState,
+        authority.publicKey,
+        "invalid-split",
+        destinationAccount,
+        certificateVault.publicKey,
+        spendThreshold,
+        [destinationAccount, Keypair.generate().publicKey],
+        [30, 60]
+      );
+      return expect(shouldFail).to.be.rejectedWith(
+        "Invalid destination proportions."
+      );
+    });
+
+    it("should forward SPL tokens to the destination", async () => {
+      const connection = client.program.provider.connection;
+      const tokenClient = await FundSenderClient.register(
+        sunriseState,
+        authority.publicKey,
+        "token",
+        destinationAccount,
+        certificateVault.publicKey,
+        spendThreshold,
+        [],
+        [],
+        mint,
+        new BN(10)
+      );
+      const inputTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        tokenClient.getInputAccount(),
+        true
+      );
+      const tokenAmount = 100;
+      const mintTx = await mintTo(
+        connection,
+        authority,
+        mint,
+        inputTokenAccount.address,
+        authority.publicKey,
+        tokenAmount
+      );
+      await connection.confirmTransaction({
+        signature: mintTx,
+        ...(await connection.getLatestBlockhash()),
+      });
+
+      await tokenClient.sendTokenFund();
+
+      const destinationTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        destinationAccount
+      );
+      expect(Number(destinationTokenAccount.amount)).to.equal(tokenAmount);
+
+      const state = await tokenClient.program.account.state.fetch(
+        tokenClient.stateAddress
+      );
+      expect(state.totalTokensSpent.toNumber()).to.equal(tokenAmount);
+    });
+
     it("should be transfer and store certificates (SPL tokens) to certificate vault", async () => {
       const connection = client.program.provider.connection;
       // const payer = Keypair.generate();
@@ -246,6 +418,22 @@ describe("fund-sender", () => {
       expect(Number(certificateVaultInfo.amount)).to.equal(mintAmount);
     });
 
+    it("should record stored certificates against the funds sent", async () => {
+      const state = await client.program.account.state.fetch(
+        client.stateAddress
+      );
+      const records = await client.getCertificateRecords();
+
+      expect(records).to.have.lengthOf(state.totalCertificates.toNumber());
+      const lastRecord = records[records.length - 1];
+      expect(lastRecord.certificate.toBase58()).to.equal(mint.toBase58());
+      expect(lastRecord.amount.toNumber()).to.equal(100);
+      expect(lastRecord.totalSpent.toString()).to.equal(
+        state.totalSpent.toString()
+      );
+      expect(lastRecord.quarantined).to.be.false;
+    });
+
     it("should not be able to transfer from a token account not owned by input_account", async () => {
       const connection = client.program.provider.connection;
       // const payer = Keypair.generate();
@@ -343,5 +531,213 @@ describe("fund-sender", () => {
 
       expect(Number(certificateVaultInfo.amount)).to.equal(mintAmount);
     });
+
+    it("should store Token-2022 certificates", async () => {
+      const connection = client.program.provider.connection;
+      const token2022Mint = await createMint(
+        connection,
+        authority,
+        authority.publicKey,
+        null,
+        0,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        token2022Mint,
+        client.getInputAccount(),
+        true,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(
+        connection,
+        authority,
+        token2022Mint,
+        ata.address,
+        authority.publicKey,
+        1,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      const updatedClient = await FundSenderClient.fetch(client.stateAddress);
+      await updatedClient.storeCertificates(ata.address, token2022Mint);
+
+      const certificateVaultAta = getAssociatedTokenAddressSync(
+        token2022Mint,
+        updatedClient.config.certificateVault,
+        true,
+        TOKEN_2022_PROGRAM_ID
+      );
+      const certificateVaultInfo = await getAccount(
+        connection,
+        certificateVaultAta,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      expect(Number(certificateVaultInfo.amount)).to.equal(1);
+    });
+
+    it("should lock certificates in the state's vault until released", async () => {
+      const connection = client.program.provider.connection;
+      const lockedClient = await FundSenderClient.register(
+        sunriseState,
+        authority.publicKey,
+        "locked",
+        destinationAccount,
+        certificateVault.publicKey,
+        spendThreshold,
+        [],
+        [],
+        PublicKey.default,
+        new BN(0),
+        PublicKey.default,
+        PublicKey.default,
+        PublicKey.default,
+        PublicKey.default,
+        [],
+        PublicKey.default,
+        true
+      );
+      const lockedVault = FundSenderClient.getCertificateVault(
+        lockedClient.stateAddress
+      );
+      expect(lockedClient.config.certificateVault.toBase58()).to.equal(
+        lockedVault.toBase58()
+      );
+
+      const ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        lockedClient.getInputAccount(),
+        true
+      );
+      await mintTo(
+        connection,
+        authority,
+        mint,
+        ata.address,
+        authority.publicKey,
+        10
+      );
+      // certificates can be attributed to a staker whose yield funded them
+      const staker = Keypair.generate().publicKey;
+      await lockedClient.storeCertificates(ata.address, mint, false, staker);
+      const certificateRecord = FundSenderClient.getCertificateRecord(
+        lockedClient.stateAddress,
+        new BN(0)
+      );
+      const storedRecord =
+        await lockedClient.program.account.certificateRecord.fetch(
+          certificateRecord
+        );
+      expect(storedRecord.beneficiary.toBase58()).to.equal(staker.toBase58());
+
+      const authorisedClient = await FundSenderClient.fetch(
+        lockedClient.stateAddress,
+        new AnchorProvider(connection, new Wallet(authority), {})
+      );
+      const beneficiary = Keypair.generate().publicKey;
+      await authorisedClient.releaseCertificate(
+        mint,
+        beneficiary,
+        new BN(4),
+        certificateRecord
+      );
+      const releasedRecord =
+        await lockedClient.program.account.certificateRecord.fetch(
+          certificateRecord
+        );
+      expect(releasedRecord.beneficiary.toBase58()).to.equal(
+        beneficiary.toBase58()
+      );
+      const beneficiaryAta = await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(mint, beneficiary)
+      );
+      expect(Number(beneficiaryAta.amount)).to.equal(4);
+
+      // the vault cannot be changed once certificates are locked
+      const shouldFail = authorisedClient.updateCertificateVault(
+        Keypair.generate().publicKey
+      );
+      return expect(shouldFail).to.be.rejectedWith(
+        "IncorrectCertificateVault."
+      );
+    });
+
+    it("should quarantine or reject certificates not matching the filter", async () => {
+      const connection = client.program.provider.connection;
+      const ata = await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        client.getInputAccount(),
+        true
+      );
+      const mintAmount = 10;
+      await mintTo(
+        connection,
+        authority,
+        mint,
+        ata.address,
+        authority.publicKey,
+        mintAmount
+      );
+
+      const authorisedClient = await FundSenderClient.fetch(
+        client.stateAddress,
+        new AnchorProvider(connection, new Wallet(authority), {})
+      );
+      // the test mint has no metadata, so it cannot match the creator
+      const quarantineVault = Keypair.generate();
+      await authorisedClient.updateCertificateFilter(
+        PublicKey.default,
+        Keypair.generate().publicKey,
+        [],
+        quarantineVault.publicKey
+      );
+
+      const updatedClient = await FundSenderClient.fetch(client.stateAddress);
+      const shouldFail = updatedClient.storeCertificates(ata.address, mint);
+      await expect(shouldFail).to.be.rejectedWith("IncorrectHoldAccount.");
+
+      await updatedClient.storeCertificates(ata.address, mint, true);
+      const quarantineVaultAta = await getOrCreateAssociatedTokenAccount(
+        connection,
+        authority,
+        mint,
+        quarantineVault.publicKey,
+        true
+      );
+      expect(Number(quarantineVaultAta.amount)).to.equal(mintAmount);
+
+      // without a quarantine vault, non-matching certificates are rejected
+      await authorisedClient.updateCertificateFilter(
+        PublicKey.default,
+        Keypair.generate().publicKey,
+        [],
+        PublicKey.default
+      );
+      await mintTo(
+        connection,
+        authority,
+        mint,
+        ata.address,
+        authority.publicKey,
+        mintAmount
+      );
+      const rejectedClient = await FundSenderClient.fetch(client.stateAddress);
+      return expect(
+        rejectedClient.storeCertificates(ata.address, mint)
+      ).to.be.rejectedWith("InvalidCertificate.");
+    });
   });
 });
@@ -22,6 +22,15 @@ describe("yield-router", () => {
   const authority = Keypair.fromSecretKey(Buffer.from(testAuthority));
 
   const spendThreshold = new BN(1);
+  // input accounts never pay out their rent-exempt minimum
+  let rentExemptMinimum: number;
+
+  before(async () => {
+    rentExemptMinimum =
+      await AnchorProvider.env().connection.getMinimumBalanceForRentExemption(
+        0
+      );
+  });
 
   beforeEach(async () => {
     sunriseState = Keypair.generate().publicKey;
@@ -152,7 +161,8 @@ describe("yield-router", () => {
           SystemProgram.transfer({
             fromPubkey: client.provider.wallet.publicKey,
             toPubkey: client.getInputYieldAccount(),
-            lamports: LAMPORTS_PER_SOL,
+            // the input yield account keeps its rent-exempt minimum
+            lamports: LAMPORTS_PER_SOL + rentExemptMinimum,
           })
         )
       );
@@ -163,6 +173,23 @@ describe("yield-router", () => {
         await client.provider.connection.getAccountInfo(outputYieldAccounts[0]);
       expect(outputYieldAccount?.lamports).to.equal(LAMPORTS_PER_SOL);
     });
+
+    it("should not allocate the input yield account's rent", async () => {
+      await client.provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: client.provider.wallet.publicKey,
+            toPubkey: client.getInputYieldAccount(),
+            lamports: LAMPORTS_PER_SOL,
+          })
+        )
+      );
+
+      const shouldFail = client.allocateYield(new BN(LAMPORTS_PER_SOL));
+      return expect(shouldFail).to.be.rejectedWith(
+        "Insufficient funds for transaction."
+      );
+    });
   });
 
   context("with two output yield accounts", () => {
@@ -189,7 +216,8 @@ describe("yield-router", () => {
           SystemProgram.transfer({
             fromPubkey: client.provider.wallet.publicKey,
             toPubkey: client.getInputYieldAccount(),
-            lamports: LAMPORTS_PER_SOL,
+            // the input yield account keeps its rent-exempt minimum
+            lamports: LAMPORTS_PER_SOL + rentExemptMinimum,
           })
         )
       );
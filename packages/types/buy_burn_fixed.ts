@@ -35,6 +35,36 @@ export type BuyBurnFixed = {
           "name": "state",
           "writable": true
         },
+        {
+          "name": "yieldAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  121,
+                  105,
+                  101,
+                  108,
+                  100,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
         {
           "name": "mint",
           "writable": true,
@@ -63,6 +93,58 @@ export type BuyBurnFixed = {
             "state"
           ]
         },
+        {
+          "name": "retirementVault",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "retirementReceipt",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  116,
+                  105,
+                  114,
+                  101,
+                  109,
+                  101,
+                  110,
+                  116,
+                  95,
+                  114,
+                  101,
+                  99,
+                  101,
+                  105,
+                  112,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_retirements",
+                "account": "state"
+              }
+            ]
+          }
+        },
         {
           "name": "tokenProgram",
           "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
@@ -83,6 +165,127 @@ export type BuyBurnFixed = {
         }
       ]
     },
+    {
+      "name": "migrateState",
+      "docs": [
+        "Move a state registered before states were indexed (seeded by the mint only)",
+        "to the indexed PDA, carrying over its configuration and total spent.",
+        "The legacy balance is moved to the new state's yield account.",
+        "The holding token account delegate must be re-approved to the new state afterwards."
+      ],
+      "discriminator": [
+        34,
+        189,
+        226,
+        222,
+        218,
+        156,
+        19,
+        213
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "legacyState",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mint"
+              }
+            ]
+          }
+        },
+        {
+          "name": "state",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "mint"
+              },
+              {
+                "kind": "arg",
+                "path": "args.index"
+              }
+            ]
+          }
+        },
+        {
+          "name": "yieldAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  121,
+                  105,
+                  101,
+                  108,
+                  100,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "args",
+          "type": {
+            "defined": {
+              "name": "migrateStateInput"
+            }
+          }
+        }
+      ]
+    },
     {
       "name": "registerState",
       "discriminator": [
@@ -119,6 +322,10 @@ export type BuyBurnFixed = {
               {
                 "kind": "arg",
                 "path": "state_in.mint"
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.index"
               }
             ]
           }
@@ -142,6 +349,189 @@ export type BuyBurnFixed = {
         }
       ]
     },
+    {
+      "name": "swapAndBurn",
+      "discriminator": [
+        238,
+        187,
+        75,
+        164,
+        53,
+        245,
+        200,
+        172
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "yieldAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  121,
+                  105,
+                  101,
+                  108,
+                  100,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "writable": true,
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "wrappedSolAccount",
+          "docs": [
+            "A wrapped SOL account owned by the state, used as the swap input"
+          ],
+          "writable": true
+        },
+        {
+          "name": "purchasedTokenAccount",
+          "docs": [
+            "A token account owned by the state, receiving the swap output before it is burned"
+          ],
+          "writable": true
+        },
+        {
+          "name": "swapProgram",
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "swapPool",
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "swapPoolAuthority"
+        },
+        {
+          "name": "swapPoolSource",
+          "writable": true
+        },
+        {
+          "name": "swapPoolDestination",
+          "writable": true
+        },
+        {
+          "name": "swapPoolMint",
+          "writable": true
+        },
+        {
+          "name": "swapPoolFeeAccount",
+          "writable": true
+        },
+        {
+          "name": "retirementVault",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "retirementReceipt",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  116,
+                  105,
+                  114,
+                  101,
+                  109,
+                  101,
+                  110,
+                  116,
+                  95,
+                  114,
+                  101,
+                  99,
+                  101,
+                  105,
+                  112,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_retirements",
+                "account": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "args",
+          "type": {
+            "defined": {
+              "name": "swapAndBurnInput"
+            }
+          }
+        }
+      ]
+    },
     {
       "name": "updatePrice",
       "discriminator": [
@@ -208,6 +598,10 @@ export type BuyBurnFixed = {
               {
                 "kind": "arg",
                 "path": "state_in.mint"
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.index"
               }
             ]
           }
@@ -226,6 +620,19 @@ export type BuyBurnFixed = {
     }
   ],
   "accounts": [
+    {
+      "name": "retirementReceipt",
+      "discriminator": [
+        47,
+        148,
+        156,
+        19,
+        231,
+        230,
+        200,
+        230
+      ]
+    },
     {
       "name": "state",
       "discriminator": [
@@ -260,6 +667,56 @@ export type BuyBurnFixed = {
       "code": 6003,
       "name": "purchaseThresholdExceeded",
       "msg": "purchase threshold exceeded"
+    },
+    {
+      "code": 6004,
+      "name": "invalidPrice",
+      "msg": "price must be greater than zero"
+    },
+    {
+      "code": 6005,
+      "name": "priceChangeTooLarge",
+      "msg": "price change exceeds the maximum allowed within the price change window"
+    },
+    {
+      "code": 6006,
+      "name": "stalePrice",
+      "msg": "price is stale"
+    },
+    {
+      "code": 6007,
+      "name": "slippageExceeded",
+      "msg": "swap returned less than the minimum amount out"
+    },
+    {
+      "code": 6008,
+      "name": "missingRetirementAccounts",
+      "msg": "retirement vault and receipt accounts are required"
+    },
+    {
+      "code": 6009,
+      "name": "unauthorized",
+      "msg": "incorrect update authority"
+    },
+    {
+      "code": 6010,
+      "name": "invalidLegacyState",
+      "msg": "invalid legacy state account"
+    },
+    {
+      "code": 6011,
+      "name": "invalidMaxSlippage",
+      "msg": "max slippage must be at most 10000 basis points"
+    },
+    {
+      "code": 6012,
+      "name": "unexpectedRetirementAccounts",
+      "msg": "retirement vault and receipt accounts must not be passed when burning"
+    },
+    {
+      "code": 6013,
+      "name": "tooManyPriceUpdates",
+      "msg": "too many price updates within the price change window"
     }
   ],
   "types": [
@@ -315,6 +772,139 @@ export type BuyBurnFixed = {
           {
             "name": "purchaseProportion",
             "type": "f32"
+          },
+          {
+            "name": "priceStalenessThreshold",
+            "type": "u64"
+          },
+          {
+            "name": "maxPriceChangeBps",
+            "type": "u16"
+          },
+          {
+            "name": "swapProgram",
+            "type": "pubkey"
+          },
+          {
+            "name": "swapPool",
+            "type": "pubkey"
+          },
+          {
+            "name": "maxSlippageBps",
+            "type": "u16"
+          },
+          {
+            "name": "retirementBackend",
+            "type": {
+              "defined": {
+                "name": "retirementBackend"
+              }
+            }
+          },
+          {
+            "name": "retirementVault",
+            "type": "pubkey"
+          },
+          {
+            "name": "retirementBeneficiary",
+            "type": "pubkey"
+          },
+          {
+            "name": "index",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "migrateStateInput",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "index",
+            "type": "u8"
+          },
+          {
+            "name": "priceStalenessThreshold",
+            "type": "u64"
+          },
+          {
+            "name": "maxPriceChangeBps",
+            "type": "u16"
+          },
+          {
+            "name": "maxSlippageBps",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "priceRecord",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "price",
+            "type": "u64"
+          },
+          {
+            "name": "slot",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "retirementBackend",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "burn"
+          },
+          {
+            "name": "vault"
+          }
+        ]
+      }
+    },
+    {
+      "name": "retirementReceipt",
+      "docs": [
+        "A record of tokens retired into the retirement vault, one per retirement"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "state",
+            "type": "pubkey"
+          },
+          {
+            "name": "mint",
+            "type": "pubkey"
+          },
+          {
+            "name": "beneficiary",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          },
+          {
+            "name": "index",
+            "type": "u64"
           }
         ]
       }
@@ -363,6 +953,95 @@ export type BuyBurnFixed = {
           {
             "name": "bump",
             "type": "u8"
+          },
+          {
+            "name": "priceStalenessThreshold",
+            "type": "u64"
+          },
+          {
+            "name": "maxPriceChangeBps",
+            "type": "u16"
+          },
+          {
+            "name": "lastPriceUpdateSlot",
+            "type": "u64"
+          },
+          {
+            "name": "lastPriceUpdateTimestamp",
+            "type": "i64"
+          },
+          {
+            "name": "priceHistory",
+            "type": {
+              "array": [
+                {
+                  "defined": {
+                    "name": "priceRecord"
+                  }
+                },
+                16
+              ]
+            }
+          },
+          {
+            "name": "priceHistoryIndex",
+            "type": "u8"
+          },
+          {
+            "name": "swapProgram",
+            "type": "pubkey"
+          },
+          {
+            "name": "swapPool",
+            "type": "pubkey"
+          },
+          {
+            "name": "retirementBackend",
+            "type": {
+              "defined": {
+                "name": "retirementBackend"
+              }
+            }
+          },
+          {
+            "name": "retirementVault",
+            "type": "pubkey"
+          },
+          {
+            "name": "retirementBeneficiary",
+            "type": "pubkey"
+          },
+          {
+            "name": "totalRetirements",
+            "type": "u64"
+          },
+          {
+            "name": "index",
+            "type": "u8"
+          },
+          {
+            "name": "yieldAccountBump",
+            "type": "u8"
+          },
+          {
+            "name": "maxSlippageBps",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "swapAndBurnInput",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "solAmount",
+            "type": "u64"
+          },
+          {
+            "name": "minimumAmountOut",
+            "type": "u64"
           }
         ]
       }
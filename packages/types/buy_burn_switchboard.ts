@@ -1,245 +1,919 @@
+/**
+ * Program IDL in camelCase format in order to be used in JS/TS.
+ *
+ * Note that this is only a type helper and is not the actual IDL. The original
+ * IDL can be found at `target/idl/buy_burn_switchboard.json`.
+ */
 export type BuyBurnSwitchboard = {
-  "version": "0.1.0",
-  "name": "buy_burn_switchboard",
+  "address": "sbnbpcN3HVfcj9jTwzncwLeNvCzSwbfMwNmdAgX36VW",
+  "metadata": {
+    "name": "buyBurnSwitchboard",
+    "version": "0.1.0",
+    "spec": "0.1.0",
+    "description": "A program that buys and burns tokens from a holding account at a price set by an oracle"
+  },
   "instructions": [
     {
-      "name": "registerState",
+      "name": "adjustTotalTokensPurchased",
+      "docs": [
+        "Correct the published total tokens purchased by `delta`,",
+        "recording the change in the adjustment log and as an event"
+      ],
+      "discriminator": [
+        248,
+        196,
+        107,
+        107,
+        232,
+        239,
+        55,
+        211
+      ],
       "accounts": [
         {
           "name": "payer",
-          "isMut": true,
-          "isSigner": true
+          "writable": true,
+          "signer": true
         },
         {
           "name": "state",
-          "isMut": true,
-          "isSigner": false
+          "writable": true
         },
         {
-          "name": "mint",
-          "isMut": false,
-          "isSigner": false
+          "name": "adjustmentLog",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  97,
+                  100,
+                  106,
+                  117,
+                  115,
+                  116,
+                  109,
+                  101,
+                  110,
+                  116,
+                  95,
+                  108,
+                  111,
+                  103
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
         },
         {
           "name": "systemProgram",
-          "isMut": false,
-          "isSigner": false
+          "address": "11111111111111111111111111111111"
         }
       ],
       "args": [
         {
-          "name": "state",
+          "name": "delta",
+          "type": "i64"
+        },
+        {
+          "name": "reason",
           "type": {
-            "defined": "GenericStateInput"
+            "defined": {
+              "name": "adjustmentReason"
+            }
           }
         }
       ]
     },
     {
-      "name": "updateState",
+      "name": "allocateYield",
+      "discriminator": [
+        225,
+        224,
+        81,
+        124,
+        246,
+        226,
+        35,
+        240
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "yieldAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  121,
+                  105,
+                  101,
+                  108,
+                  100,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint",
+          "writable": true,
+          "relations": [
+            "state",
+            "holdingTokenAccount"
+          ]
+        },
+        {
+          "name": "treasury",
+          "writable": true,
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "holdingAccount",
+          "writable": true,
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "holdingTokenAccount",
+          "writable": true,
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "solUsdPriceFeed",
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "nctUsdPriceFeed",
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "retirementVault",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "retirementReceipt",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  116,
+                  105,
+                  114,
+                  101,
+                  109,
+                  101,
+                  110,
+                  116,
+                  95,
+                  114,
+                  101,
+                  99,
+                  101,
+                  105,
+                  112,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_retirements",
+                "account": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "receiptTreeConfig",
+          "docs": [
+            "Only required when burn receipts are enabled"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "receiptMerkleTree",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "receiptVault",
+          "optional": true
+        },
+        {
+          "name": "logWrapper",
+          "docs": [
+            "Only required when burn receipts are enabled"
+          ],
+          "optional": true
+        },
+        {
+          "name": "compressionProgram",
+          "optional": true,
+          "address": "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+        },
+        {
+          "name": "bubblegumProgram",
+          "optional": true,
+          "address": "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY"
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "migrateState",
+      "docs": [
+        "Upgrade a state registered before swaps, vault retirement and burn receipts were added,",
+        "growing it to the current layout. The new settings start disabled: no swap pool,",
+        "burning rather than retiring to a vault, no receipts and no slippage allowance,",
+        "until set by `update_state`."
+      ],
+      "discriminator": [
+        34,
+        189,
+        226,
+        222,
+        218,
+        156,
+        19,
+        213
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "registerState",
+      "discriminator": [
+        137,
+        35,
+        194,
+        234,
+        128,
+        215,
+        19,
+        45
+      ],
       "accounts": [
         {
           "name": "payer",
-          "isMut": true,
-          "isSigner": true
+          "writable": true,
+          "signer": true
         },
         {
           "name": "state",
-          "isMut": true,
-          "isSigner": false
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.mint"
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.index"
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
         }
       ],
       "args": [
         {
           "name": "state",
           "type": {
-            "defined": "GenericStateInput"
+            "defined": {
+              "name": "genericStateInput"
+            }
           }
         }
       ]
     },
     {
-      "name": "allocateYield",
+      "name": "swapAndBurn",
+      "discriminator": [
+        238,
+        187,
+        75,
+        164,
+        53,
+        245,
+        200,
+        172
+      ],
       "accounts": [
         {
           "name": "payer",
-          "isMut": true,
-          "isSigner": true
+          "writable": true,
+          "signer": true
         },
         {
           "name": "state",
-          "isMut": true,
-          "isSigner": false
+          "writable": true
         },
         {
           "name": "yieldAccount",
-          "isMut": true,
-          "isSigner": false
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  121,
+                  105,
+                  101,
+                  108,
+                  100,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
         },
         {
           "name": "mint",
-          "isMut": true,
-          "isSigner": false
+          "writable": true,
+          "relations": [
+            "state"
+          ]
         },
         {
           "name": "treasury",
-          "isMut": true,
-          "isSigner": false
+          "writable": true,
+          "relations": [
+            "state"
+          ]
         },
         {
-          "name": "holdingAccount",
-          "isMut": true,
-          "isSigner": false
+          "name": "wrappedSolAccount",
+          "docs": [
+            "A wrapped SOL account owned by the state, used as the swap input"
+          ],
+          "writable": true
         },
         {
-          "name": "holdingTokenAccount",
-          "isMut": true,
-          "isSigner": false
+          "name": "purchasedTokenAccount",
+          "docs": [
+            "A token account owned by the state, receiving the swap output before it is burned"
+          ],
+          "writable": true
         },
         {
           "name": "solUsdPriceFeed",
-          "isMut": false,
-          "isSigner": false
+          "relations": [
+            "state"
+          ]
         },
         {
           "name": "nctUsdPriceFeed",
-          "isMut": false,
-          "isSigner": false
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "swapProgram",
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "swapPool",
+          "relations": [
+            "state"
+          ]
+        },
+        {
+          "name": "swapPoolAuthority"
+        },
+        {
+          "name": "swapPoolSource",
+          "writable": true
+        },
+        {
+          "name": "swapPoolDestination",
+          "writable": true
+        },
+        {
+          "name": "swapPoolMint",
+          "writable": true
+        },
+        {
+          "name": "swapPoolFeeAccount",
+          "writable": true
+        },
+        {
+          "name": "retirementVault",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "retirementReceipt",
+          "docs": [
+            "Required when the state retires to a vault, and refused when it burns"
+          ],
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  116,
+                  105,
+                  114,
+                  101,
+                  109,
+                  101,
+                  110,
+                  116,
+                  95,
+                  114,
+                  101,
+                  99,
+                  101,
+                  105,
+                  112,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_retirements",
+                "account": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "receiptTreeConfig",
+          "docs": [
+            "Only required when burn receipts are enabled"
+          ],
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "receiptMerkleTree",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "receiptVault",
+          "optional": true
+        },
+        {
+          "name": "logWrapper",
+          "docs": [
+            "Only required when burn receipts are enabled"
+          ],
+          "optional": true
+        },
+        {
+          "name": "compressionProgram",
+          "optional": true,
+          "address": "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+        },
+        {
+          "name": "bubblegumProgram",
+          "optional": true,
+          "address": "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY"
         },
         {
           "name": "tokenProgram",
-          "isMut": false,
-          "isSigner": false
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
         },
         {
           "name": "systemProgram",
-          "isMut": false,
-          "isSigner": false
+          "address": "11111111111111111111111111111111"
         }
       ],
-      "args": []
+      "args": [
+        {
+          "name": "minimumAmountOut",
+          "type": "u64"
+        }
+      ]
     },
     {
-      "name": "setTotalTokensPurchased",
+      "name": "updateState",
+      "discriminator": [
+        135,
+        112,
+        215,
+        75,
+        247,
+        185,
+        53,
+        176
+      ],
       "accounts": [
         {
           "name": "payer",
-          "isMut": true,
-          "isSigner": true
+          "writable": true,
+          "signer": true
         },
         {
           "name": "state",
-          "isMut": true,
-          "isSigner": false
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.mint"
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.index"
+              }
+            ]
+          }
         }
       ],
       "args": [
         {
-          "name": "value",
-          "type": "u64"
+          "name": "state",
+          "type": {
+            "defined": {
+              "name": "genericStateInput"
+            }
+          }
         }
       ]
     }
   ],
   "accounts": [
+    {
+      "name": "adjustmentLog",
+      "discriminator": [
+        238,
+        140,
+        23,
+        223,
+        60,
+        5,
+        185,
+        210
+      ]
+    },
+    {
+      "name": "retirementReceipt",
+      "discriminator": [
+        47,
+        148,
+        156,
+        19,
+        231,
+        230,
+        200,
+        230
+      ]
+    },
     {
       "name": "state",
+      "discriminator": [
+        216,
+        146,
+        107,
+        94,
+        104,
+        75,
+        182,
+        177
+      ]
+    }
+  ],
+  "events": [
+    {
+      "name": "totalTokensPurchasedAdjusted",
+      "discriminator": [
+        212,
+        101,
+        90,
+        117,
+        41,
+        253,
+        124,
+        134
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "insufficientFundsForTransaction",
+      "msg": "Insufficient funds for transaction"
+    },
+    {
+      "code": 6001,
+      "name": "invalidTreasury",
+      "msg": "Invalid treasury account"
+    },
+    {
+      "code": 6002,
+      "name": "invalidMint",
+      "msg": "Invalid mint"
+    },
+    {
+      "code": 6003,
+      "name": "purchaseThresholdExceeded",
+      "msg": "Purchase threshold exceeded"
+    },
+    {
+      "code": 6004,
+      "name": "invalidSwitchboardAccount",
+      "msg": "The switchboard feed account is invalid"
+    },
+    {
+      "code": 6005,
+      "name": "staleFeed",
+      "msg": "The switchboard feed has not been updated recently"
+    },
+    {
+      "code": 6006,
+      "name": "confidenceIntervalExceeded",
+      "msg": "The switchboard feed exceeds the confidence interval"
+    },
+    {
+      "code": 6007,
+      "name": "invalidAggregatorRound",
+      "msg": "The switchboard feed round has too few oracle results"
+    },
+    {
+      "code": 6008,
+      "name": "slippageExceeded",
+      "msg": "Swap returned less than the minimum amount out"
+    },
+    {
+      "code": 6009,
+      "name": "missingRetirementAccounts",
+      "msg": "Retirement vault and receipt accounts are required"
+    },
+    {
+      "code": 6010,
+      "name": "missingReceiptAccounts",
+      "msg": "Receipt tree accounts are required"
+    },
+    {
+      "code": 6011,
+      "name": "incorrectReceiptTree",
+      "msg": "Incorrect receipt merkle tree"
+    },
+    {
+      "code": 6012,
+      "name": "incorrectReceiptVault",
+      "msg": "Incorrect receipt vault"
+    },
+    {
+      "code": 6013,
+      "name": "invalidAdjustment",
+      "msg": "Adjustment would make total tokens purchased negative or overflow"
+    },
+    {
+      "code": 6014,
+      "name": "invalidMaxSlippage",
+      "msg": "Max slippage must be at most 10000 basis points"
+    },
+    {
+      "code": 6015,
+      "name": "unexpectedRetirementAccounts",
+      "msg": "Retirement vault and receipt accounts must not be passed when burning"
+    },
+    {
+      "code": 6016,
+      "name": "unauthorized",
+      "msg": "Incorrect update authority"
+    },
+    {
+      "code": 6017,
+      "name": "invalidLegacyState",
+      "msg": "Account is not a state in the legacy layout"
+    }
+  ],
+  "types": [
+    {
+      "name": "adjustment",
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "updateAuthority",
-            "type": "publicKey"
+            "name": "delta",
+            "type": "i64"
           },
           {
-            "name": "treasury",
-            "type": "publicKey"
+            "name": "reason",
+            "type": {
+              "defined": {
+                "name": "adjustmentReason"
+              }
+            }
           },
           {
-            "name": "mint",
-            "type": "publicKey"
+            "name": "signer",
+            "type": "pubkey"
           },
           {
-            "name": "solUsdPriceFeed",
-            "type": "publicKey"
-          },
-          {
-            "name": "nctUsdPriceFeed",
-            "type": "publicKey"
-          },
-          {
-            "name": "holdingAccount",
-            "type": "publicKey"
-          },
-          {
-            "name": "holdingTokenAccount",
-            "type": "publicKey"
-          },
-          {
-            "name": "feedStalenessThreshold",
-            "type": "u64"
-          },
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "adjustmentLog",
+      "docs": [
+        "An append-only log of all manual adjustments to a state's total tokens purchased"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
           {
-            "name": "purchaseThreshold",
-            "type": "u64"
+            "name": "state",
+            "type": "pubkey"
           },
           {
-            "name": "purchaseProportion",
-            "type": "f32"
-          },
+            "name": "adjustments",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "adjustment"
+                }
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "adjustmentReason",
+      "type": {
+        "kind": "enum",
+        "variants": [
           {
-            "name": "totalTokensPurchased",
-            "type": "u64"
+            "name": "correction"
           },
           {
-            "name": "index",
-            "type": "u8"
+            "name": "reconciliation"
           },
           {
-            "name": "bump",
-            "type": "u8"
+            "name": "migration"
           },
           {
-            "name": "yieldAccountBump",
-            "type": "u8"
+            "name": "other"
           }
         ]
       }
-    }
-  ],
-  "types": [
+    },
     {
-      "name": "GenericStateInput",
+      "name": "genericStateInput",
       "type": {
         "kind": "struct",
         "fields": [
           {
             "name": "mint",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "updateAuthority",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "treasury",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "holdingAccount",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "holdingTokenAccount",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "solUsdPriceFeed",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "nctUsdPriceFeed",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "feedStalenessThreshold",
@@ -260,184 +934,95 @@ export type BuyBurnSwitchboard = {
           {
             "name": "yieldAccountBump",
             "type": "u8"
+          },
+          {
+            "name": "swapProgram",
+            "type": "pubkey"
+          },
+          {
+            "name": "swapPool",
+            "type": "pubkey"
+          },
+          {
+            "name": "maxSlippageBps",
+            "type": "u16"
+          },
+          {
+            "name": "retirementBackend",
+            "type": {
+              "defined": {
+                "name": "retirementBackend"
+              }
+            }
+          },
+          {
+            "name": "retirementVault",
+            "type": "pubkey"
+          },
+          {
+            "name": "retirementBeneficiary",
+            "type": "pubkey"
+          },
+          {
+            "name": "receiptMerkleTree",
+            "type": "pubkey"
+          },
+          {
+            "name": "receiptVault",
+            "type": "pubkey"
           }
         ]
       }
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "InsufficientFundsForTransaction",
-      "msg": "Insufficient funds for transaction"
-    },
-    {
-      "code": 6001,
-      "name": "InvalidTreasury",
-      "msg": "Invalid treasury account"
-    },
-    {
-      "code": 6002,
-      "name": "InvalidMint",
-      "msg": "Invalid mint"
-    },
-    {
-      "code": 6003,
-      "name": "PurchaseThresholdExceeded",
-      "msg": "Purchase threshold exceeded"
     },
     {
-      "code": 6004,
-      "name": "InvalidSwitchboardAccount",
-      "msg": "The switchboard feed account is invalid"
-    }
-  ]
-};
-
-export const IDL: BuyBurnSwitchboard = {
-  "version": "0.1.0",
-  "name": "buy_burn_switchboard",
-  "instructions": [
-    {
-      "name": "registerState",
-      "accounts": [
-        {
-          "name": "payer",
-          "isMut": true,
-          "isSigner": true
-        },
-        {
-          "name": "state",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "mint",
-          "isMut": false,
-          "isSigner": false
-        },
-        {
-          "name": "systemProgram",
-          "isMut": false,
-          "isSigner": false
-        }
-      ],
-      "args": [
-        {
-          "name": "state",
-          "type": {
-            "defined": "GenericStateInput"
+      "name": "retirementBackend",
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "burn"
+          },
+          {
+            "name": "vault"
           }
-        }
-      ]
+        ]
+      }
     },
     {
-      "name": "updateState",
-      "accounts": [
-        {
-          "name": "payer",
-          "isMut": true,
-          "isSigner": true
-        },
-        {
-          "name": "state",
-          "isMut": true,
-          "isSigner": false
-        }
+      "name": "retirementReceipt",
+      "docs": [
+        "A record of tokens retired into the retirement vault, one per retirement"
       ],
-      "args": [
-        {
-          "name": "state",
-          "type": {
-            "defined": "GenericStateInput"
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "state",
+            "type": "pubkey"
+          },
+          {
+            "name": "mint",
+            "type": "pubkey"
+          },
+          {
+            "name": "beneficiary",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          },
+          {
+            "name": "index",
+            "type": "u64"
           }
-        }
-      ]
-    },
-    {
-      "name": "allocateYield",
-      "accounts": [
-        {
-          "name": "payer",
-          "isMut": true,
-          "isSigner": true
-        },
-        {
-          "name": "state",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "yieldAccount",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "mint",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "treasury",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "holdingAccount",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "holdingTokenAccount",
-          "isMut": true,
-          "isSigner": false
-        },
-        {
-          "name": "solUsdPriceFeed",
-          "isMut": false,
-          "isSigner": false
-        },
-        {
-          "name": "nctUsdPriceFeed",
-          "isMut": false,
-          "isSigner": false
-        },
-        {
-          "name": "tokenProgram",
-          "isMut": false,
-          "isSigner": false
-        },
-        {
-          "name": "systemProgram",
-          "isMut": false,
-          "isSigner": false
-        }
-      ],
-      "args": []
+        ]
+      }
     },
-    {
-      "name": "setTotalTokensPurchased",
-      "accounts": [
-        {
-          "name": "payer",
-          "isMut": true,
-          "isSigner": true
-        },
-        {
-          "name": "state",
-          "isMut": true,
-          "isSigner": false
-        }
-      ],
-      "args": [
-        {
-          "name": "value",
-          "type": "u64"
-        }
-      ]
-    }
-  ],
-  "accounts": [
     {
       "name": "state",
       "type": {
@@ -445,31 +1030,31 @@ export const IDL: BuyBurnSwitchboard = {
         "fields": [
           {
             "name": "updateAuthority",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "treasury",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "mint",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "solUsdPriceFeed",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "nctUsdPriceFeed",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "holdingAccount",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "holdingTokenAccount",
-            "type": "publicKey"
+            "type": "pubkey"
           },
           {
             "name": "feedStalenessThreshold",
@@ -498,94 +1083,85 @@ export const IDL: BuyBurnSwitchboard = {
           {
             "name": "yieldAccountBump",
             "type": "u8"
-          }
-        ]
-      }
-    }
-  ],
-  "types": [
-    {
-      "name": "GenericStateInput",
-      "type": {
-        "kind": "struct",
-        "fields": [
+          },
           {
-            "name": "mint",
-            "type": "publicKey"
+            "name": "swapProgram",
+            "type": "pubkey"
           },
           {
-            "name": "updateAuthority",
-            "type": "publicKey"
+            "name": "swapPool",
+            "type": "pubkey"
           },
           {
-            "name": "treasury",
-            "type": "publicKey"
+            "name": "retirementBackend",
+            "type": {
+              "defined": {
+                "name": "retirementBackend"
+              }
+            }
           },
           {
-            "name": "holdingAccount",
-            "type": "publicKey"
+            "name": "retirementVault",
+            "type": "pubkey"
           },
           {
-            "name": "holdingTokenAccount",
-            "type": "publicKey"
+            "name": "retirementBeneficiary",
+            "type": "pubkey"
           },
           {
-            "name": "solUsdPriceFeed",
-            "type": "publicKey"
+            "name": "totalRetirements",
+            "type": "u64"
           },
           {
-            "name": "nctUsdPriceFeed",
-            "type": "publicKey"
+            "name": "receiptMerkleTree",
+            "type": "pubkey"
           },
           {
-            "name": "feedStalenessThreshold",
-            "type": "u64"
+            "name": "receiptVault",
+            "type": "pubkey"
           },
           {
-            "name": "purchaseThreshold",
-            "type": "u64"
+            "name": "maxSlippageBps",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "totalTokensPurchasedAdjusted",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "state",
+            "type": "pubkey"
           },
           {
-            "name": "purchaseProportion",
-            "type": "f32"
+            "name": "delta",
+            "type": "i64"
           },
           {
-            "name": "index",
-            "type": "u8"
+            "name": "reason",
+            "type": {
+              "defined": {
+                "name": "adjustmentReason"
+              }
+            }
           },
           {
-            "name": "yieldAccountBump",
-            "type": "u8"
+            "name": "signer",
+            "type": "pubkey"
+          },
+          {
+            "name": "previousTotal",
+            "type": "u64"
+          },
+          {
+            "name": "newTotal",
+            "type": "u64"
           }
         ]
       }
     }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "InsufficientFundsForTransaction",
-      "msg": "Insufficient funds for transaction"
-    },
-    {
-      "code": 6001,
-      "name": "InvalidTreasury",
-      "msg": "Invalid treasury account"
-    },
-    {
-      "code": 6002,
-      "name": "InvalidMint",
-      "msg": "Invalid mint"
-    },
-    {
-      "code": 6003,
-      "name": "PurchaseThresholdExceeded",
-      "msg": "Purchase threshold exceeded"
-    },
-    {
-      "code": 6004,
-      "name": "InvalidSwitchboardAccount",
-      "msg": "The switchboard feed account is invalid"
-    }
   ]
 };
@@ -14,16 +14,26 @@ export type FundSender = {
   },
   "instructions": [
     {
-      "name": "registerState",
+      "name": "migrateDestination",
+      "docs": [
+        "Rename a destination: `update_state` cannot change `destination_name` as it seeds the state.",
+        "The configuration and totals move to a state under the new name, along with the SOL",
+        "in the old input account and any token balances passed as remaining accounts.",
+        "The old state is closed and its PDAs can no longer sign, so before migrating the caller must",
+        "pass every token account of the old input account, including that of the state's token,",
+        "which are emptied into the new input account's and closed.",
+        "States which have stored or locked certificates cannot be migrated,",
+        "as their certificate vault and records are derived from the state."
+      ],
       "discriminator": [
-        137,
-        35,
-        194,
-        234,
-        128,
-        215,
-        19,
-        45
+        220,
+        192,
+        228,
+        109,
+        229,
+        46,
+        147,
+        41
       ],
       "accounts": [
         {
@@ -32,7 +42,47 @@ export type FundSender = {
           "signer": true
         },
         {
-          "name": "state",
+          "name": "oldState",
+          "writable": true
+        },
+        {
+          "name": "oldInputAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "oldState"
+              }
+            ]
+          }
+        },
+        {
+          "name": "oldTokenMintAccount",
+          "docs": [
+            "Its balance must be moved with the remaining accounts."
+          ]
+        },
+        {
+          "name": "newState",
           "writable": true,
           "pda": {
             "seeds": [
@@ -48,17 +98,19 @@ export type FundSender = {
               },
               {
                 "kind": "arg",
-                "path": "state_in.destination_name"
+                "path": "newDestinationName"
               },
               {
-                "kind": "arg",
-                "path": "sunriseState"
+                "kind": "account",
+                "path": "old_state.sunrise_state",
+                "account": "state"
               }
             ]
           }
         },
         {
-          "name": "inputAccount",
+          "name": "newInputAccount",
+          "writable": true,
           "pda": {
             "seeds": [
               {
@@ -81,7 +133,7 @@ export type FundSender = {
               },
               {
                 "kind": "account",
-                "path": "state"
+                "path": "newState"
               }
             ]
           }
@@ -89,69 +141,45 @@ export type FundSender = {
         {
           "name": "systemProgram",
           "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
         }
       ],
       "args": [
         {
-          "name": "sunriseState",
-          "type": "pubkey"
-        },
-        {
-          "name": "stateIn",
-          "type": {
-            "defined": {
-              "name": "genericStateInput"
-            }
-          }
+          "name": "newDestinationName",
+          "type": "string"
         }
       ]
     },
     {
-      "name": "sendFromState",
+      "name": "migrateState",
+      "docs": [
+        "Upgrade a state registered with the original layout, which cannot be loaded (or updated)",
+        "until it is. The state keeps its address, so its input account and funds are unchanged.",
+        "Settings added since then are left disabled, to be set with `update_state`."
+      ],
       "discriminator": [
-        119,
-        32,
-        214,
-        129,
-        74,
-        57,
-        186,
-        185
+        34,
+        189,
+        226,
+        222,
+        218,
+        156,
+        19,
+        213
       ],
       "accounts": [
         {
-          "name": "state",
-          "writable": true
+          "name": "payer",
+          "writable": true,
+          "signer": true
         },
         {
-          "name": "inputAccount",
-          "writable": true,
-          "pda": {
-            "seeds": [
-              {
-                "kind": "const",
-                "value": [
-                  105,
-                  110,
-                  112,
-                  117,
-                  116,
-                  95,
-                  97,
-                  99,
-                  99,
-                  111,
-                  117,
-                  110,
-                  116
-                ]
-              },
-              {
-                "kind": "account",
-                "path": "state"
-              }
-            ]
-          }
+          "name": "state",
+          "writable": true
         },
         {
           "name": "systemProgram",
@@ -161,16 +189,16 @@ export type FundSender = {
       "args": []
     },
     {
-      "name": "sendFund",
+      "name": "registerState",
       "discriminator": [
-        92,
-        75,
-        127,
-        184,
-        67,
-        186,
-        195,
-        140
+        137,
+        35,
+        194,
+        234,
+        128,
+        215,
+        19,
+        45
       ],
       "accounts": [
         {
@@ -179,11 +207,33 @@ export type FundSender = {
           "signer": true
         },
         {
-          "name": "state"
+          "name": "state",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  116,
+                  101
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "state_in.destination_name"
+              },
+              {
+                "kind": "arg",
+                "path": "sunriseState"
+              }
+            ]
+          }
         },
         {
           "name": "inputAccount",
-          "writable": true,
           "pda": {
             "seeds": [
               {
@@ -211,10 +261,6 @@ export type FundSender = {
             ]
           }
         },
-        {
-          "name": "destinationAccount",
-          "writable": true
-        },
         {
           "name": "systemProgram",
           "address": "11111111111111111111111111111111"
@@ -222,22 +268,35 @@ export type FundSender = {
       ],
       "args": [
         {
-          "name": "amount",
-          "type": "u64"
+          "name": "sunriseState",
+          "type": "pubkey"
+        },
+        {
+          "name": "stateIn",
+          "type": {
+            "defined": {
+              "name": "genericStateInput"
+            }
+          }
         }
       ]
     },
     {
-      "name": "storeCertificates",
+      "name": "releaseCertificate",
+      "docs": [
+        "Release certificate tokens from the state's program-owned certificate vault to a beneficiary,",
+        "attributing their ledger record (if passed) to the beneficiary.",
+        "Compressed and Core certificates in the vault cannot be released."
+      ],
       "discriminator": [
-        106,
-        243,
-        105,
-        136,
-        61,
-        36,
-        94,
-        218
+        169,
+        5,
+        138,
+        118,
+        63,
+        73,
+        156,
+        110
       ],
       "accounts": [
         {
@@ -249,24 +308,28 @@ export type FundSender = {
           "name": "state"
         },
         {
-          "name": "inputAccount",
+          "name": "certificateVault",
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
+                  99,
+                  101,
+                  114,
+                  116,
                   105,
-                  110,
-                  112,
-                  117,
+                  102,
+                  105,
+                  99,
+                  97,
                   116,
+                  101,
                   95,
+                  118,
                   97,
-                  99,
-                  99,
-                  111,
                   117,
-                  110,
+                  108,
                   116
                 ]
               },
@@ -280,16 +343,6 @@ export type FundSender = {
         {
           "name": "certificateMint"
         },
-        {
-          "name": "inputTokenAccount",
-          "docs": [
-            "A token account owned by the input_account"
-          ],
-          "writable": true
-        },
-        {
-          "name": "certificateVault"
-        },
         {
           "name": "certificateVaultAta",
           "writable": true,
@@ -300,41 +353,8 @@ export type FundSender = {
                 "path": "certificateVault"
               },
               {
-                "kind": "const",
-                "value": [
-                  6,
-                  221,
-                  246,
-                  225,
-                  215,
-                  101,
-                  161,
-                  147,
-                  217,
-                  203,
-                  225,
-                  70,
-                  206,
-                  235,
-                  121,
-                  172,
-                  28,
-                  180,
-                  133,
-                  237,
-                  95,
-                  91,
-                  55,
-                  145,
-                  58,
-                  140,
-                  245,
-                  133,
-                  126,
-                  255,
-                  0,
-                  169
-                ]
+                "kind": "account",
+                "path": "tokenProgram"
               },
               {
                 "kind": "account",
@@ -381,31 +401,103 @@ export type FundSender = {
           }
         },
         {
-          "name": "systemProgram",
-          "address": "11111111111111111111111111111111"
+          "name": "beneficiary"
         },
         {
-          "name": "tokenProgram",
-          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+          "name": "certificateRecord",
+          "docs": [
+            "The ledger record of the released certificate, attributed to the beneficiary"
+          ],
+          "writable": true,
+          "optional": true
         },
         {
-          "name": "associatedTokenProgram",
-          "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
-        }
-      ],
-      "args": []
-    },
+          "name": "beneficiaryAta",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "beneficiary"
+              },
+              {
+                "kind": "account",
+                "path": "tokenProgram"
+              },
+              {
+                "kind": "account",
+                "path": "certificateMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "associatedTokenProgram",
+          "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
     {
-      "name": "storeCnftCertificate",
+      "name": "releaseCnftCertificate",
       "discriminator": [
-        214,
-        223,
-        183,
-        200,
-        167,
-        76,
-        170,
-        252
+        182,
+        255,
+        7,
+        85,
+        229,
+        181,
+        64,
+        69
       ],
       "accounts": [
         {
@@ -417,24 +509,28 @@ export type FundSender = {
           "name": "state"
         },
         {
-          "name": "inputAccount",
+          "name": "certificateVault",
           "pda": {
             "seeds": [
               {
                 "kind": "const",
                 "value": [
+                  99,
+                  101,
+                  114,
+                  116,
                   105,
-                  110,
-                  112,
-                  117,
+                  102,
+                  105,
+                  99,
+                  97,
                   116,
+                  101,
                   95,
+                  118,
                   97,
-                  99,
-                  99,
-                  111,
                   117,
-                  110,
+                  108,
                   116
                 ]
               },
@@ -446,7 +542,16 @@ export type FundSender = {
           }
         },
         {
-          "name": "certificateVault"
+          "name": "beneficiary"
+        },
+        {
+          "name": "certificateRecord",
+          "docs": [
+            "The ledger record of the released certificate, attributed to the beneficiary.",
+            "Its certificate is checked against the asset id in the instruction"
+          ],
+          "writable": true,
+          "optional": true
         },
         {
           "name": "treeAuthority",
@@ -530,16 +635,16 @@ export type FundSender = {
       ]
     },
     {
-      "name": "updateState",
+      "name": "releaseCoreCertificate",
       "discriminator": [
-        135,
-        112,
-        215,
-        75,
-        247,
-        185,
-        53,
-        176
+        105,
+        60,
+        202,
+        8,
+        129,
+        237,
+        110,
+        111
       ],
       "accounts": [
         {
@@ -548,79 +653,1994 @@ export type FundSender = {
           "signer": true
         },
         {
-          "name": "state",
+          "name": "state"
+        },
+        {
+          "name": "certificateVault",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  101,
+                  114,
+                  116,
+                  105,
+                  102,
+                  105,
+                  99,
+                  97,
+                  116,
+                  101,
+                  95,
+                  118,
+                  97,
+                  117,
+                  108,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "certificateAsset",
           "writable": true
         },
+        {
+          "name": "certificateCollection",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "beneficiary"
+        },
+        {
+          "name": "certificateRecord",
+          "docs": [
+            "The ledger record of the released certificate, attributed to the beneficiary"
+          ],
+          "writable": true,
+          "optional": true
+        },
         {
           "name": "systemProgram",
           "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "coreProgram",
+          "address": "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
         }
       ],
-      "args": [
+      "args": []
+    },
+    {
+      "name": "sendAllFunds",
+      "docs": [
+        "Send the whole spendable balance of the input account to the destination,",
+        "so that cranks need not query the balance first."
+      ],
+      "discriminator": [
+        4,
+        66,
+        60,
+        113,
+        24,
+        55,
+        70,
+        232
+      ],
+      "accounts": [
         {
-          "name": "stateIn",
-          "type": {
-            "defined": {
-              "name": "genericStateInput"
-            }
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
           }
+        },
+        {
+          "name": "destinationAccount",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
         }
-      ]
-    }
-  ],
-  "accounts": [
-    {
-      "name": "state",
-      "discriminator": [
-        216,
-        146,
-        107,
-        94,
-        104,
-        75,
-        182,
-        177
-      ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "insufficientFundsForTransaction",
-      "msg": "Insufficient funds for transaction"
-    },
-    {
-      "code": 6001,
-      "name": "incorrectOutputYieldAccount",
-      "msg": "Incorrect output yield account"
-    },
-    {
-      "code": 6002,
-      "name": "incorrectTokenAccountOwner",
-      "msg": "Token account not owned by output yield account"
-    },
-    {
-      "code": 6003,
-      "name": "incorrectDestinationAccount",
-      "msg": "Incorrect destination account"
+      ],
+      "args": []
     },
     {
-      "code": 6004,
-      "name": "incorrectHoldAccount",
-      "msg": "Incorrect hold account"
+      "name": "sendFromState",
+      "discriminator": [
+        119,
+        32,
+        214,
+        129,
+        74,
+        57,
+        186,
+        185
+      ],
+      "accounts": [
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "sendFund",
+      "discriminator": [
+        92,
+        75,
+        127,
+        184,
+        67,
+        186,
+        195,
+        140
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "destinationAccount",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "sendTokenFund",
+      "docs": [
+        "Forward the whole balance of the state's token in the input account's ATA",
+        "to the destination account's ATA. Weighted destinations apply to SOL only."
+      ],
+      "discriminator": [
+        196,
+        176,
+        105,
+        187,
+        126,
+        167,
+        250,
+        130
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "tokenMint"
+        },
+        {
+          "name": "inputTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "inputAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  6,
+                  221,
+                  246,
+                  225,
+                  215,
+                  101,
+                  161,
+                  147,
+                  217,
+                  203,
+                  225,
+                  70,
+                  206,
+                  235,
+                  121,
+                  172,
+                  28,
+                  180,
+                  133,
+                  237,
+                  95,
+                  91,
+                  55,
+                  145,
+                  58,
+                  140,
+                  245,
+                  133,
+                  126,
+                  255,
+                  0,
+                  169
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "tokenMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "destinationAccount"
+        },
+        {
+          "name": "destinationTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "destinationAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  6,
+                  221,
+                  246,
+                  225,
+                  215,
+                  101,
+                  161,
+                  147,
+                  217,
+                  203,
+                  225,
+                  70,
+                  206,
+                  235,
+                  121,
+                  172,
+                  28,
+                  180,
+                  133,
+                  237,
+                  95,
+                  91,
+                  55,
+                  145,
+                  58,
+                  140,
+                  245,
+                  133,
+                  126,
+                  255,
+                  0,
+                  169
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "tokenMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "associatedTokenProgram",
+          "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "storeCertificates",
+      "discriminator": [
+        106,
+        243,
+        105,
+        136,
+        61,
+        36,
+        94,
+        218
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "certificateMint"
+        },
+        {
+          "name": "inputTokenAccount",
+          "docs": [
+            "A token account owned by the input_account"
+          ],
+          "writable": true
+        },
+        {
+          "name": "certificateVault",
+          "docs": [
+            "for certificates not matching the state's filter (checked in the instruction)"
+          ]
+        },
+        {
+          "name": "beneficiary",
+          "docs": [
+            "if not the state's certificate beneficiary. Only the update authority can name one"
+          ],
+          "optional": true
+        },
+        {
+          "name": "certificateVaultAta",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "certificateVault"
+              },
+              {
+                "kind": "account",
+                "path": "tokenProgram"
+              },
+              {
+                "kind": "account",
+                "path": "certificateMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "certificateMetadata",
+          "optional": true
+        },
+        {
+          "name": "certificateRecord",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  101,
+                  114,
+                  116,
+                  105,
+                  102,
+                  105,
+                  99,
+                  97,
+                  116,
+                  101,
+                  95,
+                  114,
+                  101,
+                  99,
+                  111,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_certificates",
+                "account": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "associatedTokenProgram",
+          "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "storeCnftCertificate",
+      "discriminator": [
+        214,
+        223,
+        183,
+        200,
+        167,
+        76,
+        170,
+        252
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "leafDelegate",
+          "docs": [
+            "The bubblegum program checks it against the leaf - we don't need to do so here"
+          ],
+          "optional": true
+        },
+        {
+          "name": "certificateVault",
+          "docs": [
+            "for certificates not matching the state's filter (checked in the instruction)"
+          ]
+        },
+        {
+          "name": "beneficiary",
+          "docs": [
+            "if not the state's certificate beneficiary. Only the update authority can name one"
+          ],
+          "optional": true
+        },
+        {
+          "name": "certificateRecord",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  101,
+                  114,
+                  116,
+                  105,
+                  102,
+                  105,
+                  99,
+                  97,
+                  116,
+                  101,
+                  95,
+                  114,
+                  101,
+                  99,
+                  111,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_certificates",
+                "account": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "treeAuthority",
+          "docs": [
+            "The bubblegum program checks its type - we don't need to do so here"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "merkleTree"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "bubblegumProgram"
+            }
+          }
+        },
+        {
+          "name": "merkleTree",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "logWrapper",
+          "docs": [
+            "via CPI, to workaround the CPI size limit on Solana.",
+            "The bubblegum program checks its type - we don't need to do so here",
+            "While SplAccountCompression is using an older version of Anchor, we cannot get its ID here"
+          ]
+        },
+        {
+          "name": "compressionProgram",
+          "address": "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+        },
+        {
+          "name": "bubblegumProgram",
+          "address": "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY"
+        }
+      ],
+      "args": [
+        {
+          "name": "root",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          }
+        },
+        {
+          "name": "dataHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          }
+        },
+        {
+          "name": "creatorHash",
+          "type": {
+            "array": [
+              "u8",
+              32
+            ]
+          }
+        },
+        {
+          "name": "nonce",
+          "type": "u64"
+        },
+        {
+          "name": "index",
+          "type": "u32"
+        },
+        {
+          "name": "creators",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "certificateCreator"
+              }
+            }
+          }
+        },
+        {
+          "name": "metadata",
+          "type": {
+            "option": {
+              "defined": {
+                "name": "certificateMetadata"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "storeCnftCertificates",
+      "discriminator": [
+        255,
+        22,
+        123,
+        240,
+        228,
+        127,
+        77,
+        58
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "leafDelegate",
+          "docs": [
+            "The bubblegum program checks it against the leaf - we don't need to do so here"
+          ],
+          "optional": true
+        },
+        {
+          "name": "certificateVault",
+          "docs": [
+            "for certificates not matching the state's filter (checked in the instruction)"
+          ]
+        },
+        {
+          "name": "beneficiary",
+          "docs": [
+            "if not the state's certificate beneficiary. Only the update authority can name one"
+          ],
+          "optional": true
+        },
+        {
+          "name": "treeAuthority",
+          "docs": [
+            "The bubblegum program checks its type - we don't need to do so here"
+          ],
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "merkleTree"
+              }
+            ],
+            "program": {
+              "kind": "account",
+              "path": "bubblegumProgram"
+            }
+          }
+        },
+        {
+          "name": "merkleTree",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "logWrapper",
+          "docs": [
+            "via CPI, to workaround the CPI size limit on Solana.",
+            "The bubblegum program checks its type - we don't need to do so here",
+            "While SplAccountCompression is using an older version of Anchor, we cannot get its ID here"
+          ]
+        },
+        {
+          "name": "compressionProgram",
+          "address": "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+        },
+        {
+          "name": "bubblegumProgram",
+          "address": "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY"
+        }
+      ],
+      "args": [
+        {
+          "name": "certificates",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "cnftCertificate"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "storeCoreCertificate",
+      "discriminator": [
+        58,
+        11,
+        131,
+        197,
+        0,
+        48,
+        196,
+        114
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "certificateAsset",
+          "writable": true
+        },
+        {
+          "name": "certificateCollection",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "certificateVault",
+          "docs": [
+            "for certificates not matching the state's filter (checked in the instruction)"
+          ]
+        },
+        {
+          "name": "beneficiary",
+          "docs": [
+            "if not the state's certificate beneficiary. Only the update authority can name one"
+          ],
+          "optional": true
+        },
+        {
+          "name": "certificateRecord",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  99,
+                  101,
+                  114,
+                  116,
+                  105,
+                  102,
+                  105,
+                  99,
+                  97,
+                  116,
+                  101,
+                  95,
+                  114,
+                  101,
+                  99,
+                  111,
+                  114,
+                  100
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              },
+              {
+                "kind": "account",
+                "path": "state.total_certificates",
+                "account": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "coreProgram",
+          "address": "CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "swapAndSend",
+      "docs": [
+        "Wrap `amount` lamports from the input account and swap them to the state's token",
+        "through the configured pool, delivering the output to the destination account's ATA.",
+        "Only the update authority can swap, as it chooses the amount and the minimum output."
+      ],
+      "discriminator": [
+        51,
+        196,
+        102,
+        91,
+        16,
+        33,
+        217,
+        16
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "inputAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
+                "value": [
+                  105,
+                  110,
+                  112,
+                  117,
+                  116,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "state"
+              }
+            ]
+          }
+        },
+        {
+          "name": "nativeMint",
+          "address": "So11111111111111111111111111111111111111112"
+        },
+        {
+          "name": "wrappedSolAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "inputAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  6,
+                  221,
+                  246,
+                  225,
+                  215,
+                  101,
+                  161,
+                  147,
+                  217,
+                  203,
+                  225,
+                  70,
+                  206,
+                  235,
+                  121,
+                  172,
+                  28,
+                  180,
+                  133,
+                  237,
+                  95,
+                  91,
+                  55,
+                  145,
+                  58,
+                  140,
+                  245,
+                  133,
+                  126,
+                  255,
+                  0,
+                  169
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "nativeMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "tokenMint"
+        },
+        {
+          "name": "destinationAccount"
+        },
+        {
+          "name": "destinationTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "destinationAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  6,
+                  221,
+                  246,
+                  225,
+                  215,
+                  101,
+                  161,
+                  147,
+                  217,
+                  203,
+                  225,
+                  70,
+                  206,
+                  235,
+                  121,
+                  172,
+                  28,
+                  180,
+                  133,
+                  237,
+                  95,
+                  91,
+                  55,
+                  145,
+                  58,
+                  140,
+                  245,
+                  133,
+                  126,
+                  255,
+                  0,
+                  169
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "tokenMint"
+              }
+            ],
+            "program": {
+              "kind": "const",
+              "value": [
+                140,
+                151,
+                37,
+                143,
+                78,
+                36,
+                137,
+                241,
+                187,
+                61,
+                16,
+                41,
+                20,
+                142,
+                13,
+                131,
+                11,
+                90,
+                19,
+                153,
+                218,
+                255,
+                16,
+                132,
+                4,
+                142,
+                123,
+                216,
+                219,
+                233,
+                248,
+                89
+              ]
+            }
+          }
+        },
+        {
+          "name": "swapProgram"
+        },
+        {
+          "name": "swapPool"
+        },
+        {
+          "name": "swapPoolAuthority"
+        },
+        {
+          "name": "swapPoolSource",
+          "writable": true
+        },
+        {
+          "name": "swapPoolDestination",
+          "writable": true
+        },
+        {
+          "name": "swapPoolMint",
+          "writable": true
+        },
+        {
+          "name": "swapPoolFeeAccount",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        },
+        {
+          "name": "tokenProgram",
+          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        },
+        {
+          "name": "associatedTokenProgram",
+          "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        },
+        {
+          "name": "minimumAmountOut",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "updateState",
+      "discriminator": [
+        135,
+        112,
+        215,
+        75,
+        247,
+        185,
+        53,
+        176
+      ],
+      "accounts": [
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "state",
+          "writable": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "stateIn",
+          "type": {
+            "defined": {
+              "name": "genericStateInput"
+            }
+          }
+        }
+      ]
+    }
+  ],
+  "accounts": [
+    {
+      "name": "certificateRecord",
+      "discriminator": [
+        250,
+        87,
+        4,
+        127,
+        86,
+        3,
+        52,
+        240
+      ]
+    },
+    {
+      "name": "state",
+      "discriminator": [
+        216,
+        146,
+        107,
+        94,
+        104,
+        75,
+        182,
+        177
+      ]
+    }
+  ],
+  "events": [
+    {
+      "name": "certificateReleased",
+      "discriminator": [
+        71,
+        192,
+        117,
+        171,
+        136,
+        6,
+        99,
+        46
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "insufficientFundsForTransaction",
+      "msg": "Insufficient funds for transaction"
+    },
+    {
+      "code": 6001,
+      "name": "incorrectOutputYieldAccount",
+      "msg": "Incorrect output yield account"
+    },
+    {
+      "code": 6002,
+      "name": "incorrectTokenAccountOwner",
+      "msg": "Token account not owned by output yield account"
+    },
+    {
+      "code": 6003,
+      "name": "incorrectDestinationAccount",
+      "msg": "Incorrect destination account"
+    },
+    {
+      "code": 6004,
+      "name": "incorrectHoldAccount",
+      "msg": "Incorrect hold account"
+    },
+    {
+      "code": 6005,
+      "name": "unauthorized",
+      "msg": "Incorrect update authority"
+    },
+    {
+      "code": 6006,
+      "name": "noCertificatesFound",
+      "msg": "No certificates found"
+    },
+    {
+      "code": 6007,
+      "name": "invalidProportions",
+      "msg": "Invalid destination proportions"
+    },
+    {
+      "code": 6008,
+      "name": "tooManyDestinations",
+      "msg": "Too many destinations"
+    },
+    {
+      "code": 6009,
+      "name": "incorrectTokenMint",
+      "msg": "Incorrect token mint"
+    },
+    {
+      "code": 6010,
+      "name": "incorrectSwapPool",
+      "msg": "Incorrect swap program or pool"
+    },
+    {
+      "code": 6011,
+      "name": "slippageExceeded",
+      "msg": "Swap returned less than the minimum amount out"
+    },
+    {
+      "code": 6012,
+      "name": "incorrectTokenAccountPair",
+      "msg": "Token accounts must be passed as pairs of the same mint"
+    },
+    {
+      "code": 6013,
+      "name": "invalidCertificate",
+      "msg": "Certificate does not match the allowed collection, creator or mints"
+    },
+    {
+      "code": 6014,
+      "name": "tooManyAllowedCertificateMints",
+      "msg": "Too many allowed certificate mints"
+    },
+    {
+      "code": 6015,
+      "name": "incorrectCertificateMetadata",
+      "msg": "Incorrect certificate metadata account"
+    },
+    {
+      "code": 6016,
+      "name": "creatorHashMismatch",
+      "msg": "Certificate creators do not match the creator hash"
+    },
+    {
+      "code": 6017,
+      "name": "incorrectCertificateRecord",
+      "msg": "Incorrect certificate record account"
+    },
+    {
+      "code": 6018,
+      "name": "insufficientProofAccounts",
+      "msg": "Not enough proof accounts for the certificates"
+    },
+    {
+      "code": 6019,
+      "name": "tooManyCertificates",
+      "msg": "Too many certificates in one batch"
+    },
+    {
+      "code": 6020,
+      "name": "invalidCoreAsset",
+      "msg": "Invalid Metaplex Core asset"
+    },
+    {
+      "code": 6021,
+      "name": "incorrectCoreCollection",
+      "msg": "Incorrect Metaplex Core collection account"
+    },
+    {
+      "code": 6022,
+      "name": "invalidCertificateMetadata",
+      "msg": "Invalid certificate metadata"
+    },
+    {
+      "code": 6023,
+      "name": "dataHashMismatch",
+      "msg": "Certificate metadata does not match the data hash"
+    },
+    {
+      "code": 6024,
+      "name": "certificatesLocked",
+      "msg": "Certificates are locked in the state's certificate vault"
+    },
+    {
+      "code": 6025,
+      "name": "certificatesNotLocked",
+      "msg": "Certificates are not locked in the state's certificate vault"
+    },
+    {
+      "code": 6026,
+      "name": "incorrectCertificateVault",
+      "msg": "Incorrect certificate vault"
+    },
+    {
+      "code": 6027,
+      "name": "invalidLegacyState",
+      "msg": "Account is not a fund sender state in the legacy layout"
+    },
+    {
+      "code": 6028,
+      "name": "invalidCertificateMint",
+      "msg": "The state's token and wrapped SOL cannot be stored as certificates"
+    },
+    {
+      "code": 6029,
+      "name": "certificatesStored",
+      "msg": "Certificates have been stored or retired by the state"
+    },
+    {
+      "code": 6030,
+      "name": "tokensNotMigrated",
+      "msg": "The old input account's balance of the state's token was not moved"
+    }
+  ],
+  "types": [
+    {
+      "name": "cnftCertificate",
+      "docs": [
+        "A compressed certificate leaf, as returned by the DAS API"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "root",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "dataHash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "creatorHash",
+            "type": {
+              "array": [
+                "u8",
+                32
+              ]
+            }
+          },
+          {
+            "name": "nonce",
+            "type": "u64"
+          },
+          {
+            "name": "index",
+            "type": "u32"
+          },
+          {
+            "name": "creators",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "certificateCreator"
+                }
+              }
+            }
+          },
+          {
+            "name": "metadata",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "certificateMetadata"
+                }
+              }
+            }
+          },
+          {
+            "name": "proofLen",
+            "type": "u8"
+          }
+        ]
+      }
     },
     {
-      "code": 6005,
-      "name": "unauthorized",
-      "msg": "Incorrect update authority"
+      "name": "certificateCollection",
+      "docs": [
+        "The collection of a compressed certificate, as in its leaf metadata"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "verified",
+            "type": "bool"
+          },
+          {
+            "name": "key",
+            "type": "pubkey"
+          }
+        ]
+      }
     },
     {
-      "code": 6006,
-      "name": "noCertificatesFound",
-      "msg": "No certificates found"
-    }
-  ],
-  "types": [
+      "name": "certificateCreator",
+      "docs": [
+        "A creator of a compressed certificate, as hashed into the leaf's creator hash"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "address",
+            "type": "pubkey"
+          },
+          {
+            "name": "verified",
+            "type": "bool"
+          },
+          {
+            "name": "share",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "certificateMetadata",
+      "docs": [
+        "The metadata of a compressed certificate, with the same layout as Bubblegum's `MetadataArgs`",
+        "(enums are passed as their variant index), so that it can be checked against the leaf's data hash"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "name",
+            "type": "string"
+          },
+          {
+            "name": "symbol",
+            "type": "string"
+          },
+          {
+            "name": "uri",
+            "type": "string"
+          },
+          {
+            "name": "sellerFeeBasisPoints",
+            "type": "u16"
+          },
+          {
+            "name": "primarySaleHappened",
+            "type": "bool"
+          },
+          {
+            "name": "isMutable",
+            "type": "bool"
+          },
+          {
+            "name": "editionNonce",
+            "type": {
+              "option": "u8"
+            }
+          },
+          {
+            "name": "tokenStandard",
+            "type": {
+              "option": "u8"
+            }
+          },
+          {
+            "name": "collection",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "certificateCollection"
+                }
+              }
+            }
+          },
+          {
+            "name": "uses",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "certificateUses"
+                }
+              }
+            }
+          },
+          {
+            "name": "tokenProgramVersion",
+            "type": "u8"
+          },
+          {
+            "name": "creators",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "certificateCreator"
+                }
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "certificateRecord",
+      "docs": [
+        "A record of certificates stored by a state, one per store instruction,",
+        "linking them to the total funds sent by the state at that point"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "state",
+            "type": "pubkey"
+          },
+          {
+            "name": "certificate",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          },
+          {
+            "name": "totalSpent",
+            "type": "u64"
+          },
+          {
+            "name": "index",
+            "type": "u64"
+          },
+          {
+            "name": "quarantined",
+            "type": "bool"
+          },
+          {
+            "name": "beneficiary",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "certificateReleased",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "state",
+            "type": "pubkey"
+          },
+          {
+            "name": "certificate",
+            "type": "pubkey"
+          },
+          {
+            "name": "beneficiary",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "certificateUses",
+      "docs": [
+        "The uses of a compressed certificate, as in its leaf metadata"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "useMethod",
+            "type": "u8"
+          },
+          {
+            "name": "remaining",
+            "type": "u64"
+          },
+          {
+            "name": "total",
+            "type": "u64"
+          }
+        ]
+      }
+    },
     {
       "name": "genericStateInput",
       "type": {
@@ -645,6 +2665,58 @@ export type FundSender = {
           {
             "name": "spendThreshold",
             "type": "u64"
+          },
+          {
+            "name": "destinationAccounts",
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "destinationProportions",
+            "type": "bytes"
+          },
+          {
+            "name": "tokenMint",
+            "type": "pubkey"
+          },
+          {
+            "name": "tokenSpendThreshold",
+            "type": "u64"
+          },
+          {
+            "name": "swapProgram",
+            "type": "pubkey"
+          },
+          {
+            "name": "swapPool",
+            "type": "pubkey"
+          },
+          {
+            "name": "certificateCollection",
+            "type": "pubkey"
+          },
+          {
+            "name": "certificateCreator",
+            "type": "pubkey"
+          },
+          {
+            "name": "allowedCertificateMints",
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "quarantineVault",
+            "type": "pubkey"
+          },
+          {
+            "name": "lockCertificates",
+            "type": "bool"
+          },
+          {
+            "name": "certificateBeneficiary",
+            "type": "pubkey"
           }
         ]
       }
@@ -685,6 +2757,70 @@ export type FundSender = {
           {
             "name": "inputAccountBump",
             "type": "u8"
+          },
+          {
+            "name": "destinationAccounts",
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "destinationProportions",
+            "type": "bytes"
+          },
+          {
+            "name": "tokenMint",
+            "type": "pubkey"
+          },
+          {
+            "name": "tokenSpendThreshold",
+            "type": "u64"
+          },
+          {
+            "name": "totalTokensSpent",
+            "type": "u64"
+          },
+          {
+            "name": "swapProgram",
+            "type": "pubkey"
+          },
+          {
+            "name": "swapPool",
+            "type": "pubkey"
+          },
+          {
+            "name": "certificateCollection",
+            "type": "pubkey"
+          },
+          {
+            "name": "certificateCreator",
+            "type": "pubkey"
+          },
+          {
+            "name": "allowedCertificateMints",
+            "type": {
+              "vec": "pubkey"
+            }
+          },
+          {
+            "name": "quarantineVault",
+            "type": "pubkey"
+          },
+          {
+            "name": "totalCertificates",
+            "type": "u64"
+          },
+          {
+            "name": "lockCertificates",
+            "type": "bool"
+          },
+          {
+            "name": "certificateVaultBump",
+            "type": "u8"
+          },
+          {
+            "name": "certificateBeneficiary",
+            "type": "pubkey"
           }
         ]
       }
@@ -1,10 +1,18 @@
 #![allow(clippy::result_large_err)]
-use crate::utils::errors::ErrorCode;
+use crate::utils::seeds::{STATE, YIELD_ACCOUNT};
 use crate::utils::state::*;
 use crate::utils::token::*;
 use anchor_lang::prelude::*;
+use yield_controller_common::swap::*;
 mod utils;
 
+// account and argument types, for off-chain clients
+pub use crate::utils::errors::ErrorCode;
+pub use crate::utils::state::{
+    AllocateYieldInput, GenericStateInput, LegacyState, MigrateStateInput, PriceRecord,
+    RetirementBackend, RetirementReceipt, State, SwapAndBurnInput, PRICE_CHANGE_WINDOW,
+};
+
 declare_id!("stcGmoLCBsr2KSu2vvcSuqMiEZx36F32ySUtCXjab5B");
 
 #[program]
@@ -18,10 +26,24 @@ pub mod buy_burn_fixed {
         yield_account.treasury = state.treasury;
         yield_account.purchase_threshold = state.purchase_threshold;
         yield_account.purchase_proportion = state.purchase_proportion;
-        yield_account.price = state.price;
         yield_account.holding_account = state.holding_account;
         yield_account.holding_token_account = state.holding_token_account;
+        yield_account.price_staleness_threshold = state.price_staleness_threshold;
+        yield_account.max_price_change_bps = state.max_price_change_bps;
+        yield_account.swap_program = state.swap_program;
+        yield_account.swap_pool = state.swap_pool;
+        yield_account.set_max_slippage_bps(state.max_slippage_bps)?;
+        yield_account.retirement_backend = state.retirement_backend;
+        yield_account.retirement_vault = state.retirement_vault;
+        yield_account.retirement_beneficiary = state.retirement_beneficiary;
+        yield_account.index = state.index;
         yield_account.bump = ctx.bumps.yield_account;
+        yield_account.yield_account_bump = Pubkey::find_program_address(
+            &[YIELD_ACCOUNT, yield_account.key().as_ref()],
+            ctx.program_id,
+        )
+        .1;
+        yield_account.set_price(state.price, &Clock::get()?)?;
         Ok(())
     }
 
@@ -34,13 +56,68 @@ pub mod buy_burn_fixed {
         yield_account.purchase_proportion = state.purchase_proportion;
         yield_account.holding_account = state.holding_account;
         yield_account.holding_token_account = state.holding_token_account;
-        yield_account.price = state.price;
+        // a price change is checked against the limit in place before this update
+        if state.price != yield_account.price {
+            yield_account.set_price(state.price, &Clock::get()?)?;
+        }
+        yield_account.price_staleness_threshold = state.price_staleness_threshold;
+        yield_account.max_price_change_bps = state.max_price_change_bps;
+        yield_account.swap_program = state.swap_program;
+        yield_account.swap_pool = state.swap_pool;
+        yield_account.set_max_slippage_bps(state.max_slippage_bps)?;
+        yield_account.retirement_backend = state.retirement_backend;
+        yield_account.retirement_vault = state.retirement_vault;
+        yield_account.retirement_beneficiary = state.retirement_beneficiary;
+        Ok(())
+    }
+
+    /// Move a state registered before states were indexed (seeded by the mint only)
+    /// to the indexed PDA, carrying over its configuration and total spent.
+    /// The legacy balance is moved to the new state's yield account.
+    /// The holding token account delegate must be re-approved to the new state afterwards.
+    pub fn migrate_state(ctx: Context<MigrateState>, args: MigrateStateInput) -> Result<()> {
+        let legacy_state_info = ctx.accounts.legacy_state.to_account_info();
+        let legacy_state = LegacyState::try_from_account_info(&legacy_state_info)?;
+        require_keys_eq!(
+            legacy_state.update_authority,
+            ctx.accounts.payer.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.state;
+        state.update_authority = legacy_state.update_authority;
+        state.treasury = legacy_state.treasury;
+        state.mint = legacy_state.mint;
+        state.purchase_threshold = legacy_state.purchase_threshold;
+        state.purchase_proportion = legacy_state.purchase_proportion;
+        state.holding_account = legacy_state.holding_account;
+        state.holding_token_account = legacy_state.holding_token_account;
+        state.total_spent = legacy_state.total_spent;
+        state.price_staleness_threshold = args.price_staleness_threshold;
+        state.max_price_change_bps = args.max_price_change_bps;
+        state.set_max_slippage_bps(args.max_slippage_bps)?;
+        state.index = args.index;
+        state.bump = ctx.bumps.state;
+        state.yield_account_bump = ctx.bumps.yield_account;
+        state.set_price(legacy_state.price, &Clock::get()?)?;
+
+        // close the legacy state, moving its yield (and rent) to the new yield account
+        let legacy_lamports = legacy_state_info.lamports();
+        **legacy_state_info.try_borrow_mut_lamports()? = 0;
+        **ctx
+            .accounts
+            .yield_account
+            .to_account_info()
+            .try_borrow_mut_lamports()? += legacy_lamports;
+        legacy_state_info.assign(&System::id());
+        legacy_state_info.realloc(0, false)?;
+
         Ok(())
     }
 
     pub fn update_price(ctx: Context<UpdatePrice>, price: u64) -> Result<()> {
         let state = &mut ctx.accounts.state;
-        state.price = price;
+        state.set_price(price, &Clock::get()?)?;
         Ok(())
     }
 
@@ -51,11 +128,15 @@ pub mod buy_burn_fixed {
         let token_program = &ctx.accounts.token_program;
         let holding_account = &mut ctx.accounts.holding_account;
         let holding_token_account = &mut ctx.accounts.holding_token_account;
+        let yield_account = &ctx.accounts.yield_account.to_account_info();
 
         if args.token_amount < state.purchase_threshold {
             return Err(ErrorCode::PurchaseThresholdExceeded.into());
         }
 
+        // refuse to buy at a price that has not been updated recently
+        state.check_price_staleness(&Clock::get()?)?;
+
         // for now, we'll just assume the total amount is passed in as an argument
         let treasury_amount = (args.sol_amount as f64 * state.purchase_proportion as f64) as u64;
 
@@ -63,17 +144,21 @@ pub mod buy_burn_fixed {
 
         let burn_amount = args.token_amount / state.price;
 
-        burn(
+        retire(
             burn_amount,
             state,
             mint_account,
             holding_token_account,
+            &ctx.accounts.retirement_vault,
+            &mut ctx.accounts.retirement_receipt,
             token_program,
         )?;
 
-        transfer_native(&state.to_account_info(), treasury, treasury_amount)?;
-        transfer_native(
-            &state.to_account_info(),
+        sweep_state_lamports(&state.to_account_info(), yield_account)?;
+        transfer_signed(state, yield_account, treasury, treasury_amount)?;
+        transfer_signed(
+            state,
+            yield_account,
             holding_account,
             holding_account_amount,
         )?;
@@ -83,4 +168,87 @@ pub mod buy_burn_fixed {
 
         Ok(())
     }
+
+    pub fn swap_and_burn(ctx: Context<SwapAndBurn>, args: SwapAndBurnInput) -> Result<()> {
+        let mint_account = &ctx.accounts.mint;
+        let state = &mut ctx.accounts.state;
+        let treasury = &mut ctx.accounts.treasury;
+        let token_program = &ctx.accounts.token_program;
+        let wrapped_sol_account = &ctx.accounts.wrapped_sol_account;
+        let purchased_token_account = &mut ctx.accounts.purchased_token_account;
+        let yield_account = &ctx.accounts.yield_account.to_account_info();
+
+        if args.minimum_amount_out < state.purchase_threshold {
+            return Err(ErrorCode::PurchaseThresholdExceeded.into());
+        }
+
+        // the state's price bounds the swap's output, so it must be current
+        state.check_price_staleness(&Clock::get()?)?;
+
+        let treasury_amount = (args.sol_amount as f64 * state.purchase_proportion as f64) as u64;
+        let swap_amount = args.sol_amount - treasury_amount;
+        let minimum_amount_out = args
+            .minimum_amount_out
+            .max(state.minimum_swap_output(swap_amount));
+
+        // move the purchase amount into the state's wrapped SOL account
+        sweep_state_lamports(&state.to_account_info(), yield_account)?;
+        wrap_native(
+            swap_amount,
+            state,
+            yield_account,
+            wrapped_sol_account,
+            &token_program.to_account_info(),
+        )?;
+
+        let balance_before = purchased_token_account.amount;
+
+        msg!("Swapping {} lamports", swap_amount);
+        let index_bytes = state.index.to_le_bytes();
+        let seeds = [STATE, state.mint.as_ref(), &index_bytes, &[state.bump]];
+        swap(
+            &SwapAccounts {
+                swap_program: ctx.accounts.swap_program.to_account_info(),
+                pool: ctx.accounts.swap_pool.to_account_info(),
+                pool_authority: ctx.accounts.swap_pool_authority.to_account_info(),
+                user_transfer_authority: state.to_account_info(),
+                source: wrapped_sol_account.to_account_info(),
+                pool_source: ctx.accounts.swap_pool_source.to_account_info(),
+                pool_destination: ctx.accounts.swap_pool_destination.to_account_info(),
+                destination: purchased_token_account.to_account_info(),
+                pool_mint: ctx.accounts.swap_pool_mint.to_account_info(),
+                pool_fee_account: ctx.accounts.swap_pool_fee_account.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+            swap_amount,
+            minimum_amount_out,
+            &[&seeds],
+        )?;
+
+        // retire exactly what the swap delivered
+        purchased_token_account.reload()?;
+        let burn_amount = purchased_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(ErrorCode::SlippageExceeded)?;
+        require_gte!(burn_amount, minimum_amount_out, ErrorCode::SlippageExceeded);
+
+        msg!("Retiring {} tokens", burn_amount);
+        retire(
+            burn_amount,
+            state,
+            mint_account,
+            purchased_token_account,
+            &ctx.accounts.retirement_vault,
+            &mut ctx.accounts.retirement_receipt,
+            token_program,
+        )?;
+
+        transfer_signed(state, yield_account, treasury, treasury_amount)?;
+
+        // update total sol spent
+        state.total_spent += swap_amount;
+
+        Ok(())
+    }
 }
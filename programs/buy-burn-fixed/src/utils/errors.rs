@@ -13,4 +13,34 @@ pub enum ErrorCode {
 
     #[msg("purchase threshold exceeded")]
     PurchaseThresholdExceeded,
+
+    #[msg("price must be greater than zero")]
+    InvalidPrice,
+
+    #[msg("price change exceeds the maximum allowed within the price change window")]
+    PriceChangeTooLarge,
+
+    #[msg("price is stale")]
+    StalePrice,
+
+    #[msg("swap returned less than the minimum amount out")]
+    SlippageExceeded,
+
+    #[msg("retirement vault and receipt accounts are required")]
+    MissingRetirementAccounts,
+
+    #[msg("incorrect update authority")]
+    Unauthorized,
+
+    #[msg("invalid legacy state account")]
+    InvalidLegacyState,
+
+    #[msg("max slippage must be at most 10000 basis points")]
+    InvalidMaxSlippage,
+
+    #[msg("retirement vault and receipt accounts must not be passed when burning")]
+    UnexpectedRetirementAccounts,
+
+    #[msg("too many price updates within the price change window")]
+    TooManyPriceUpdates,
 }
@@ -1 +1 @@
-pub const STATE: &[u8] = b"state";
+pub use yield_controller_common::seeds::{RETIREMENT_RECEIPT, STATE, YIELD_ACCOUNT};
@@ -1,6 +1,14 @@
-use crate::utils::seeds::STATE;
+use crate::utils::errors::ErrorCode;
+use crate::utils::seeds::{RETIREMENT_RECEIPT, STATE, YIELD_ACCOUNT};
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token::{Mint, Token, TokenAccount};
+pub use yield_controller_common::retirement::RetirementBackend;
+
+pub const PRICE_HISTORY_LENGTH: usize = 16;
+// the period (in seconds) over which the price may change by at most `max_price_change_bps`
+pub const PRICE_CHANGE_WINDOW: i64 = 60 * 60;
 
 /* This argument will be used for both registering and updating the state account */
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -14,6 +22,23 @@ pub struct GenericStateInput {
     pub price: u64, /* TODO: replace with oracle */
     pub purchase_threshold: u64,
     pub purchase_proportion: f32,
+    // maximum age of the price (in seconds) after which allocate_yield is refused, 0 to disable
+    pub price_staleness_threshold: u64,
+    // maximum change of the price within `PRICE_CHANGE_WINDOW` (in basis points), 0 to disable
+    pub max_price_change_bps: u16,
+    // the AMM program and pool used by swap_and_burn
+    pub swap_program: Pubkey,
+    pub swap_pool: Pubkey,
+    // maximum shortfall of a swap's output from the amount the price buys (in basis points)
+    pub max_slippage_bps: u16,
+    // how purchased tokens are taken out of circulation
+    pub retirement_backend: RetirementBackend,
+    // the token account receiving purchased tokens when retiring to a vault
+    pub retirement_vault: Pubkey,
+    // the party on whose behalf tokens are retired, recorded in each receipt
+    pub retirement_beneficiary: Pubkey,
+    // distinguishes multiple states (strategies) for the same mint
+    pub index: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -22,6 +47,22 @@ pub struct AllocateYieldInput {
     pub token_amount: u64,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateStateInput {
+    pub index: u8,
+    pub price_staleness_threshold: u64,
+    pub max_price_change_bps: u16,
+    pub max_slippage_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SwapAndBurnInput {
+    pub sol_amount: u64,
+    // slippage protection: the swap fails if fewer tokens are received,
+    // or fewer than the state's price buys less its maximum slippage
+    pub minimum_amount_out: u64,
+}
+
 #[account]
 pub struct State {
     pub update_authority: Pubkey,
@@ -34,12 +75,151 @@ pub struct State {
     pub holding_token_account: Pubkey,
     pub total_spent: u64,
     pub bump: u8,
+    pub price_staleness_threshold: u64,
+    pub max_price_change_bps: u16,
+    pub last_price_update_slot: u64,
+    pub last_price_update_timestamp: i64,
+    // ring buffer of the most recent price updates, oldest entry at `price_history_index`
+    pub price_history: [PriceRecord; PRICE_HISTORY_LENGTH],
+    pub price_history_index: u8,
+    pub swap_program: Pubkey,
+    pub swap_pool: Pubkey,
+    pub retirement_backend: RetirementBackend,
+    pub retirement_vault: Pubkey,
+    pub retirement_beneficiary: Pubkey,
+    pub total_retirements: u64,
+    pub index: u8,
+    // the system-owned PDA holding the yield to be allocated, kept apart from the state's rent
+    pub yield_account_bump: u8,
+    pub max_slippage_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PriceRecord {
+    pub price: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+impl PriceRecord {
+    const SPACE: usize = 8 + 8 + 8;
 }
 
 impl State {
-    const SPACE: usize = 32 + 32 + 32 + 32 + 32 + 32 + 4 + 1 + 8 /* Discriminator */;
+    const SPACE: usize = 32 + 32 + 32 + 8 + 8 + 4 + 32 + 32 + 8 + 1
+        + 8 + 2 + 8 + 8
+        + (PriceRecord::SPACE * PRICE_HISTORY_LENGTH) + 1
+        + 32 + 32
+        + 1 + 32 + 32 + 8
+        + 1 + 1
+        + 2
+        + 8 /* Discriminator */;
+
+    pub fn set_price(&mut self, price: u64, clock: &Clock) -> Result<()> {
+        require_gt!(price, 0, ErrorCode::InvalidPrice);
+
+        let index = self.price_history_index as usize;
+        if self.max_price_change_bps > 0 && self.price > 0 {
+            // the new price is checked against every price in effect within the window,
+            // so back-to-back updates cannot step it further than a single one
+            let window_start = clock.unix_timestamp.saturating_sub(PRICE_CHANGE_WINDOW);
+            let mut window_covered = false;
+            for age in 1..=PRICE_HISTORY_LENGTH {
+                let record = &self.price_history
+                    [(index + PRICE_HISTORY_LENGTH - age) % PRICE_HISTORY_LENGTH];
+                if record.price == 0 {
+                    // no earlier price was recorded
+                    window_covered = true;
+                    break;
+                }
+                let change = record.price.abs_diff(price) as u128;
+                let max_change = record.price as u128 * self.max_price_change_bps as u128 / 10_000;
+                require_gte!(max_change, change, ErrorCode::PriceChangeTooLarge);
+                if record.timestamp <= window_start {
+                    // the price in effect at the start of the window
+                    window_covered = true;
+                    break;
+                }
+            }
+            // the whole history lies within the window, so the earlier prices in it are unknown
+            require!(window_covered, ErrorCode::TooManyPriceUpdates);
+        }
+
+        self.price = price;
+        self.last_price_update_slot = clock.slot;
+        self.last_price_update_timestamp = clock.unix_timestamp;
+
+        // overwrite the oldest entry in the price history
+        self.price_history[index] = PriceRecord {
+            price,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        };
+        self.price_history_index = ((index + 1) % PRICE_HISTORY_LENGTH) as u8;
+
+        Ok(())
+    }
+
+    pub fn set_max_slippage_bps(&mut self, max_slippage_bps: u16) -> Result<()> {
+        require_gte!(10_000, max_slippage_bps, ErrorCode::InvalidMaxSlippage);
+        self.max_slippage_bps = max_slippage_bps;
+        Ok(())
+    }
+
+    /// The fewest tokens a swap of `lamports` may return: what they buy at the state's price
+    /// (in lamports per token minor unit, as in allocate_yield), less the maximum slippage
+    pub fn minimum_swap_output(&self, lamports: u64) -> u64 {
+        let expected = lamports as u128 / self.price as u128;
+        let minimum = expected * (10_000 - self.max_slippage_bps as u128) / 10_000;
+        minimum as u64
+    }
+
+    pub fn check_price_staleness(&self, clock: &Clock) -> Result<()> {
+        // a threshold of 0 disables the check
+        if self.price_staleness_threshold == 0 {
+            return Ok(());
+        }
+        let price_age = clock
+            .unix_timestamp
+            .saturating_sub(self.last_price_update_timestamp);
+        msg!("Price age: {}s", price_age);
+        require_gte!(
+            i64::try_from(self.price_staleness_threshold).unwrap_or(i64::MAX),
+            price_age,
+            ErrorCode::StalePrice
+        );
+        Ok(())
+    }
+}
+
+/// The layout of states registered before states were indexed, seeded by [STATE, mint].
+/// It shares the `State` discriminator, so it is read manually during migration.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyState {
+    pub update_authority: Pubkey,
+    pub treasury: Pubkey,
+    pub mint: Pubkey,
+    pub price: u64,
+    pub purchase_threshold: u64,
+    pub purchase_proportion: f32,
+    pub holding_account: Pubkey,
+    pub holding_token_account: Pubkey,
+    pub total_spent: u64,
+    pub bump: u8,
 }
 
+impl LegacyState {
+    pub fn try_from_account_info(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != State::DISCRIMINATOR {
+            return Err(ErrorCode::InvalidLegacyState.into());
+        }
+        LegacyState::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::InvalidLegacyState.into())
+    }
+}
+
+yield_controller_common::retirement_receipt!();
+
 #[derive(Accounts)]
 #[instruction(state_in: GenericStateInput)]
 pub struct RegisterState<'info> {
@@ -48,7 +228,7 @@ pub struct RegisterState<'info> {
     #[account(
         init,
         space = State::SPACE,
-        seeds = [STATE, state_in.mint.key().as_ref()],
+        seeds = [STATE, state_in.mint.key().as_ref(), state_in.index.to_le_bytes().as_ref()],
         payer = payer,
         bump
     )]
@@ -57,6 +237,37 @@ pub struct RegisterState<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(args: MigrateStateInput)]
+pub struct MigrateState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE, mint.key().as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    /// CHECK: The legacy layout is deserialized (and the authority checked) in the instruction
+    pub legacy_state: UncheckedAccount<'info>,
+    #[account(
+        init,
+        space = State::SPACE,
+        seeds = [STATE, mint.key().as_ref(), args.index.to_le_bytes().as_ref()],
+        payer = payer,
+        bump
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        seeds = [YIELD_ACCOUNT, state.key().as_ref()],
+        bump,
+    )]
+    pub yield_account: SystemAccount<'info>,
+    pub mint: Account<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 #[instruction(state_in: GenericStateInput)]
 pub struct UpdateState<'info> {
@@ -64,7 +275,7 @@ pub struct UpdateState<'info> {
     pub payer: Signer<'info>,
     #[account(
         mut,
-        seeds = [STATE, state_in.mint.key().as_ref()],
+        seeds = [STATE, state_in.mint.key().as_ref(), state_in.index.to_le_bytes().as_ref()],
         bump = yield_account.bump,
         constraint = yield_account.update_authority == payer.key()
     )]
@@ -96,6 +307,12 @@ pub struct AllocateYield<'info> {
         has_one = mint,
     )]
     pub state: Account<'info, State>,
+    #[account(
+        mut,
+        seeds = [YIELD_ACCOUNT, state.key().as_ref()],
+        bump = state.yield_account_bump,
+    )]
+    pub yield_account: SystemAccount<'info>,
     #[account(mut)]
     pub mint: Account<'info, Mint>,
     /// CHECK: constraints checked in state
@@ -106,6 +323,99 @@ pub struct AllocateYield<'info> {
     pub holding_account: AccountInfo<'info>,
     #[account(mut)]
     pub holding_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = retirement_vault.key() == state.retirement_vault,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_vault: Option<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        space = RetirementReceipt::SPACE,
+        seeds = [RETIREMENT_RECEIPT, state.key().as_ref(), state.total_retirements.to_le_bytes().as_ref()],
+        bump,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_receipt: Option<Account<'info, RetirementReceipt>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SwapAndBurn<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        has_one = treasury,
+        has_one = mint,
+        has_one = swap_program,
+        has_one = swap_pool,
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        seeds = [YIELD_ACCOUNT, state.key().as_ref()],
+        bump = state.yield_account_bump,
+    )]
+    pub yield_account: SystemAccount<'info>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: constraints checked in state
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = native_mint::ID,
+        token::authority = state,
+    )]
+    /// A wrapped SOL account owned by the state, used as the swap input
+    pub wrapped_sol_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = state,
+    )]
+    /// A token account owned by the state, receiving the swap output before it is burned
+    pub purchased_token_account: Account<'info, TokenAccount>,
+    #[account(executable)]
+    /// CHECK: constraints checked in state
+    pub swap_program: UncheckedAccount<'info>,
+    #[account(owner = swap_program.key())]
+    /// CHECK: constraints checked in state
+    pub swap_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the swap program
+    pub swap_pool_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_source: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_destination: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_fee_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = retirement_vault.key() == state.retirement_vault,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_vault: Option<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        space = RetirementReceipt::SPACE,
+        seeds = [RETIREMENT_RECEIPT, state.key().as_ref(), state.total_retirements.to_le_bytes().as_ref()],
+        bump,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_receipt: Option<Account<'info, RetirementReceipt>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
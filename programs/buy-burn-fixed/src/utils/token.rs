@@ -1,60 +1,103 @@
 use crate::utils::errors::ErrorCode;
-use crate::utils::seeds::STATE;
-use crate::utils::state::State;
+use crate::utils::seeds::{STATE, YIELD_ACCOUNT};
+use crate::utils::state::{RetirementReceipt, State};
 use anchor_lang::prelude::*;
-use anchor_spl::{token, token::Mint, token::TokenAccount};
+use anchor_spl::{token::Mint, token::TokenAccount};
+use yield_controller_common::retirement::{retire_signed, RetirementAccounts};
+use yield_controller_common::token::wrap_native_signed;
+use yield_controller_common::transfer::{sweep_excess_lamports, transfer_native_signed};
 
-pub fn burn<'a>(
+pub fn wrap_native<'a>(
     amount: u64,
     state: &Account<'a, State>,
-    mint: &Account<'a, Mint>,
-    token_account: &Account<'a, TokenAccount>,
+    yield_account: &AccountInfo<'a>,
+    wrapped_sol_account: &Account<'a, TokenAccount>,
     token_program: &AccountInfo<'a>,
 ) -> Result<()> {
-    let seeds = [STATE, state.mint.as_ref(), &[state.bump]];
+    let state_key = state.key();
+    let seeds = [
+        YIELD_ACCOUNT,
+        state_key.as_ref(),
+        &[state.yield_account_bump],
+    ];
 
-    let cpi_program = token_program.clone();
-    let accounts = token::Burn {
-        mint: mint.to_account_info(),
-        authority: state.to_account_info(),
-        from: token_account.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new(cpi_program, accounts);
-
-    token::burn(cpi_ctx.with_signer(&[&seeds]), amount)
+    wrap_native_signed(
+        token_program,
+        yield_account,
+        &wrapped_sol_account.to_account_info(),
+        amount,
+        &seeds,
+        ErrorCode::InsufficientFundsForTransaction,
+    )
 }
 
-pub fn transfer_native<'a>(
-    source: &AccountInfo<'a>,
-    dest: &AccountInfo<'a>,
+/// Transfer lamports out of the yield account, signed by its PDA.
+pub fn transfer_signed<'a>(
+    state: &Account<'a, State>,
+    yield_account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
     amount: u64,
 ) -> Result<()> {
-    if **source.try_borrow_lamports()? < amount {
-        return Err(ErrorCode::InsufficientFundsForTransaction.into());
-    }
+    // never take the yield account below its rent-exempt minimum
+    let state_key = state.key();
+    let seeds = [
+        YIELD_ACCOUNT,
+        state_key.as_ref(),
+        &[state.yield_account_bump],
+    ];
 
-    **source.try_borrow_mut_lamports()? -= amount;
-    **dest.try_borrow_mut_lamports()? += amount;
+    transfer_native_signed(
+        yield_account,
+        destination,
+        amount,
+        &seeds,
+        ErrorCode::InsufficientFundsForTransaction,
+    )
+}
+
+/// Move any lamports held by the state above its rent-exempt minimum into the yield account,
+/// so that yield sent to the state address directly is not stranded, and the state stays rent-exempt.
+pub fn sweep_state_lamports<'a>(
+    state: &AccountInfo<'a>,
+    yield_account: &AccountInfo<'a>,
+) -> Result<()> {
+    // the state is owned by this program, so its lamports are debited directly
+    sweep_excess_lamports(state, yield_account)?;
     Ok(())
 }
 
-/*
-pub fn _transfer_token<'a>(
+/// Take `amount` tokens held in `token_account` out of circulation using the state's retirement backend
+pub fn retire<'a>(
     amount: u64,
-    authority: &Account<'a, State>,
-    source: &Account<'a, TokenAccount>,
-    destination: &Account<'a, TokenAccount>,
-    seeds: &[&[u8]],
+    state: &mut Account<'a, State>,
+    mint: &Account<'a, Mint>,
+    token_account: &Account<'a, TokenAccount>,
+    retirement_vault: &Option<Account<'a, TokenAccount>>,
+    retirement_receipt: &mut Option<Account<'a, RetirementReceipt>>,
     token_program: &AccountInfo<'a>,
 ) -> Result<()> {
-    let cpi_program = token_program.clone();
-    let accounts = token::Transfer {
-        from: source.to_account_info(),
-        to: destination.to_account_info(),
-        authority: authority.to_account_info(),
+    let accounts = RetirementAccounts {
+        token_program: token_program.clone(),
+        mint: mint.to_account_info(),
+        token_account: token_account.to_account_info(),
+        authority: state.to_account_info(),
+        retirement_vault: retirement_vault
+            .as_ref()
+            .map(|retirement_vault| retirement_vault.to_account_info()),
     };
+    let state_mint = state.mint;
+    let index_bytes = state.index.to_le_bytes();
+    let seeds = [STATE, state_mint.as_ref(), &index_bytes, &[state.bump]];
 
-    let cpi_ctx = CpiContext::new(cpi_program, accounts);
-    token::transfer(cpi_ctx.with_signer(&[seeds]), amount)
+    retire_signed(
+        state.retirement_backend,
+        &accounts,
+        retirement_receipt.as_deref_mut(),
+        state.retirement_beneficiary,
+        &mut state.total_retirements,
+        amount,
+        &seeds,
+        ErrorCode::MissingRetirementAccounts,
+        ErrorCode::UnexpectedRetirementAccounts,
+    )
 }
-*/
@@ -0,0 +1,2 @@
+pub(crate) mod mpl_bubblegum;
+pub(crate) mod spl_account_compression;
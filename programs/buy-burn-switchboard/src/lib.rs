@@ -1,10 +1,20 @@
 #![allow(clippy::result_large_err)]
-use crate::utils::errors::ErrorCode;
+use crate::utils::receipt::*;
+use crate::utils::seeds::STATE;
 use crate::utils::state::*;
 use crate::utils::token::*;
 use anchor_lang::prelude::*;
+use yield_controller_common::swap::*;
+mod external_programs;
 mod utils;
 
+// account and argument types, for off-chain clients
+pub use crate::utils::errors::ErrorCode;
+pub use crate::utils::state::{
+    Adjustment, AdjustmentLog, AdjustmentReason, GenericStateInput, LegacyState, RetirementBackend,
+    RetirementReceipt, State,
+};
+
 declare_id!("sbnbpcN3HVfcj9jTwzncwLeNvCzSwbfMwNmdAgX36VW");
 
 #[program]
@@ -26,6 +36,14 @@ pub mod buy_burn_switchboard {
         state_account.feed_staleness_threshold = state.feed_staleness_threshold;
         state_account.index = state.index;
         state_account.yield_account_bump = state.yield_account_bump;
+        state_account.swap_program = state.swap_program;
+        state_account.swap_pool = state.swap_pool;
+        state_account.set_max_slippage_bps(state.max_slippage_bps)?;
+        state_account.retirement_backend = state.retirement_backend;
+        state_account.retirement_vault = state.retirement_vault;
+        state_account.retirement_beneficiary = state.retirement_beneficiary;
+        state_account.receipt_merkle_tree = state.receipt_merkle_tree;
+        state_account.receipt_vault = state.receipt_vault;
         state_account.bump = ctx.bumps.state;
         Ok(())
     }
@@ -43,6 +61,77 @@ pub mod buy_burn_switchboard {
         state_account.nct_usd_price_feed = state.nct_usd_price_feed;
         state_account.feed_staleness_threshold = state.feed_staleness_threshold;
         state_account.yield_account_bump = state.yield_account_bump;
+        state_account.swap_program = state.swap_program;
+        state_account.swap_pool = state.swap_pool;
+        state_account.set_max_slippage_bps(state.max_slippage_bps)?;
+        state_account.retirement_backend = state.retirement_backend;
+        state_account.retirement_vault = state.retirement_vault;
+        state_account.retirement_beneficiary = state.retirement_beneficiary;
+        state_account.receipt_merkle_tree = state.receipt_merkle_tree;
+        state_account.receipt_vault = state.receipt_vault;
+        Ok(())
+    }
+
+    /// Upgrade a state registered before swaps, vault retirement and burn receipts were added,
+    /// growing it to the current layout. The new settings start disabled: no swap pool,
+    /// burning rather than retiring to a vault, no receipts and no slippage allowance,
+    /// until set by `update_state`.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let state_info = ctx.accounts.state.to_account_info();
+        let legacy_state = LegacyState::try_from_account_info(&state_info)?;
+        require_keys_eq!(
+            legacy_state.update_authority,
+            ctx.accounts.payer.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let state = State {
+            update_authority: legacy_state.update_authority,
+            treasury: legacy_state.treasury,
+            mint: legacy_state.mint,
+            sol_usd_price_feed: legacy_state.sol_usd_price_feed,
+            nct_usd_price_feed: legacy_state.nct_usd_price_feed,
+            holding_account: legacy_state.holding_account,
+            holding_token_account: legacy_state.holding_token_account,
+            feed_staleness_threshold: legacy_state.feed_staleness_threshold,
+            purchase_threshold: legacy_state.purchase_threshold,
+            purchase_proportion: legacy_state.purchase_proportion,
+            total_tokens_purchased: legacy_state.total_tokens_purchased,
+            index: legacy_state.index,
+            bump: legacy_state.bump,
+            yield_account_bump: legacy_state.yield_account_bump,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            retirement_backend: RetirementBackend::Burn,
+            retirement_vault: Pubkey::default(),
+            retirement_beneficiary: Pubkey::default(),
+            total_retirements: 0,
+            receipt_merkle_tree: Pubkey::default(),
+            receipt_vault: Pubkey::default(),
+            max_slippage_bps: 0,
+        };
+
+        // grow the state to the current layout, topping up its rent from the payer
+        let rent_shortfall = Rent::get()?
+            .minimum_balance(State::SPACE)
+            .saturating_sub(state_info.lamports());
+        if rent_shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: state_info.clone(),
+                    },
+                ),
+                rent_shortfall,
+            )?;
+        }
+        state_info.realloc(State::SPACE, false)?;
+
+        let mut data = state_info.try_borrow_mut_data()?;
+        state.try_serialize(&mut &mut data[..])?;
+
         Ok(())
     }
 
@@ -64,7 +153,7 @@ pub mod buy_burn_switchboard {
             return Err(ErrorCode::InvalidMint.into());
         }
 
-        let available_amount = yield_account.to_account_info().try_lamports()?;
+        let available_amount = spendable_balance(&yield_account.to_account_info())?;
 
         // for now, we'll just assume the total amount is passed in as an argument
         // "Purchase_proportion" of the amount will go to purchasing
@@ -73,23 +162,20 @@ pub mod buy_burn_switchboard {
 
         let amount_sent_to_treasury = available_amount
             .checked_sub(amount_used_for_token_purchase)
-            .unwrap();
+            .ok_or(ErrorCode::InsufficientFundsForTransaction)?;
 
-        let price = get_latest_price(
-            &ctx.accounts.sol_usd_price_feed,
-            &ctx.accounts.nct_usd_price_feed,
+        let latest_price = get_latest_price(
+            &ctx.accounts.sol_usd_price_feed.to_account_info(),
+            &ctx.accounts.nct_usd_price_feed.to_account_info(),
             state_account.feed_staleness_threshold,
+            Clock::get()?.unix_timestamp,
         )?;
+        let price = latest_price.nct_sol;
         msg!("Latest oracle price: {}", price);
 
         // Price is token price in SOL
-        // Amount is in lamports (9 dp)
-        // We need to convert to the token amount in minor units
-        // token amount = lamports / (10^(9-decimals)) * price
-        // Note, this works even if decimals > 9
-        let token_decimal_denominator = (10_f64).powi(9_i32 - mint_account.decimals as i32);
         let token_amount_to_buy_and_burn =
-            (amount_used_for_token_purchase as f64 / (token_decimal_denominator * price)) as u64;
+            latest_price.token_amount(amount_used_for_token_purchase, mint_account.decimals);
 
         msg!("Available amount: {}", available_amount);
         msg!(
@@ -110,14 +196,35 @@ pub mod buy_burn_switchboard {
 
         msg!("Buying and burning {} tokens", token_amount_to_buy_and_burn);
 
-        burn(
+        retire(
             token_amount_to_buy_and_burn,
             state_account,
             mint_account,
             holding_token_account,
+            &ctx.accounts.retirement_vault,
+            &mut ctx.accounts.retirement_receipt,
             token_program,
         )?;
 
+        // mint a compressed NFT receipt for this burn if a receipt tree is configured
+        if state_account.receipt_merkle_tree != Pubkey::default() {
+            mint_burn_receipt(
+                &BurnReceiptAccounts {
+                    tree_config: ctx.accounts.receipt_tree_config.as_ref(),
+                    merkle_tree: ctx.accounts.receipt_merkle_tree.as_ref(),
+                    receipt_vault: ctx.accounts.receipt_vault.as_ref(),
+                    payer: &ctx.accounts.payer,
+                    log_wrapper: ctx.accounts.log_wrapper.as_ref(),
+                    compression_program: ctx.accounts.compression_program.as_ref(),
+                    bubblegum_program: ctx.accounts.bubblegum_program.as_ref(),
+                    system_program: &ctx.accounts.system_program,
+                },
+                state_account,
+                token_amount_to_buy_and_burn,
+                &latest_price,
+            )?;
+        }
+
         msg!(
             "Sending {} to holding account",
             amount_used_for_token_purchase
@@ -144,12 +251,197 @@ pub mod buy_burn_switchboard {
         Ok(())
     }
 
-    pub fn set_total_tokens_purchased(
-        ctx: Context<SetTotalTokensPurchased>,
-        value: u64,
+    /// Correct the published total tokens purchased by `delta`,
+    /// recording the change in the adjustment log and as an event
+    pub fn adjust_total_tokens_purchased(
+        ctx: Context<AdjustTotalTokensPurchased>,
+        delta: i64,
+        reason: AdjustmentReason,
     ) -> Result<()> {
         let state_account = &mut ctx.accounts.state;
-        state_account.total_tokens_purchased = value;
+        let adjustment_log = &mut ctx.accounts.adjustment_log;
+        let payer = &ctx.accounts.payer;
+
+        let previous_total = state_account.total_tokens_purchased;
+        let new_total = previous_total
+            .checked_add_signed(delta)
+            .ok_or(ErrorCode::InvalidAdjustment)?;
+        state_account.total_tokens_purchased = new_total;
+
+        // grow the log by one entry, topping up its rent from the payer
+        let adjustment_log_info = adjustment_log.to_account_info();
+        let new_space = AdjustmentLog::space(adjustment_log.adjustments.len() + 1);
+        let rent_shortfall = Rent::get()?
+            .minimum_balance(new_space)
+            .saturating_sub(adjustment_log_info.lamports());
+        if rent_shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: payer.to_account_info(),
+                        to: adjustment_log_info.clone(),
+                    },
+                ),
+                rent_shortfall,
+            )?;
+        }
+        adjustment_log_info.realloc(new_space, false)?;
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        adjustment_log.state = state_account.key();
+        adjustment_log.adjustments.push(Adjustment {
+            delta,
+            reason,
+            signer: payer.key(),
+            timestamp,
+        });
+
+        emit!(TotalTokensPurchasedAdjusted {
+            state: state_account.key(),
+            delta,
+            reason,
+            signer: payer.key(),
+            previous_total,
+            new_total,
+        });
+
+        Ok(())
+    }
+
+    pub fn swap_and_burn(ctx: Context<SwapAndBurn>, minimum_amount_out: u64) -> Result<()> {
+        let mint_account = &ctx.accounts.mint;
+        let state_account = &mut ctx.accounts.state;
+        let treasury = &mut ctx.accounts.treasury;
+        let token_program = &ctx.accounts.token_program;
+        let wrapped_sol_account = &ctx.accounts.wrapped_sol_account;
+        let purchased_token_account = &mut ctx.accounts.purchased_token_account;
+
+        let yield_account = &ctx.accounts.yield_account;
+
+        let available_amount = spendable_balance(&yield_account.to_account_info())?;
+
+        let amount_used_for_token_purchase =
+            (available_amount as f64 * state_account.purchase_proportion as f64) as u64;
+
+        let amount_sent_to_treasury = available_amount
+            .checked_sub(amount_used_for_token_purchase)
+            .ok_or(ErrorCode::InsufficientFundsForTransaction)?;
+
+        msg!("Available amount: {}", available_amount);
+        msg!(
+            "Amount used for token purchase: {}",
+            amount_used_for_token_purchase
+        );
+
+        require_gte!(
+            available_amount,
+            state_account.purchase_threshold,
+            ErrorCode::PurchaseThresholdExceeded
+        );
+
+        // the swap must return close to what the purchase amount buys at the oracle price
+        let latest_price = get_latest_price(
+            &ctx.accounts.sol_usd_price_feed.to_account_info(),
+            &ctx.accounts.nct_usd_price_feed.to_account_info(),
+            state_account.feed_staleness_threshold,
+            Clock::get()?.unix_timestamp,
+        )?;
+        let minimum_amount_out = minimum_amount_out.max(state_account.minimum_swap_output(
+            latest_price.token_amount(amount_used_for_token_purchase, mint_account.decimals),
+        ));
+        msg!("Minimum amount out: {}", minimum_amount_out);
+
+        // move the purchase amount into the state's wrapped SOL account
+        wrap_native(
+            amount_used_for_token_purchase,
+            state_account,
+            &yield_account.to_account_info(),
+            wrapped_sol_account,
+            &token_program.to_account_info(),
+        )?;
+
+        let balance_before = purchased_token_account.amount;
+
+        let index_bytes = state_account.index.to_le_bytes();
+        let seeds = [
+            STATE,
+            state_account.mint.as_ref(),
+            &index_bytes,
+            &[state_account.bump],
+        ];
+        swap(
+            &SwapAccounts {
+                swap_program: ctx.accounts.swap_program.to_account_info(),
+                pool: ctx.accounts.swap_pool.to_account_info(),
+                pool_authority: ctx.accounts.swap_pool_authority.to_account_info(),
+                user_transfer_authority: state_account.to_account_info(),
+                source: wrapped_sol_account.to_account_info(),
+                pool_source: ctx.accounts.swap_pool_source.to_account_info(),
+                pool_destination: ctx.accounts.swap_pool_destination.to_account_info(),
+                destination: purchased_token_account.to_account_info(),
+                pool_mint: ctx.accounts.swap_pool_mint.to_account_info(),
+                pool_fee_account: ctx.accounts.swap_pool_fee_account.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+            amount_used_for_token_purchase,
+            minimum_amount_out,
+            &[&seeds],
+        )?;
+
+        // retire exactly what the swap delivered
+        purchased_token_account.reload()?;
+        let token_amount_to_burn = purchased_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(ErrorCode::SlippageExceeded)?;
+        require_gte!(
+            token_amount_to_burn,
+            minimum_amount_out,
+            ErrorCode::SlippageExceeded
+        );
+
+        msg!("Retiring {} tokens", token_amount_to_burn);
+        retire(
+            token_amount_to_burn,
+            state_account,
+            mint_account,
+            purchased_token_account,
+            &ctx.accounts.retirement_vault,
+            &mut ctx.accounts.retirement_receipt,
+            token_program,
+        )?;
+
+        // mint a compressed NFT receipt for this burn if a receipt tree is configured
+        if state_account.receipt_merkle_tree != Pubkey::default() {
+            mint_burn_receipt(
+                &BurnReceiptAccounts {
+                    tree_config: ctx.accounts.receipt_tree_config.as_ref(),
+                    merkle_tree: ctx.accounts.receipt_merkle_tree.as_ref(),
+                    receipt_vault: ctx.accounts.receipt_vault.as_ref(),
+                    payer: &ctx.accounts.payer,
+                    log_wrapper: ctx.accounts.log_wrapper.as_ref(),
+                    compression_program: ctx.accounts.compression_program.as_ref(),
+                    bubblegum_program: ctx.accounts.bubblegum_program.as_ref(),
+                    system_program: &ctx.accounts.system_program,
+                },
+                state_account,
+                token_amount_to_burn,
+                &latest_price,
+            )?;
+        }
+
+        msg!("Sending {} to treasury", amount_sent_to_treasury);
+        transfer_signed(
+            state_account,
+            &yield_account.to_account_info(),
+            treasury,
+            amount_sent_to_treasury,
+        )?;
+
+        // update total tokens purchased
+        state_account.total_tokens_purchased += token_amount_to_burn;
+
         Ok(())
     }
 }
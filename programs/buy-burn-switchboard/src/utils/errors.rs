@@ -16,4 +16,43 @@ pub enum ErrorCode {
 
     #[msg("The switchboard feed account is invalid")]
     InvalidSwitchboardAccount,
+
+    #[msg("The switchboard feed has not been updated recently")]
+    StaleFeed,
+
+    #[msg("The switchboard feed exceeds the confidence interval")]
+    ConfidenceIntervalExceeded,
+
+    #[msg("The switchboard feed round has too few oracle results")]
+    InvalidAggregatorRound,
+
+    #[msg("Swap returned less than the minimum amount out")]
+    SlippageExceeded,
+
+    #[msg("Retirement vault and receipt accounts are required")]
+    MissingRetirementAccounts,
+
+    #[msg("Receipt tree accounts are required")]
+    MissingReceiptAccounts,
+
+    #[msg("Incorrect receipt merkle tree")]
+    IncorrectReceiptTree,
+
+    #[msg("Incorrect receipt vault")]
+    IncorrectReceiptVault,
+
+    #[msg("Adjustment would make total tokens purchased negative or overflow")]
+    InvalidAdjustment,
+
+    #[msg("Max slippage must be at most 10000 basis points")]
+    InvalidMaxSlippage,
+
+    #[msg("Retirement vault and receipt accounts must not be passed when burning")]
+    UnexpectedRetirementAccounts,
+
+    #[msg("Incorrect update authority")]
+    Unauthorized,
+
+    #[msg("Account is not a state in the legacy layout")]
+    InvalidLegacyState,
 }
@@ -1,4 +1,5 @@
 pub(crate) mod errors;
+pub(crate) mod receipt;
 pub(crate) mod seeds;
 pub(crate) mod state;
 pub(crate) mod switchboard;
@@ -0,0 +1,112 @@
+use crate::external_programs::mpl_bubblegum::MplBubblegum;
+use crate::external_programs::spl_account_compression::SplAccountCompression;
+use crate::utils::errors::ErrorCode;
+use crate::utils::seeds::STATE;
+use crate::utils::state::State;
+use crate::utils::switchboard::LatestPrice;
+use anchor_lang::prelude::*;
+use mpl_bubblegum::types::{MetadataArgs, TokenProgramVersion, TokenStandard};
+use yield_controller_common::bubblegum::{mint_cnft_signed, AccountsCNFTMint};
+
+const RECEIPT_NAME: &str = "Sunrise Burn Receipt";
+const RECEIPT_SYMBOL: &str = "SBR";
+
+pub struct BurnReceiptAccounts<'a, 'b> {
+    pub tree_config: Option<&'b UncheckedAccount<'a>>,
+    pub merkle_tree: Option<&'b UncheckedAccount<'a>>,
+    pub receipt_vault: Option<&'b UncheckedAccount<'a>>,
+    pub payer: &'b Signer<'a>,
+    pub log_wrapper: Option<&'b UncheckedAccount<'a>>,
+    pub compression_program: Option<&'b Program<'a, SplAccountCompression>>,
+    pub bubblegum_program: Option<&'b Program<'a, MplBubblegum>>,
+    pub system_program: &'b Program<'a, System>,
+}
+
+/// The receipt data is stored on-chain in the leaf itself, as a JSON data URI,
+/// so that the burned amount and the price it was bought at can be verified from the tree alone.
+fn receipt_uri(amount: u64, latest_price: &LatestPrice) -> String {
+    format!(
+        "data:application/json,{{\"amount\":{},\"nct_sol\":{},\"sol_usd\":{},\"nct_usd\":{}}}",
+        amount, latest_price.nct_sol, latest_price.sol_usd, latest_price.nct_usd
+    )
+}
+
+pub fn mint_burn_receipt<'a>(
+    accounts: &BurnReceiptAccounts<'a, '_>,
+    state_account: &Account<'a, State>,
+    amount: u64,
+    latest_price: &LatestPrice,
+) -> Result<()> {
+    let (
+        Some(tree_config),
+        Some(merkle_tree),
+        Some(receipt_vault),
+        Some(log_wrapper),
+        Some(compression_program),
+        Some(bubblegum_program),
+    ) = (
+        accounts.tree_config,
+        accounts.merkle_tree,
+        accounts.receipt_vault,
+        accounts.log_wrapper,
+        accounts.compression_program,
+        accounts.bubblegum_program,
+    )
+    else {
+        return Err(ErrorCode::MissingReceiptAccounts.into());
+    };
+
+    require_keys_eq!(
+        merkle_tree.key(),
+        state_account.receipt_merkle_tree,
+        ErrorCode::IncorrectReceiptTree
+    );
+    require_keys_eq!(
+        receipt_vault.key(),
+        state_account.receipt_vault,
+        ErrorCode::IncorrectReceiptVault
+    );
+
+    let metadata = MetadataArgs {
+        name: RECEIPT_NAME.to_string(),
+        symbol: RECEIPT_SYMBOL.to_string(),
+        uri: receipt_uri(amount, latest_price),
+        seller_fee_basis_points: 0,
+        primary_sale_happened: true,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: vec![],
+    };
+
+    let state_account_info = state_account.to_account_info();
+    let receipt_vault_info = receipt_vault.to_account_info();
+    let seeds = [
+        STATE,
+        state_account.mint.as_ref(),
+        &state_account.index.to_le_bytes(),
+        &[state_account.bump],
+    ];
+
+    msg!("Minting burn receipt to {}", receipt_vault.key());
+    mint_cnft_signed(
+        &AccountsCNFTMint {
+            tree_config: tree_config.to_account_info(),
+            leaf_owner: receipt_vault_info.clone(),
+            leaf_delegate: receipt_vault_info,
+            merkle_tree: merkle_tree.to_account_info(),
+            payer: accounts.payer.to_account_info(),
+            // the state is the tree delegate and signs for it
+            tree_creator_or_delegate: state_account_info,
+            log_wrapper: log_wrapper.to_account_info(),
+            compression_program: compression_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            bubblegum_program: bubblegum_program.to_account_info(),
+        },
+        metadata,
+        &seeds,
+    )
+}
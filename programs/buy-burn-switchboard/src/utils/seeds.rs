@@ -1,2 +1,3 @@
-pub const STATE: &[u8] = b"state";
-pub const YIELD_ACCOUNT: &[u8] = b"yield_account";
+pub use yield_controller_common::seeds::{
+    ADJUSTMENT_LOG, RETIREMENT_RECEIPT, STATE, YIELD_ACCOUNT,
+};
@@ -1,7 +1,13 @@
-use crate::utils::seeds::{STATE, YIELD_ACCOUNT};
+use crate::external_programs::mpl_bubblegum::MplBubblegum;
+use crate::external_programs::spl_account_compression::SplAccountCompression;
+use crate::utils::errors::ErrorCode;
+use crate::utils::seeds::{ADJUSTMENT_LOG, RETIREMENT_RECEIPT, STATE, YIELD_ACCOUNT};
+use crate::utils::switchboard::SWITCHBOARD_PROGRAM_ID;
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use switchboard_v2::AggregatorAccountData;
+pub use yield_controller_common::retirement::RetirementBackend;
 
 /* This argument will be used for both registering and updating the state account */
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -18,6 +24,22 @@ pub struct GenericStateInput {
     pub purchase_proportion: f32,
     pub index: u8,
     pub yield_account_bump: u8,
+    // the AMM program and pool used by swap_and_burn
+    pub swap_program: Pubkey,
+    pub swap_pool: Pubkey,
+    // maximum shortfall of a swap's output from the amount the oracle price buys (in basis points)
+    pub max_slippage_bps: u16,
+    // how purchased tokens are taken out of circulation
+    pub retirement_backend: RetirementBackend,
+    // the token account receiving purchased tokens when retiring to a vault
+    pub retirement_vault: Pubkey,
+    // the party on whose behalf tokens are retired, recorded in each receipt
+    pub retirement_beneficiary: Pubkey,
+    // the Bubblegum tree burn receipts are minted into (the state must be its tree delegate),
+    // the default pubkey disables receipts
+    pub receipt_merkle_tree: Pubkey,
+    // the owner of minted burn receipts
+    pub receipt_vault: Pubkey,
 }
 
 #[account]
@@ -36,12 +58,68 @@ pub struct State {
     pub index: u8,
     pub bump: u8,
     pub yield_account_bump: u8,
+    pub swap_program: Pubkey,
+    pub swap_pool: Pubkey,
+    pub retirement_backend: RetirementBackend,
+    pub retirement_vault: Pubkey,
+    pub retirement_beneficiary: Pubkey,
+    pub total_retirements: u64,
+    pub receipt_merkle_tree: Pubkey,
+    pub receipt_vault: Pubkey,
+    pub max_slippage_bps: u16,
 }
 
 impl State {
+    pub const SPACE: usize = 32 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 4 + 8 + 1 + 1 + 1 + 32 + 32 + 1 + 32 + 32 + 8 + 32 + 32 + 2 + 8 /* Discriminator */;
+
+    pub fn set_max_slippage_bps(&mut self, max_slippage_bps: u16) -> Result<()> {
+        require_gte!(10_000, max_slippage_bps, ErrorCode::InvalidMaxSlippage);
+        self.max_slippage_bps = max_slippage_bps;
+        Ok(())
+    }
+
+    /// The fewest tokens a swap may return for `expected_amount`, the tokens bought at the oracle price
+    pub fn minimum_swap_output(&self, expected_amount: u64) -> u64 {
+        let minimum = expected_amount as u128 * (10_000 - self.max_slippage_bps as u128) / 10_000;
+        minimum as u64
+    }
+}
+
+/// The layout of states registered before swaps, vault retirement and burn receipts were added.
+/// It shares the `State` discriminator, so it is read manually during migration.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyState {
+    pub update_authority: Pubkey,
+    pub treasury: Pubkey,
+    pub mint: Pubkey,
+    pub sol_usd_price_feed: Pubkey,
+    pub nct_usd_price_feed: Pubkey,
+    pub holding_account: Pubkey,
+    pub holding_token_account: Pubkey,
+    pub feed_staleness_threshold: u64,
+    pub purchase_threshold: u64,
+    pub purchase_proportion: f32,
+    pub total_tokens_purchased: u64,
+    pub index: u8,
+    pub bump: u8,
+    pub yield_account_bump: u8,
+}
+
+impl LegacyState {
     pub const SPACE: usize = 32 + 32 + 32 + 32 + 32 + 32 + 32 + 8 + 8 + 4 + 8 + 1 + 1 + 1 + 8 /* Discriminator */;
+
+    pub fn try_from_account_info(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        // a current state starts with the same fields, but is longer
+        if data.len() != Self::SPACE || data[..8] != State::DISCRIMINATOR {
+            return Err(ErrorCode::InvalidLegacyState.into());
+        }
+        LegacyState::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::InvalidLegacyState.into())
+    }
 }
 
+yield_controller_common::retirement_receipt!();
+
 #[derive(Accounts)]
 #[instruction(state_in: GenericStateInput)]
 pub struct RegisterState<'info> {
@@ -74,7 +152,63 @@ pub struct UpdateState<'info> {
 }
 
 #[derive(Accounts)]
-pub struct SetTotalTokensPurchased<'info> {
+pub struct MigrateState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, owner = crate::ID)]
+    /// CHECK: The legacy layout is deserialized (and the authority checked) in the instruction
+    pub state: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdjustmentReason {
+    /// Fixing an incorrectly recorded amount
+    Correction,
+    /// Reconciling with tokens retired outside of this program
+    Reconciliation,
+    /// Carrying over totals from a previous program or state
+    Migration,
+    Other,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Adjustment {
+    pub delta: i64,
+    pub reason: AdjustmentReason,
+    pub signer: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Adjustment {
+    pub const SPACE: usize = 8 + 1 + 32 + 8;
+}
+
+/// An append-only log of all manual adjustments to a state's total tokens purchased
+#[account]
+pub struct AdjustmentLog {
+    pub state: Pubkey,
+    pub adjustments: Vec<Adjustment>,
+}
+
+impl AdjustmentLog {
+    pub fn space(adjustment_count: usize) -> usize {
+        32 + 4 + (Adjustment::SPACE * adjustment_count) + 8 /* Discriminator */
+    }
+}
+
+#[event]
+pub struct TotalTokensPurchasedAdjusted {
+    pub state: Pubkey,
+    pub delta: i64,
+    pub reason: AdjustmentReason,
+    pub signer: Pubkey,
+    pub previous_total: u64,
+    pub new_total: u64,
+}
+
+#[derive(Accounts)]
+pub struct AdjustTotalTokensPurchased<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -82,6 +216,15 @@ pub struct SetTotalTokensPurchased<'info> {
     constraint = state.update_authority == payer.key()
     )]
     pub state: Account<'info, State>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AdjustmentLog::space(0),
+        seeds = [ADJUSTMENT_LOG, state.key().as_ref()],
+        bump,
+    )]
+    pub adjustment_log: Account<'info, AdjustmentLog>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -122,8 +265,143 @@ pub struct AllocateYield<'info> {
         has_one = mint
     )]
     pub holding_token_account: Account<'info, TokenAccount>,
-    pub sol_usd_price_feed: AccountLoader<'info, AggregatorAccountData>,
-    pub nct_usd_price_feed: AccountLoader<'info, AggregatorAccountData>,
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount)]
+    /// CHECK: A Switchboard V2 aggregator, parsed in utils::switchboard
+    pub sol_usd_price_feed: UncheckedAccount<'info>,
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount)]
+    /// CHECK: A Switchboard V2 aggregator, parsed in utils::switchboard
+    pub nct_usd_price_feed: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = retirement_vault.key() == state.retirement_vault,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_vault: Option<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        space = RetirementReceipt::SPACE,
+        seeds = [RETIREMENT_RECEIPT, state.key().as_ref(), state.total_retirements.to_le_bytes().as_ref()],
+        bump,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_receipt: Option<Account<'info, RetirementReceipt>>,
+    #[account(mut)]
+    /// CHECK: The bubblegum program derives and checks it from the merkle tree.
+    /// Only required when burn receipts are enabled
+    pub receipt_tree_config: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    /// CHECK: Checked against the state when minting. Only required when burn receipts are enabled
+    pub receipt_merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked against the state when minting. Only required when burn receipts are enabled
+    pub receipt_vault: Option<UncheckedAccount<'info>>,
+    /// CHECK: The noop program used by the compression program, checked by bubblegum.
+    /// Only required when burn receipts are enabled
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    pub compression_program: Option<Program<'info, SplAccountCompression>>,
+    pub bubblegum_program: Option<Program<'info, MplBubblegum>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SwapAndBurn<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        has_one = treasury,
+        has_one = mint,
+        has_one = swap_program,
+        has_one = swap_pool,
+        has_one = sol_usd_price_feed,
+        has_one = nct_usd_price_feed,
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        mut,
+        seeds = [YIELD_ACCOUNT, state.key().as_ref()],
+        bump = state.yield_account_bump,
+    )]
+    pub yield_account: SystemAccount<'info>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = treasury.key() == state.treasury.key(),
+    )]
+    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        token::mint = native_mint::ID,
+        token::authority = state,
+    )]
+    /// A wrapped SOL account owned by the state, used as the swap input
+    pub wrapped_sol_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = state,
+    )]
+    /// A token account owned by the state, receiving the swap output before it is burned
+    pub purchased_token_account: Account<'info, TokenAccount>,
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount)]
+    /// CHECK: A Switchboard V2 aggregator, parsed in utils::switchboard
+    pub sol_usd_price_feed: UncheckedAccount<'info>,
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidSwitchboardAccount)]
+    /// CHECK: A Switchboard V2 aggregator, parsed in utils::switchboard
+    pub nct_usd_price_feed: UncheckedAccount<'info>,
+    #[account(executable)]
+    /// CHECK: constraints checked in state
+    pub swap_program: UncheckedAccount<'info>,
+    #[account(owner = swap_program.key())]
+    /// CHECK: constraints checked in state
+    pub swap_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the swap program
+    pub swap_pool_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_source: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_destination: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_fee_account: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = retirement_vault.key() == state.retirement_vault,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_vault: Option<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        space = RetirementReceipt::SPACE,
+        seeds = [RETIREMENT_RECEIPT, state.key().as_ref(), state.total_retirements.to_le_bytes().as_ref()],
+        bump,
+    )]
+    /// Required when the state retires to a vault, and refused when it burns
+    pub retirement_receipt: Option<Account<'info, RetirementReceipt>>,
+    #[account(mut)]
+    /// CHECK: The bubblegum program derives and checks it from the merkle tree.
+    /// Only required when burn receipts are enabled
+    pub receipt_tree_config: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    /// CHECK: Checked against the state when minting. Only required when burn receipts are enabled
+    pub receipt_merkle_tree: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked against the state when minting. Only required when burn receipts are enabled
+    pub receipt_vault: Option<UncheckedAccount<'info>>,
+    /// CHECK: The noop program used by the compression program, checked by bubblegum.
+    /// Only required when burn receipts are enabled
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+    pub compression_program: Option<Program<'info, SplAccountCompression>>,
+    pub bubblegum_program: Option<Program<'info, MplBubblegum>>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
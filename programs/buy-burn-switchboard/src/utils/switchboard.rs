@@ -1,35 +1,278 @@
+use crate::utils::errors::ErrorCode;
 use anchor_lang::prelude::*;
-use std::convert::TryInto;
-use switchboard_solana::{AggregatorAccountData, SwitchboardDecimal};
 
-fn get_price_from_feed(
-    price_feed: &AccountLoader<AggregatorAccountData>,
-    max_staleness: u64,
-) -> Result<f64> {
-    // deserialize account info
-    let feed = price_feed.load()?;
+// the Switchboard V2 oracle program, owner of all aggregator (feed) accounts
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+// first 8 bytes of SHA256("account:AggregatorAccountData")
+const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Byte offsets into the (packed) AggregatorAccountData account, including the discriminator.
+// Only the fields needed to read a price are parsed, so we do not depend on the Switchboard SDK.
+const MIN_ORACLE_RESULTS_OFFSET: usize = 236;
+// fields of `latest_confirmed_round`
+const NUM_SUCCESS_OFFSET: usize = 341;
+const ROUND_OPEN_TIMESTAMP_OFFSET: usize = 358;
+const RESULT_OFFSET: usize = 366;
+const STD_DEVIATION_OFFSET: usize = 386;
+const RESOLUTION_MODE_OFFSET: usize = 3712;
+const AGGREGATOR_MIN_SIZE: usize = RESOLUTION_MODE_OFFSET + 1;
 
-    // check if feed is stake
-    feed.check_staleness(Clock::get().unwrap().unix_timestamp, max_staleness as i64)?;
+// resolution_mode value for sliding window feeds, whose result is always usable
+const MODE_SLIDING_RESOLUTION: u8 = 1;
+
+// the maximum standard deviation of oracle responses accepted (+/- $0.80)
+const MAX_CONFIDENCE_INTERVAL: f64 = 0.80;
+
+/// The latest confirmed round of a Switchboard V2 aggregator
+struct AggregatorRound {
+    min_oracle_results: u32,
+    num_success: u32,
+    round_open_timestamp: i64,
+    result: f64,
+    std_deviation: f64,
+    sliding_resolution: bool,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+// a SwitchboardDecimal is an i128 mantissa followed by a u32 scale
+fn read_decimal(data: &[u8], offset: usize) -> f64 {
+    let mantissa = i128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+    let scale = read_u32(data, offset + 16);
+    mantissa as f64 / 10_f64.powi(scale as i32)
+}
+
+impl AggregatorRound {
+    fn load(price_feed: &AccountInfo) -> Result<Self> {
+        if price_feed.owner != &SWITCHBOARD_PROGRAM_ID {
+            return Err(ErrorCode::InvalidSwitchboardAccount.into());
+        }
+
+        let data = price_feed.try_borrow_data()?;
+        if data.len() < AGGREGATOR_MIN_SIZE || data[..8] != AGGREGATOR_DISCRIMINATOR {
+            return Err(ErrorCode::InvalidSwitchboardAccount.into());
+        }
+
+        Ok(Self {
+            min_oracle_results: read_u32(&data, MIN_ORACLE_RESULTS_OFFSET),
+            num_success: read_u32(&data, NUM_SUCCESS_OFFSET),
+            round_open_timestamp: read_i64(&data, ROUND_OPEN_TIMESTAMP_OFFSET),
+            result: read_decimal(&data, RESULT_OFFSET),
+            std_deviation: read_decimal(&data, STD_DEVIATION_OFFSET),
+            sliding_resolution: data[RESOLUTION_MODE_OFFSET] == MODE_SLIDING_RESOLUTION,
+        })
+    }
+}
+
+fn get_price_from_feed(price_feed: &AccountInfo, max_staleness: u64, now: i64) -> Result<f64> {
+    let round = AggregatorRound::load(price_feed)?;
+
+    // check if feed is stale
+    let staleness = now - round.round_open_timestamp;
+    if staleness > max_staleness as i64 {
+        msg!("Feed has not been updated in {} seconds!", staleness);
+        return Err(ErrorCode::StaleFeed.into());
+    }
 
     // check if feed exceeds a confidence interval of +/i $0.80
-    feed.check_confidence_interval(SwitchboardDecimal::from_f64(0.80))?;
+    if round.std_deviation > MAX_CONFIDENCE_INTERVAL {
+        return Err(ErrorCode::ConfidenceIntervalExceeded.into());
+    }
+
+    // check the round had enough oracle responses
+    if !round.sliding_resolution && round.min_oracle_results > round.num_success {
+        return Err(ErrorCode::InvalidAggregatorRound.into());
+    }
+
+    Ok(round.result)
+}
 
-    // get result
-    feed.get_result()?.try_into()
+pub struct LatestPrice {
+    pub sol_usd: f64,
+    pub nct_usd: f64,
+    // the NCT price in SOL
+    pub nct_sol: f64,
 }
 
-// returns the NCT price in SOL
+impl LatestPrice {
+    // Amount is in lamports (9 dp)
+    // We need to convert to the token amount in minor units
+    // token amount = lamports / (10^(9-decimals)) * price
+    // Note, this works even if decimals > 9
+    pub fn token_amount(&self, lamports: u64, decimals: u8) -> u64 {
+        let token_decimal_denominator = (10_f64).powi(9_i32 - decimals as i32);
+        (lamports as f64 / (token_decimal_denominator * self.nct_sol)) as u64
+    }
+}
+
+// returns the NCT price in SOL at unix time `now`, along with the feed values it was derived from
 pub fn get_latest_price(
-    sol_usd_price_feed: &AccountLoader<AggregatorAccountData>,
-    nct_usd_price_feed: &AccountLoader<AggregatorAccountData>,
+    sol_usd_price_feed: &AccountInfo,
+    nct_usd_price_feed: &AccountInfo,
     max_staleness: u64,
-) -> Result<f64> {
-    let sol_usd_price = get_price_from_feed(sol_usd_price_feed, max_staleness)?;
-    let nct_usd_price = get_price_from_feed(nct_usd_price_feed, max_staleness)?;
+    now: i64,
+) -> Result<LatestPrice> {
+    let sol_usd_price = get_price_from_feed(sol_usd_price_feed, max_staleness, now)?;
+    let nct_usd_price = get_price_from_feed(nct_usd_price_feed, max_staleness, now)?;
 
     msg!("sol_usd_price: {}", sol_usd_price);
     msg!("nct_usd_price: {}", nct_usd_price);
 
-    Ok(nct_usd_price / sol_usd_price)
+    Ok(LatestPrice {
+        sol_usd: sol_usd_price,
+        nct_usd: nct_usd_price,
+        nct_sol: nct_usd_price / sol_usd_price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+
+    // the feeds the tests run against, as dumped by `solana account --output json`
+    const SOL_USD_FIXTURE: &str =
+        include_str!("../../../../packages/tests/fixtures/switchboard_sol_usd.json");
+    const NCT_USD_FIXTURE: &str =
+        include_str!("../../../../packages/tests/fixtures/switchboard_nct_usd.json");
+
+    // when the fixtures' latest rounds opened
+    const SOL_USD_ROUND_OPEN: i64 = 1679576405;
+    const NCT_USD_ROUND_OPEN: i64 = 1679572328;
+
+    struct Feed {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    impl Feed {
+        fn load(fixture: &str) -> Self {
+            let fixture: serde_json::Value = serde_json::from_str(fixture).unwrap();
+            let account = &fixture["account"];
+            Self {
+                key: fixture["pubkey"].as_str().unwrap().parse().unwrap(),
+                owner: account["owner"].as_str().unwrap().parse().unwrap(),
+                lamports: account["lamports"].as_u64().unwrap(),
+                data: base64::engine::general_purpose::STANDARD
+                    .decode(account["data"][0].as_str().unwrap())
+                    .unwrap(),
+            }
+        }
+
+        fn account_info(&mut self) -> AccountInfo<'_> {
+            AccountInfo::new(
+                &self.key,
+                false,
+                false,
+                &mut self.lamports,
+                &mut self.data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+
+        fn price(&mut self, max_staleness: u64, now: i64) -> Result<f64> {
+            get_price_from_feed(&self.account_info(), max_staleness, now)
+        }
+    }
+
+    #[test]
+    fn reads_the_latest_round_of_each_feed() {
+        let mut sol_usd = Feed::load(SOL_USD_FIXTURE);
+        let mut nct_usd = Feed::load(NCT_USD_FIXTURE);
+        let now = SOL_USD_ROUND_OPEN;
+        let max_staleness = (SOL_USD_ROUND_OPEN - NCT_USD_ROUND_OPEN) as u64;
+
+        let latest_price = get_latest_price(
+            &sol_usd.account_info(),
+            &nct_usd.account_info(),
+            max_staleness,
+            now,
+        )
+        .unwrap();
+        let assert_close = |actual: f64, expected: f64| {
+            assert!(
+                (actual - expected).abs() < 1e-12,
+                "{} != {}",
+                actual,
+                expected
+            )
+        };
+        assert_close(latest_price.sol_usd, 21.7042154);
+        assert_close(latest_price.nct_usd, 2.0475649887674585);
+        assert_close(latest_price.nct_sol, 2.0475649887674585 / 21.7042154);
+
+        // 1 SOL buys 10.6 tokens of 6 decimals
+        assert_eq!(latest_price.token_amount(1_000_000_000, 6), 10_600_012);
+    }
+
+    #[test]
+    fn rejects_a_round_older_than_the_staleness_threshold() {
+        let mut nct_usd = Feed::load(NCT_USD_FIXTURE);
+
+        assert!(nct_usd.price(60, NCT_USD_ROUND_OPEN + 60).is_ok());
+        assert_eq!(
+            nct_usd.price(60, NCT_USD_ROUND_OPEN + 61).unwrap_err(),
+            ErrorCode::StaleFeed.into()
+        );
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_aggregators() {
+        let mut sol_usd = Feed::load(SOL_USD_FIXTURE);
+        sol_usd.owner = Pubkey::new_unique();
+        assert_eq!(
+            sol_usd.price(60, SOL_USD_ROUND_OPEN).unwrap_err(),
+            ErrorCode::InvalidSwitchboardAccount.into()
+        );
+
+        let mut truncated = Feed::load(SOL_USD_FIXTURE);
+        truncated.data.truncate(RESOLUTION_MODE_OFFSET);
+        assert_eq!(
+            truncated.price(60, SOL_USD_ROUND_OPEN).unwrap_err(),
+            ErrorCode::InvalidSwitchboardAccount.into()
+        );
+
+        let mut other_account = Feed::load(SOL_USD_FIXTURE);
+        other_account.data[0] ^= 1;
+        assert_eq!(
+            other_account.price(60, SOL_USD_ROUND_OPEN).unwrap_err(),
+            ErrorCode::InvalidSwitchboardAccount.into()
+        );
+    }
+
+    #[test]
+    fn rejects_rounds_that_are_not_confident() {
+        // a standard deviation of $1, a mantissa of 1 at scale 0
+        let mut uncertain = Feed::load(SOL_USD_FIXTURE);
+        uncertain.data[STD_DEVIATION_OFFSET..STD_DEVIATION_OFFSET + 20].fill(0);
+        uncertain.data[STD_DEVIATION_OFFSET] = 1;
+        assert_eq!(
+            uncertain.price(60, SOL_USD_ROUND_OPEN).unwrap_err(),
+            ErrorCode::ConfidenceIntervalExceeded.into()
+        );
+
+        // fewer oracle responses than the feed requires
+        let mut unresolved = Feed::load(SOL_USD_FIXTURE);
+        unresolved.data[NUM_SUCCESS_OFFSET..NUM_SUCCESS_OFFSET + 4]
+            .copy_from_slice(&1_u32.to_le_bytes());
+        assert_eq!(
+            unresolved.price(60, SOL_USD_ROUND_OPEN).unwrap_err(),
+            ErrorCode::InvalidAggregatorRound.into()
+        );
+
+        // unless the feed resolves with a sliding window
+        unresolved.data[RESOLUTION_MODE_OFFSET] = MODE_SLIDING_RESOLUTION;
+        assert!(unresolved.price(60, SOL_USD_ROUND_OPEN).is_ok());
+    }
 }
@@ -1,33 +1,13 @@
+use crate::utils::errors::ErrorCode;
 use crate::utils::seeds::{STATE, YIELD_ACCOUNT};
-use crate::utils::state::State;
+use crate::utils::state::{RetirementReceipt, State};
 use anchor_lang::prelude::*;
-use anchor_spl::{token, token::Mint, token::TokenAccount};
-use solana_program::system_instruction;
+use anchor_spl::{token::Mint, token::TokenAccount};
+use yield_controller_common::retirement::{retire_signed, RetirementAccounts};
+use yield_controller_common::token::wrap_native_signed;
+use yield_controller_common::transfer::transfer_native_signed;
 
-pub fn burn<'a>(
-    amount: u64,
-    state_account: &Account<'a, State>,
-    mint: &Account<'a, Mint>,
-    token_account: &Account<'a, TokenAccount>,
-    token_program: &AccountInfo<'a>,
-) -> Result<()> {
-    let seeds = [
-        STATE,
-        state_account.mint.as_ref(),
-        &state_account.index.to_le_bytes(),
-        &[state_account.bump],
-    ];
-
-    let cpi_program = token_program.clone();
-    let accounts = token::Burn {
-        mint: mint.to_account_info(),
-        authority: state_account.to_account_info(),
-        from: token_account.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new(cpi_program, accounts);
-
-    token::burn(cpi_ctx.with_signer(&[&seeds]), amount)
-}
+pub use yield_controller_common::rent::spendable_balance;
 
 pub fn transfer_signed<'a>(
     state_account: &Account<'a, State>,
@@ -35,37 +15,78 @@ pub fn transfer_signed<'a>(
     destination: &AccountInfo<'a>,
     amount: u64,
 ) -> Result<()> {
-    let ix = system_instruction::transfer(&yield_account.key(), &destination.key(), amount);
+    // never take the yield account below its rent-exempt minimum
+    let bump = &[state_account.yield_account_bump][..];
+    let state = state_account.key();
+    let seeds = &[YIELD_ACCOUNT, state.as_ref(), bump][..];
 
+    transfer_native_signed(
+        yield_account,
+        destination,
+        amount,
+        seeds,
+        ErrorCode::InsufficientFundsForTransaction,
+    )
+}
+
+pub fn wrap_native<'a>(
+    amount: u64,
+    state_account: &Account<'a, State>,
+    yield_account: &AccountInfo<'a>,
+    wrapped_sol_account: &Account<'a, TokenAccount>,
+    token_program: &AccountInfo<'a>,
+) -> Result<()> {
     let bump = &[state_account.yield_account_bump][..];
     let state = state_account.key();
     let seeds = &[YIELD_ACCOUNT, state.as_ref(), bump][..];
-    solana_program::program::invoke_signed(
-        &ix,
-        &[yield_account.clone(), destination.clone()],
-        &[seeds],
-    )?;
 
-    Ok(())
+    wrap_native_signed(
+        token_program,
+        yield_account,
+        &wrapped_sol_account.to_account_info(),
+        amount,
+        seeds,
+        ErrorCode::InsufficientFundsForTransaction,
+    )
 }
 
-/*
-pub fn _transfer_token<'a>(
+/// Take `amount` tokens held in `token_account` out of circulation using the state's retirement backend
+pub fn retire<'a>(
     amount: u64,
-    authority: &Account<'a, State>,
-    source: &Account<'a, TokenAccount>,
-    destination: &Account<'a, TokenAccount>,
-    seeds: &[&[u8]],
+    state_account: &mut Account<'a, State>,
+    mint: &Account<'a, Mint>,
+    token_account: &Account<'a, TokenAccount>,
+    retirement_vault: &Option<Account<'a, TokenAccount>>,
+    retirement_receipt: &mut Option<Account<'a, RetirementReceipt>>,
     token_program: &AccountInfo<'a>,
 ) -> Result<()> {
-    let cpi_program = token_program.clone();
-    let accounts = token::Transfer {
-        from: source.to_account_info(),
-        to: destination.to_account_info(),
-        authority: authority.to_account_info(),
+    let accounts = RetirementAccounts {
+        token_program: token_program.clone(),
+        mint: mint.to_account_info(),
+        token_account: token_account.to_account_info(),
+        authority: state_account.to_account_info(),
+        retirement_vault: retirement_vault
+            .as_ref()
+            .map(|retirement_vault| retirement_vault.to_account_info()),
     };
+    let state_mint = state_account.mint;
+    let index_bytes = state_account.index.to_le_bytes();
+    let seeds = [
+        STATE,
+        state_mint.as_ref(),
+        &index_bytes,
+        &[state_account.bump],
+    ];
 
-    let cpi_ctx = CpiContext::new(cpi_program, accounts);
-    token::transfer(cpi_ctx.with_signer(&[seeds]), amount)
+    retire_signed(
+        state_account.retirement_backend,
+        &accounts,
+        retirement_receipt.as_deref_mut(),
+        state_account.retirement_beneficiary,
+        &mut state_account.total_retirements,
+        amount,
+        &seeds,
+        ErrorCode::MissingRetirementAccounts,
+        ErrorCode::UnexpectedRetirementAccounts,
+    )
 }
-*/
@@ -1,2 +1,3 @@
 pub(crate) mod mpl_bubblegum;
+pub(crate) mod mpl_core;
 pub(crate) mod spl_account_compression;
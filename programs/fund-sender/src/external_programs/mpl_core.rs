@@ -0,0 +1,12 @@
+use anchor_lang::prelude::Pubkey;
+
+pub use mpl_core::ID as MPL_CORE_ID;
+
+#[derive(Clone)]
+pub struct MplCore;
+
+impl anchor_lang::Id for MplCore {
+    fn id() -> Pubkey {
+        MPL_CORE_ID
+    }
+}
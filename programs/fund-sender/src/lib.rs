@@ -1,12 +1,28 @@
 #![allow(clippy::result_large_err)]
-use crate::utils::bubblegum::TRANSFER_DISCRIMINATOR;
-use crate::utils::errors::ErrorCode;
+use crate::utils::bubblegum::*;
+use crate::utils::certificate::*;
+use crate::utils::core_asset::*;
+use crate::utils::seeds::{CERTIFICATE_VAULT, INPUT_ACCOUNT};
 use crate::utils::spend::*;
 use crate::utils::state::*;
 use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+use anchor_spl::token::TokenAccount;
+use mpl_bubblegum::utils::get_asset_id;
+use yield_controller_common::swap::*;
+use yield_controller_common::transfer::sweep_excess_lamports;
 mod external_programs;
 mod utils;
 
+// account and argument types, for off-chain clients
+pub use crate::external_programs::mpl_core::MPL_CORE_ID;
+pub use crate::utils::bubblegum::{CNFTCertificate, MAX_CNFT_BATCH_SIZE};
+pub use crate::utils::certificate::{
+    CertificateCollection, CertificateCreator, CertificateMetadata, CertificateUses,
+};
+pub use crate::utils::errors::ErrorCode;
+pub use crate::utils::state::{CertificateRecord, GenericStateInput, LegacyState, State};
+
 declare_id!("sfsH2CVS2SaXwnrGwgTVrG7ytZAxSCsTnW82BvjWTGz");
 
 #[program]
@@ -26,8 +42,31 @@ pub mod fund_sender {
         state.destination_account = state_in.destination_account;
         state.certificate_vault = state_in.certificate_vault;
         state.spend_threshold = state_in.spend_threshold;
+        state.destination_accounts = state_in.destination_accounts;
+        state.destination_proportions = state_in.destination_proportions;
+        state.token_mint = state_in.token_mint;
+        state.token_spend_threshold = state_in.token_spend_threshold;
+        state.swap_program = state_in.swap_program;
+        state.swap_pool = state_in.swap_pool;
+        state.certificate_collection = state_in.certificate_collection;
+        state.certificate_creator = state_in.certificate_creator;
+        state.allowed_certificate_mints = state_in.allowed_certificate_mints;
+        state.quarantine_vault = state_in.quarantine_vault;
+        state.lock_certificates = state_in.lock_certificates;
+        state.certificate_beneficiary = state_in.certificate_beneficiary;
         state.input_account_bump = ctx.bumps.input_account;
         state.total_spent = 0;
+        state.total_tokens_spent = 0;
+        state.total_certificates = 0;
+
+        // make sure any weighted destinations are valid
+        check_destinations(
+            &state.destination_account,
+            &state.destination_accounts,
+            &state.destination_proportions,
+        )?;
+        check_allowed_certificate_mints(&state.allowed_certificate_mints)?;
+        check_certificate_vault(state, false)?;
 
         Ok(())
     }
@@ -35,22 +74,222 @@ pub mod fund_sender {
     pub fn update_state(ctx: Context<UpdateState>, state_in: GenericStateInput) -> Result<()> {
         // update state account parameters
         let state = &mut ctx.accounts.state;
+        let was_locked = state.lock_certificates;
         state.update_authority = state_in.update_authority;
         state.destination_account = state_in.destination_account;
         state.certificate_vault = state_in.certificate_vault;
         state.spend_threshold = state_in.spend_threshold;
+        state.destination_accounts = state_in.destination_accounts;
+        state.destination_proportions = state_in.destination_proportions;
+        state.token_mint = state_in.token_mint;
+        state.token_spend_threshold = state_in.token_spend_threshold;
+        state.swap_program = state_in.swap_program;
+        state.swap_pool = state_in.swap_pool;
+        state.certificate_collection = state_in.certificate_collection;
+        state.certificate_creator = state_in.certificate_creator;
+        state.allowed_certificate_mints = state_in.allowed_certificate_mints;
+        state.quarantine_vault = state_in.quarantine_vault;
+        state.lock_certificates = state_in.lock_certificates;
+        state.certificate_beneficiary = state_in.certificate_beneficiary;
+
+        // make sure any weighted destinations are valid
+        check_destinations(
+            &state.destination_account,
+            &state.destination_accounts,
+            &state.destination_proportions,
+        )?;
+        check_allowed_certificate_mints(&state.allowed_certificate_mints)?;
+        check_certificate_vault(state, was_locked)?;
 
         Ok(())
     }
 
-    pub fn send_from_state(ctx: Context<SendFromState>) -> Result<()> {
-        let state = &ctx.accounts.state;
-        let amount = state.get_lamports();
+    /// Upgrade a state registered with the original layout, which cannot be loaded (or updated)
+    /// until it is. The state keeps its address, so its input account and funds are unchanged.
+    /// Settings added since then are left disabled, to be set with `update_state`.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let state_info = ctx.accounts.state.to_account_info();
+        let legacy_state = LegacyState::try_from_account_info(&state_info)?;
+        require_keys_eq!(
+            legacy_state.update_authority,
+            ctx.accounts.payer.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let state = State {
+            sunrise_state: legacy_state.sunrise_state,
+            update_authority: legacy_state.update_authority,
+            destination_name: legacy_state.destination_name,
+            destination_account: legacy_state.destination_account,
+            certificate_vault: legacy_state.certificate_vault,
+            spend_threshold: legacy_state.spend_threshold,
+            total_spent: legacy_state.total_spent,
+            input_account_bump: legacy_state.input_account_bump,
+            destination_accounts: vec![],
+            destination_proportions: vec![],
+            token_mint: Pubkey::default(),
+            token_spend_threshold: 0,
+            total_tokens_spent: 0,
+            swap_program: Pubkey::default(),
+            swap_pool: Pubkey::default(),
+            certificate_collection: Pubkey::default(),
+            certificate_creator: Pubkey::default(),
+            allowed_certificate_mints: vec![],
+            quarantine_vault: Pubkey::default(),
+            total_certificates: 0,
+            lock_certificates: false,
+            certificate_vault_bump: 0,
+            certificate_beneficiary: Pubkey::default(),
+        };
 
-        if amount > 0 {
-            **state.to_account_info().try_borrow_mut_lamports()? -= amount;
-            **ctx.accounts.input_account.try_borrow_mut_lamports()? += amount;
+        // grow the state to the current layout, topping up its rent from the payer
+        let new_space = State::space(state.destination_name.len() as u8, 0, 0);
+        let rent_shortfall = Rent::get()?
+            .minimum_balance(new_space)
+            .saturating_sub(state_info.lamports());
+        if rent_shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: state_info.clone(),
+                    },
+                ),
+                rent_shortfall,
+            )?;
         }
+        state_info.realloc(new_space, false)?;
+
+        let mut data = state_info.try_borrow_mut_data()?;
+        state.try_serialize(&mut &mut data[..])?;
+
+        Ok(())
+    }
+
+    /// Rename a destination: `update_state` cannot change `destination_name` as it seeds the state.
+    /// The configuration and totals move to a state under the new name, along with the SOL
+    /// in the old input account and any token balances passed as remaining accounts.
+    /// The old state is closed and its PDAs can no longer sign, so before migrating the caller must
+    /// pass every token account of the old input account, including that of the state's token,
+    /// which are emptied into the new input account's and closed.
+    /// States which have stored or locked certificates cannot be migrated,
+    /// as their certificate vault and records are derived from the state.
+    pub fn migrate_destination<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateDestination<'info>>,
+        new_destination_name: String,
+    ) -> Result<()> {
+        let old_state = &ctx.accounts.old_state;
+        if old_state.lock_certificates {
+            return Err(ErrorCode::CertificatesLocked.into());
+        }
+        if old_state.total_certificates > 0 {
+            return Err(ErrorCode::CertificatesStored.into());
+        }
+        let new_state = &mut ctx.accounts.new_state;
+        new_state.sunrise_state = old_state.sunrise_state;
+        new_state.update_authority = old_state.update_authority;
+        new_state.destination_name = new_destination_name;
+        new_state.destination_account = old_state.destination_account;
+        new_state.certificate_vault = old_state.certificate_vault;
+        new_state.spend_threshold = old_state.spend_threshold;
+        new_state.total_spent = old_state.total_spent;
+        new_state.input_account_bump = ctx.bumps.new_input_account;
+        new_state.destination_accounts = old_state.destination_accounts.clone();
+        new_state.destination_proportions = old_state.destination_proportions.clone();
+        new_state.token_mint = old_state.token_mint;
+        new_state.token_spend_threshold = old_state.token_spend_threshold;
+        new_state.total_tokens_spent = old_state.total_tokens_spent;
+        // states with stored certificates are not migrated, so the new state's ledger starts empty
+        new_state.total_certificates = 0;
+        new_state.swap_program = old_state.swap_program;
+        new_state.swap_pool = old_state.swap_pool;
+        new_state.certificate_collection = old_state.certificate_collection;
+        new_state.certificate_creator = old_state.certificate_creator;
+        new_state.allowed_certificate_mints = old_state.allowed_certificate_mints.clone();
+        new_state.quarantine_vault = old_state.quarantine_vault;
+        new_state.certificate_beneficiary = old_state.certificate_beneficiary;
+
+        // move token balances held by the old input account
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        if !pairs.remainder().is_empty() {
+            return Err(ErrorCode::IncorrectTokenAccountPair.into());
+        }
+        let old_token_mint_account = &ctx.accounts.old_token_mint_account;
+        if old_token_mint_account.owner == &anchor_spl::token::ID
+            && TokenAccount::try_deserialize(&mut &old_token_mint_account.try_borrow_data()?[..])?
+                .amount
+                > 0
+            && !pairs
+                .clone()
+                .any(|pair| pair[0].key == old_token_mint_account.key)
+        {
+            return Err(ErrorCode::TokensNotMigrated.into());
+        }
+        for pair in pairs {
+            let old_token_account = Account::<TokenAccount>::try_from(&pair[0])?;
+            let new_token_account = Account::<TokenAccount>::try_from(&pair[1])?;
+            if old_token_account.owner != ctx.accounts.old_input_account.key()
+                || new_token_account.owner != ctx.accounts.new_input_account.key()
+            {
+                return Err(ErrorCode::IncorrectTokenAccountOwner.into());
+            }
+            if old_token_account.mint != new_token_account.mint {
+                return Err(ErrorCode::IncorrectTokenAccountPair.into());
+            }
+
+            transfer_token(
+                &old_state.key(),
+                &AccountsTokenTransfer {
+                    source: pair[0].clone(),
+                    dest: pair[1].clone(),
+                    authority: ctx.accounts.old_input_account.to_account_info(),
+                },
+                old_token_account.amount,
+                old_state.input_account_bump,
+                &ctx.accounts.token_program,
+            )?;
+            close_token_account(
+                &old_state.key(),
+                &AccountsTokenTransfer {
+                    source: pair[0].clone(),
+                    dest: ctx.accounts.new_input_account.to_account_info(),
+                    authority: ctx.accounts.old_input_account.to_account_info(),
+                },
+                old_state.input_account_bump,
+                &ctx.accounts.token_program,
+            )?;
+        }
+
+        // any funds sent to the old state directly go to the new input account
+        sweep_excess_lamports(
+            &old_state.to_account_info(),
+            &ctx.accounts.new_input_account.to_account_info(),
+        )?;
+
+        empty_input_account(
+            &old_state.key(),
+            &ctx.accounts.old_input_account.to_account_info(),
+            &ctx.accounts.new_input_account.to_account_info(),
+            old_state.input_account_bump,
+        )?;
+
+        msg!(
+            "Migrated destination {} to {}",
+            old_state.destination_name,
+            new_state.destination_name
+        );
+
+        Ok(())
+    }
+
+    pub fn send_from_state(ctx: Context<SendFromState>) -> Result<()> {
+        let state = &ctx.accounts.state;
+        // leave the state its rent, so that it is not closed
+        sweep_excess_lamports(
+            &state.to_account_info(),
+            &ctx.accounts.input_account.to_account_info(),
+        )?;
 
         Ok(())
     }
@@ -59,23 +298,19 @@ pub mod fund_sender {
         ctx: Context<'_, '_, '_, 'info, SendFund<'info>>,
         amount: u64,
     ) -> Result<()> {
-        // send yield to input_accounts with specified proportions
+        // send yield to the destinations with specified proportions
         let state = &mut ctx.accounts.state;
         let input_account = &mut ctx.accounts.input_account;
         let destination_account = &mut ctx.accounts.destination_account;
-        if destination_account.key() != state.destination_account {
-            return Err(ErrorCode::IncorrectDestinationAccount.into());
-        }
 
         if amount >= state.spend_threshold {
-            transfer_native_cpi(
-                &state.key(),
+            send_to_destinations(
+                state,
                 &input_account.to_account_info(),
                 // state.destination_account.to_account_info(), <- this leads to error trait bounds not satisfied, why?
                 &destination_account.to_account_info(),
+                ctx.remaining_accounts,
                 amount,
-                state.input_account_bump,
-                &ctx.accounts.system_program,
             )?;
             state.total_spent += amount;
         } else {
@@ -85,10 +320,136 @@ pub mod fund_sender {
         Ok(())
     }
 
+    /// Send the whole spendable balance of the input account to the destination,
+    /// so that cranks need not query the balance first.
+    pub fn send_all_funds<'info>(ctx: Context<'_, '_, '_, 'info, SendFund<'info>>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let input_account = &ctx.accounts.input_account;
+        let destination_account = &ctx.accounts.destination_account;
+
+        let amount = spendable_balance(&input_account.to_account_info())?;
+        if amount == 0 || amount < state.spend_threshold {
+            return Err(ErrorCode::InsufficientFundsForTransaction.into());
+        }
+
+        msg!("Sending {} lamports", amount);
+        send_to_destinations(
+            state,
+            &input_account.to_account_info(),
+            &destination_account.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        state.total_spent += amount;
+
+        Ok(())
+    }
+
+    /// Forward the whole balance of the state's token in the input account's ATA
+    /// to the destination account's ATA. Weighted destinations apply to SOL only.
+    pub fn send_token_fund(ctx: Context<SendTokenFund>) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let input_token_account = &ctx.accounts.input_token_account;
+
+        let amount = input_token_account.amount;
+        if amount == 0 || amount < state.token_spend_threshold {
+            return Err(ErrorCode::InsufficientFundsForTransaction.into());
+        }
+
+        msg!("Sending {} tokens", amount);
+        transfer_token(
+            &state.key(),
+            &AccountsTokenTransfer {
+                source: input_token_account.to_account_info(),
+                dest: ctx.accounts.destination_token_account.to_account_info(),
+                authority: ctx.accounts.input_account.to_account_info(),
+            },
+            amount,
+            state.input_account_bump,
+            &ctx.accounts.token_program,
+        )?;
+        state.total_tokens_spent += amount;
+
+        Ok(())
+    }
+
+    /// Wrap `amount` lamports from the input account and swap them to the state's token
+    /// through the configured pool, delivering the output to the destination account's ATA.
+    /// Only the update authority can swap, as it chooses the amount and the minimum output.
+    pub fn swap_and_send(
+        ctx: Context<SwapAndSend>,
+        amount: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        let input_account = &ctx.accounts.input_account;
+        let wrapped_sol_account = &ctx.accounts.wrapped_sol_account;
+        let destination_token_account = &mut ctx.accounts.destination_token_account;
+
+        if amount == 0 || amount < state.spend_threshold {
+            return Err(ErrorCode::InsufficientFundsForTransaction.into());
+        }
+
+        wrap_native(
+            &state.key(),
+            &input_account.to_account_info(),
+            &wrapped_sol_account.to_account_info(),
+            amount,
+            state.input_account_bump,
+            &ctx.accounts.token_program,
+        )?;
+
+        let balance_before = destination_token_account.amount;
+
+        msg!("Swapping {} lamports", amount);
+        let state_key = state.key();
+        let seeds = [
+            INPUT_ACCOUNT,
+            state_key.as_ref(),
+            &[state.input_account_bump],
+        ];
+        swap(
+            &SwapAccounts {
+                swap_program: ctx.accounts.swap_program.to_account_info(),
+                pool: ctx.accounts.swap_pool.to_account_info(),
+                pool_authority: ctx.accounts.swap_pool_authority.to_account_info(),
+                user_transfer_authority: input_account.to_account_info(),
+                source: wrapped_sol_account.to_account_info(),
+                pool_source: ctx.accounts.swap_pool_source.to_account_info(),
+                pool_destination: ctx.accounts.swap_pool_destination.to_account_info(),
+                destination: destination_token_account.to_account_info(),
+                pool_mint: ctx.accounts.swap_pool_mint.to_account_info(),
+                pool_fee_account: ctx.accounts.swap_pool_fee_account.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            amount,
+            minimum_amount_out,
+            &[&seeds],
+        )?;
+
+        destination_token_account.reload()?;
+        let amount_out = destination_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(ErrorCode::SlippageExceeded)?;
+        require_gte!(amount_out, minimum_amount_out, ErrorCode::SlippageExceeded);
+
+        msg!("Sent {} tokens", amount_out);
+        state.total_spent += amount;
+        state.total_tokens_spent += amount_out;
+
+        Ok(())
+    }
+
     pub fn store_certificates<'info>(
         ctx: Context<'_, '_, '_, 'info, StoreCertificates<'info>>,
     ) -> Result<()> {
         // send received climate tokens in input_account to a hold account
+        let beneficiary = ctx
+            .accounts
+            .beneficiary
+            .as_ref()
+            .map(|beneficiary| beneficiary.key());
         let state = &mut ctx.accounts.state;
         let input_account = &mut ctx.accounts.input_account;
         let input_token_account = &mut ctx.accounts.input_token_account;
@@ -100,21 +461,255 @@ pub mod fund_sender {
             return Err(ErrorCode::NoCertificatesFound.into());
         }
 
-        transfer_token(
+        let certificate_mint = ctx.accounts.certificate_mint.key();
+        // without its metadata, a certificate only passes the filter if its mint is explicitly allowed
+        let is_valid = match &ctx.accounts.certificate_metadata {
+            Some(metadata) if state.has_certificate_filter() => {
+                let (expected_metadata, _) = Metadata::find_pda(&certificate_mint);
+                if metadata.key() != expected_metadata {
+                    return Err(ErrorCode::IncorrectCertificateMetadata.into());
+                }
+                let verified_collection = metadata
+                    .collection
+                    .as_ref()
+                    .filter(|collection| collection.verified)
+                    .map(|collection| collection.key);
+                let verified_creators: Vec<Pubkey> = metadata
+                    .creators
+                    .iter()
+                    .flatten()
+                    .filter(|creator| creator.verified)
+                    .map(|creator| creator.address)
+                    .collect();
+                state.is_valid_certificate(
+                    &certificate_mint,
+                    verified_collection,
+                    &verified_creators,
+                )
+            }
+            _ => state.is_valid_certificate(&certificate_mint, None, &[]),
+        };
+        if ctx.accounts.certificate_vault.key() != state.certificate_destination(is_valid)? {
+            return Err(ErrorCode::IncorrectHoldAccount.into());
+        }
+
+        transfer_token_checked(
             &state.key(),
             &AccountsTokenTransfer {
                 source: input_token_account.to_account_info(),
                 dest: certificate_vault_ata.to_account_info(),
                 authority: input_account.to_account_info(),
             },
+            ctx.accounts.certificate_mint.to_account_info(),
             amount,
+            ctx.accounts.certificate_mint.decimals,
+            INPUT_ACCOUNT,
             state.input_account_bump,
             &ctx.accounts.token_program,
         )?;
 
+        record_certificate(
+            state,
+            &mut ctx.accounts.certificate_record,
+            certificate_mint,
+            amount,
+            !is_valid,
+            beneficiary,
+        )
+    }
+
+    /// Release certificate tokens from the state's program-owned certificate vault to a beneficiary,
+    /// attributing their ledger record (if passed) to the beneficiary.
+    /// Compressed and Core certificates in the vault cannot be released.
+    pub fn release_certificate(ctx: Context<ReleaseCertificate>, amount: u64) -> Result<()> {
+        let state = &ctx.accounts.state;
+
+        transfer_token_checked(
+            &state.key(),
+            &AccountsTokenTransfer {
+                source: ctx.accounts.certificate_vault_ata.to_account_info(),
+                dest: ctx.accounts.beneficiary_ata.to_account_info(),
+                authority: ctx.accounts.certificate_vault.to_account_info(),
+            },
+            ctx.accounts.certificate_mint.to_account_info(),
+            amount,
+            ctx.accounts.certificate_mint.decimals,
+            CERTIFICATE_VAULT,
+            state.certificate_vault_bump,
+            &ctx.accounts.token_program,
+        )?;
+
+        if let Some(certificate_record) = &mut ctx.accounts.certificate_record {
+            certificate_record.beneficiary = ctx.accounts.beneficiary.key();
+        }
+
+        emit!(CertificateReleased {
+            state: state.key(),
+            certificate: ctx.accounts.certificate_mint.key(),
+            beneficiary: ctx.accounts.beneficiary.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn release_core_certificate(ctx: Context<ReleaseCoreCertificate>) -> Result<()> {
+        let state = &ctx.accounts.state;
+        let certificate_asset = &ctx.accounts.certificate_asset;
+
+        let asset = CoreAsset::try_from_account(certificate_asset)?;
+        if asset.collection
+            != ctx
+                .accounts
+                .certificate_collection
+                .as_ref()
+                .map(|c| c.key())
+        {
+            return Err(ErrorCode::IncorrectCoreCollection.into());
+        }
+
+        transfer_core_asset(
+            &state.key(),
+            &AccountsCoreTransfer {
+                asset: certificate_asset.to_account_info(),
+                collection: ctx
+                    .accounts
+                    .certificate_collection
+                    .as_ref()
+                    .map(|collection| collection.to_account_info()),
+                payer: ctx.accounts.payer.to_account_info(),
+                owner: ctx.accounts.certificate_vault.to_account_info(),
+                new_owner: ctx.accounts.beneficiary.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                core_program: ctx.accounts.core_program.to_account_info(),
+            },
+            CERTIFICATE_VAULT,
+            state.certificate_vault_bump,
+        )?;
+
+        if let Some(certificate_record) = &mut ctx.accounts.certificate_record {
+            certificate_record.beneficiary = ctx.accounts.beneficiary.key();
+        }
+
+        emit!(CertificateReleased {
+            state: state.key(),
+            certificate: certificate_asset.key(),
+            beneficiary: ctx.accounts.beneficiary.key(),
+            amount: 1,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn release_cnft_certificate<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseCNFTCertificate<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        let accounts_cnft_transfer = ctx.accounts.accounts_cnft_transfer();
+        let state = &ctx.accounts.state;
+
+        let asset_id = get_asset_id(&ctx.accounts.merkle_tree.key(), nonce);
+        if let Some(certificate_record) = &ctx.accounts.certificate_record {
+            if certificate_record.certificate != asset_id {
+                return Err(ErrorCode::IncorrectCertificateRecord.into());
+            }
+        }
+
+        // the vault is the leaf's owner and delegate, as bubblegum resets the delegate on transfer
+        transfer_cnft(
+            &state.key(),
+            &accounts_cnft_transfer,
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+            ctx.remaining_accounts,
+            CERTIFICATE_VAULT,
+            state.certificate_vault_bump,
+        )?;
+
+        if let Some(certificate_record) = &mut ctx.accounts.certificate_record {
+            certificate_record.beneficiary = ctx.accounts.beneficiary.key();
+        }
+
+        emit!(CertificateReleased {
+            state: state.key(),
+            certificate: asset_id,
+            beneficiary: ctx.accounts.beneficiary.key(),
+            amount: 1,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
         Ok(())
     }
 
+    pub fn store_core_certificate(ctx: Context<StoreCoreCertificate>) -> Result<()> {
+        let beneficiary = ctx
+            .accounts
+            .beneficiary
+            .as_ref()
+            .map(|beneficiary| beneficiary.key());
+        let state = &mut ctx.accounts.state;
+        let certificate_asset = &ctx.accounts.certificate_asset;
+
+        let asset = CoreAsset::try_from_account(certificate_asset)?;
+        if asset.owner != ctx.accounts.input_account.key() {
+            return Err(ErrorCode::IncorrectTokenAccountOwner.into());
+        }
+        if asset.collection
+            != ctx
+                .accounts
+                .certificate_collection
+                .as_ref()
+                .map(|c| c.key())
+        {
+            return Err(ErrorCode::IncorrectCoreCollection.into());
+        }
+
+        // creators of Core assets are held in their royalties plugin, which is not read here,
+        // so Core certificates can only be filtered by collection or asset address
+        let is_valid = state.is_valid_certificate(&certificate_asset.key(), asset.collection, &[]);
+        if ctx.accounts.certificate_vault.key() != state.certificate_destination(is_valid)? {
+            return Err(ErrorCode::IncorrectHoldAccount.into());
+        }
+
+        transfer_core_asset(
+            &state.key(),
+            &AccountsCoreTransfer {
+                asset: certificate_asset.to_account_info(),
+                collection: ctx
+                    .accounts
+                    .certificate_collection
+                    .as_ref()
+                    .map(|collection| collection.to_account_info()),
+                payer: ctx.accounts.payer.to_account_info(),
+                owner: ctx.accounts.input_account.to_account_info(),
+                new_owner: ctx.accounts.certificate_vault.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                core_program: ctx.accounts.core_program.to_account_info(),
+            },
+            INPUT_ACCOUNT,
+            state.input_account_bump,
+        )?;
+
+        record_certificate(
+            state,
+            &mut ctx.accounts.certificate_record,
+            certificate_asset.key(),
+            1,
+            !is_valid,
+            beneficiary,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn store_cnft_certificate<'info>(
         ctx: Context<'_, '_, '_, 'info, StoreCNFTCertificates<'info>>,
         root: [u8; 32],
@@ -122,64 +717,124 @@ pub mod fund_sender {
         creator_hash: [u8; 32],
         nonce: u64,
         index: u32,
+        creators: Vec<CertificateCreator>,
+        metadata: Option<CertificateMetadata>,
     ) -> Result<()> {
+        let beneficiary = ctx
+            .accounts
+            .beneficiary
+            .as_ref()
+            .map(|beneficiary| beneficiary.key());
+        let accounts_cnft_transfer = ctx.accounts.accounts_cnft_transfer();
         let state = &mut ctx.accounts.state;
 
-        let mut accounts: Vec<solana_program::instruction::AccountMeta> = vec![
-            AccountMeta::new_readonly(ctx.accounts.tree_authority.key(), false),
-            AccountMeta::new_readonly(ctx.accounts.input_account.key(), true),
-            AccountMeta::new_readonly(ctx.accounts.input_account.key(), false),
-            AccountMeta::new_readonly(ctx.accounts.certificate_vault.key(), false),
-            AccountMeta::new(ctx.accounts.merkle_tree.key(), false),
-            AccountMeta::new_readonly(ctx.accounts.log_wrapper.key(), false),
-            AccountMeta::new_readonly(ctx.accounts.compression_program.key(), false),
-            AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
-        ];
+        let asset_id = get_asset_id(&ctx.accounts.merkle_tree.key(), nonce);
+        let is_valid = state.is_valid_cnft_certificate(
+            &asset_id,
+            data_hash,
+            creator_hash,
+            &creators,
+            metadata.as_ref(),
+        )?;
+        if ctx.accounts.certificate_vault.key() != state.certificate_destination(is_valid)? {
+            return Err(ErrorCode::IncorrectHoldAccount.into());
+        }
 
-        let mut data: Vec<u8> = vec![];
-        data.extend(TRANSFER_DISCRIMINATOR);
-        data.extend(root);
-        data.extend(data_hash);
-        data.extend(creator_hash);
-        data.extend(nonce.to_le_bytes());
-        data.extend(index.to_le_bytes());
-
-        let mut account_infos: Vec<AccountInfo> = vec![
-            ctx.accounts.tree_authority.to_account_info(),
-            ctx.accounts.input_account.to_account_info(),
-            ctx.accounts.input_account.to_account_info(),
-            ctx.accounts.certificate_vault.to_account_info(),
-            ctx.accounts.merkle_tree.to_account_info(),
-            ctx.accounts.log_wrapper.to_account_info(),
-            ctx.accounts.compression_program.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ];
+        transfer_cnft(
+            &state.key(),
+            &accounts_cnft_transfer,
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+            ctx.remaining_accounts,
+            INPUT_ACCOUNT,
+            state.input_account_bump,
+        )?;
 
-        // add "accounts" (hashes) that make up the merkle proof
-        for acc in ctx.remaining_accounts.iter() {
-            accounts.push(AccountMeta::new_readonly(acc.key(), false));
-            account_infos.push(acc.to_account_info());
-        }
-
-        let state_bytes = state.key().to_bytes();
-        let bump_bytes = &[state.input_account_bump];
-        let seeds = &[
-            crate::utils::seeds::INPUT_ACCOUNT,
-            &state_bytes[..],
-            bump_bytes,
-        ][..];
-        let signer_seeds = &[seeds];
-
-        msg!("manual cpi call");
-        solana_program::program::invoke_signed(
-            &solana_program::instruction::Instruction {
-                program_id: ctx.accounts.bubblegum_program.key(),
-                accounts,
-                data,
-            },
-            &account_infos[..],
-            signer_seeds,
+        record_certificate(
+            state,
+            &mut ctx.accounts.certificate_record,
+            asset_id,
+            1,
+            !is_valid,
+            beneficiary,
         )
-        .map_err(Into::into)
+    }
+
+    pub fn store_cnft_certificates<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StoreCNFTCertificateBatch<'info>>,
+        certificates: Vec<CNFTCertificate>,
+    ) -> Result<()> {
+        if certificates.is_empty() {
+            return Err(ErrorCode::NoCertificatesFound.into());
+        }
+        if certificates.len() > MAX_CNFT_BATCH_SIZE {
+            return Err(ErrorCode::TooManyCertificates.into());
+        }
+        let accounts_cnft_transfer = ctx.accounts.accounts_cnft_transfer();
+        let beneficiary = ctx
+            .accounts
+            .beneficiary
+            .as_ref()
+            .map(|beneficiary| beneficiary.key());
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let state = &mut ctx.accounts.state;
+
+        // a certificate record per certificate, followed by the proofs of each certificate.
+        // The proofs can all be against the same root, as the concurrent merkle tree fast-forwards
+        // them through the earlier transfers of the batch (up to the tree's max buffer size)
+        let (certificate_records, mut proofs) = ctx
+            .remaining_accounts
+            .split_at(certificates.len().min(ctx.remaining_accounts.len()));
+        if certificate_records.len() != certificates.len() {
+            return Err(ErrorCode::IncorrectCertificateRecord.into());
+        }
+
+        for (certificate, certificate_record) in certificates.iter().zip(certificate_records) {
+            let proof_len = certificate.proof_len as usize;
+            if proofs.len() < proof_len {
+                return Err(ErrorCode::InsufficientProofAccounts.into());
+            }
+            let (proof, rest) = proofs.split_at(proof_len);
+            proofs = rest;
+
+            let asset_id =
+                get_asset_id(&accounts_cnft_transfer.merkle_tree.key(), certificate.nonce);
+            let is_valid = state.is_valid_cnft_certificate(
+                &asset_id,
+                certificate.data_hash,
+                certificate.creator_hash,
+                &certificate.creators,
+                certificate.metadata.as_ref(),
+            )?;
+            if accounts_cnft_transfer.new_leaf_owner.key()
+                != state.certificate_destination(is_valid)?
+            {
+                return Err(ErrorCode::IncorrectHoldAccount.into());
+            }
+
+            transfer_cnft(
+                &state.key(),
+                &accounts_cnft_transfer,
+                certificate.root,
+                certificate.data_hash,
+                certificate.creator_hash,
+                certificate.nonce,
+                certificate.index,
+                proof,
+                INPUT_ACCOUNT,
+                state.input_account_bump,
+            )?;
+
+            let mut record =
+                init_certificate_record(state, &payer, certificate_record, &system_program)?;
+            record_certificate(state, &mut record, asset_id, 1, !is_valid, beneficiary)?;
+            record.exit(&crate::ID)?;
+        }
+
+        Ok(())
     }
 }
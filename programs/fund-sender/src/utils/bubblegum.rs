@@ -1,2 +1,53 @@
-// first 8 bytes of SHA256("global:transfer")
-pub const TRANSFER_DISCRIMINATOR: &[u8; 8] = &[163, 52, 200, 231, 140, 3, 69, 186];
+use crate::utils::certificate::{CertificateCreator, CertificateMetadata};
+use anchor_lang::prelude::*;
+use yield_controller_common::bubblegum::transfer_cnft_signed;
+pub use yield_controller_common::bubblegum::AccountsCNFTTransfer;
+
+// the most compressed certificates that can be stored in one instruction
+pub const MAX_CNFT_BATCH_SIZE: usize = 8;
+
+/// A compressed certificate leaf, as returned by the DAS API
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CNFTCertificate {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+    pub creators: Vec<CertificateCreator>,
+    // the leaf's metadata, to check the certificate's collection against the data hash
+    pub metadata: Option<CertificateMetadata>,
+    // the number of proof accounts for this leaf,
+    // which is the tree depth minus the canopy depth of the tree
+    pub proof_len: u8,
+}
+
+/// Transfer a compressed NFT owned by a state PDA, with `proof` being the (canopy-truncated)
+/// merkle proof of the leaf. `owner_seed` is the seed of the state PDA owning the leaf
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_cnft<'a>(
+    state: &Pubkey,
+    accounts_cnft_transfer: &AccountsCNFTTransfer<'a>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+    proof: &[AccountInfo<'a>],
+    owner_seed: &[u8],
+    owner_bump: u8,
+) -> Result<()> {
+    let state_bytes = state.to_bytes();
+    let bump_bytes = &[owner_bump];
+    let seeds = &[owner_seed, &state_bytes[..], bump_bytes][..];
+    transfer_cnft_signed(
+        accounts_cnft_transfer,
+        root,
+        data_hash,
+        creator_hash,
+        nonce,
+        index,
+        proof,
+        seeds,
+    )
+}
@@ -0,0 +1,279 @@
+use crate::utils::errors::ErrorCode;
+use crate::utils::seeds::{CERTIFICATE_RECORD, CERTIFICATE_VAULT};
+use crate::utils::state::{CertificateRecord, State};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use mpl_bubblegum::hash::{hash_creators, hash_metadata};
+use mpl_bubblegum::types::{Creator, MetadataArgs};
+
+// the most mints (or cNFT asset ids) a state can allow as certificates
+pub const MAX_ALLOWED_CERTIFICATE_MINTS: usize = 8;
+
+/// A creator of a compressed certificate, as hashed into the leaf's creator hash
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CertificateCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+impl From<&CertificateCreator> for mpl_bubblegum::types::Creator {
+    fn from(creator: &CertificateCreator) -> Self {
+        Self {
+            address: creator.address,
+            verified: creator.verified,
+            share: creator.share,
+        }
+    }
+}
+
+/// The collection of a compressed certificate, as in its leaf metadata
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CertificateCollection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+/// The uses of a compressed certificate, as in its leaf metadata
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CertificateUses {
+    pub use_method: u8,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+/// The metadata of a compressed certificate, with the same layout as Bubblegum's `MetadataArgs`
+/// (enums are passed as their variant index), so that it can be checked against the leaf's data hash
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CertificateMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<CertificateCollection>,
+    pub uses: Option<CertificateUses>,
+    pub token_program_version: u8,
+    pub creators: Vec<CertificateCreator>,
+}
+
+impl CertificateMetadata {
+    /// The data hash of a leaf with this metadata
+    pub fn data_hash(&self) -> Result<[u8; 32]> {
+        let metadata_args = MetadataArgs::try_from_slice(&self.try_to_vec()?)
+            .map_err(|_| ErrorCode::InvalidCertificateMetadata)?;
+        hash_metadata(&metadata_args).map_err(|_| ErrorCode::InvalidCertificateMetadata.into())
+    }
+}
+
+impl State {
+    /// States configured before certificate filtering accept any certificate
+    pub fn has_certificate_filter(&self) -> bool {
+        self.certificate_collection != Pubkey::default()
+            || self.certificate_creator != Pubkey::default()
+            || !self.allowed_certificate_mints.is_empty()
+    }
+
+    /// A certificate matches if it is an allowed mint (or asset id), belongs to the verified
+    /// collection, or has the expected verified creator.
+    pub fn is_valid_certificate(
+        &self,
+        mint: &Pubkey,
+        verified_collection: Option<Pubkey>,
+        verified_creators: &[Pubkey],
+    ) -> bool {
+        if !self.has_certificate_filter() || self.allowed_certificate_mints.contains(mint) {
+            return true;
+        }
+        if self.certificate_collection != Pubkey::default()
+            && verified_collection == Some(self.certificate_collection)
+        {
+            return true;
+        }
+        self.certificate_creator != Pubkey::default()
+            && verified_creators.contains(&self.certificate_creator)
+    }
+
+    /// Check a compressed certificate against the filter. The creators are only needed
+    /// (and checked against the leaf's creator hash) when the state filters certificates.
+    /// The leaf only commits to its collection through the data hash, so its collection is only
+    /// checked if its metadata is passed, which is then checked against the data hash.
+    pub fn is_valid_cnft_certificate(
+        &self,
+        asset_id: &Pubkey,
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        creators: &[CertificateCreator],
+        metadata: Option<&CertificateMetadata>,
+    ) -> Result<bool> {
+        let verified_collection = match metadata {
+            Some(metadata) => {
+                if metadata.data_hash()? != data_hash {
+                    return Err(ErrorCode::DataHashMismatch.into());
+                }
+                metadata
+                    .collection
+                    .as_ref()
+                    .filter(|collection| collection.verified)
+                    .map(|collection| collection.key)
+            }
+            None => None,
+        };
+        if !self.has_certificate_filter() {
+            return Ok(true);
+        }
+        let leaf_creators: Vec<Creator> = creators.iter().map(Into::into).collect();
+        if hash_creators(&leaf_creators) != creator_hash {
+            return Err(ErrorCode::CreatorHashMismatch.into());
+        }
+        let verified_creators: Vec<Pubkey> = creators
+            .iter()
+            .filter(|creator| creator.verified)
+            .map(|creator| creator.address)
+            .collect();
+        Ok(self.is_valid_certificate(asset_id, verified_collection, &verified_creators))
+    }
+
+    /// The vault a certificate is stored in: the certificate vault if it is valid,
+    /// otherwise the quarantine vault if one is set. Invalid certificates are rejected otherwise.
+    pub fn certificate_destination(&self, is_valid: bool) -> Result<Pubkey> {
+        if is_valid {
+            return Ok(self.certificate_vault);
+        }
+        if self.quarantine_vault == Pubkey::default() {
+            return Err(ErrorCode::InvalidCertificate.into());
+        }
+        msg!("Certificate does not match the state's filter, quarantining");
+        Ok(self.quarantine_vault)
+    }
+}
+
+/// Record `amount` of `certificate` stored by the state in the state's certificate ledger
+pub fn record_certificate(
+    state: &mut Account<State>,
+    certificate_record: &mut CertificateRecord,
+    certificate: Pubkey,
+    amount: u64,
+    quarantined: bool,
+    beneficiary: Option<Pubkey>,
+) -> Result<()> {
+    certificate_record.state = state.key();
+    certificate_record.certificate = certificate;
+    certificate_record.amount = amount;
+    certificate_record.timestamp = Clock::get()?.unix_timestamp;
+    certificate_record.total_spent = state.total_spent;
+    certificate_record.index = state.total_certificates;
+    certificate_record.quarantined = quarantined;
+    certificate_record.beneficiary = beneficiary.unwrap_or(state.certificate_beneficiary);
+
+    state.total_certificates += 1;
+
+    Ok(())
+}
+
+/// Create the next record of the state's certificate ledger at `certificate_record`,
+/// for instructions that store a variable number of certificates
+pub fn init_certificate_record<'info>(
+    state: &Account<'info, State>,
+    payer: &AccountInfo<'info>,
+    certificate_record: &'info AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<Account<'info, CertificateRecord>> {
+    let state_key = state.key();
+    let index_bytes = state.total_certificates.to_le_bytes();
+    let (expected_record, bump) = Pubkey::find_program_address(
+        &[CERTIFICATE_RECORD, state_key.as_ref(), index_bytes.as_ref()],
+        &crate::ID,
+    );
+    if certificate_record.key() != expected_record {
+        return Err(ErrorCode::IncorrectCertificateRecord.into());
+    }
+
+    let bump_bytes = &[bump];
+    let seeds = &[
+        CERTIFICATE_RECORD,
+        state_key.as_ref(),
+        index_bytes.as_ref(),
+        bump_bytes,
+    ][..];
+    let signer_seeds = &[seeds];
+    let rent = Rent::get()?.minimum_balance(CertificateRecord::SPACE);
+    let current_lamports = certificate_record.lamports();
+    if current_lamports == 0 {
+        let cpi_ctx = CpiContext::new(
+            system_program.clone(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: certificate_record.clone(),
+            },
+        )
+        .with_signer(signer_seeds);
+        system_program::create_account(cpi_ctx, rent, CertificateRecord::SPACE as u64, &crate::ID)?;
+    } else {
+        // the address was funded in advance, which create_account refuses,
+        // so top up its rent and allocate and assign it instead (as Anchor's `init` does)
+        let rent_shortfall = rent.saturating_sub(current_lamports);
+        if rent_shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: certificate_record.clone(),
+                    },
+                ),
+                rent_shortfall,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: certificate_record.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            CertificateRecord::SPACE as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: certificate_record.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            &crate::ID,
+        )?;
+    }
+
+    // the new account is zeroed, so has no discriminator to check yet
+    Account::try_from_unchecked(certificate_record)
+}
+
+/// Check the certificate vault is the state's program-owned vault if certificates are locked,
+/// which they stay once locked
+pub fn check_certificate_vault(state: &mut Account<State>, was_locked: bool) -> Result<()> {
+    if was_locked && !state.lock_certificates {
+        return Err(ErrorCode::CertificatesLocked.into());
+    }
+    if state.lock_certificates {
+        let (certificate_vault, bump) =
+            Pubkey::find_program_address(&[CERTIFICATE_VAULT, state.key().as_ref()], &crate::ID);
+        if state.certificate_vault != certificate_vault {
+            return Err(ErrorCode::IncorrectCertificateVault.into());
+        }
+        state.certificate_vault_bump = bump;
+    }
+    Ok(())
+}
+
+pub fn check_allowed_certificate_mints(allowed_certificate_mints: &[Pubkey]) -> Result<()> {
+    if allowed_certificate_mints.len() > MAX_ALLOWED_CERTIFICATE_MINTS {
+        return Err(ErrorCode::TooManyAllowedCertificateMints.into());
+    }
+    Ok(())
+}
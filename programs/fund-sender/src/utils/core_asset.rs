@@ -0,0 +1,72 @@
+use crate::utils::errors::ErrorCode;
+use anchor_lang::prelude::*;
+use mpl_core::instructions::TransferV1CpiBuilder;
+
+// the `Key` of an mpl-core asset account
+const ASSET_V1_KEY: u8 = 1;
+// the `UpdateAuthority` variant of an asset that belongs to a collection
+const UPDATE_AUTHORITY_COLLECTION: u8 = 2;
+
+/// The fields of a Metaplex Core asset needed to store it as a certificate
+pub struct CoreAsset {
+    pub owner: Pubkey,
+    // the collection the asset belongs to. Core assets can only be added to a collection
+    // by its authority, so this is equivalent to a verified collection
+    pub collection: Option<Pubkey>,
+}
+
+impl CoreAsset {
+    /// Read the start of a BaseAssetV1 account: key (u8), owner, update authority (enum of an optional Pubkey)
+    pub fn try_from_account(asset: &AccountInfo) -> Result<Self> {
+        let data = asset.try_borrow_data()?;
+        if data.len() < 34 || data[0] != ASSET_V1_KEY {
+            return Err(ErrorCode::InvalidCoreAsset.into());
+        }
+        let owner = Pubkey::try_from(&data[1..33]).map_err(|_| ErrorCode::InvalidCoreAsset)?;
+        let collection = if data[33] == UPDATE_AUTHORITY_COLLECTION {
+            let collection = data
+                .get(34..66)
+                .and_then(|key| Pubkey::try_from(key).ok())
+                .ok_or(ErrorCode::InvalidCoreAsset)?;
+            Some(collection)
+        } else {
+            None
+        };
+        Ok(Self { owner, collection })
+    }
+}
+
+pub struct AccountsCoreTransfer<'a> {
+    pub asset: AccountInfo<'a>,
+    pub collection: Option<AccountInfo<'a>>,
+    pub payer: AccountInfo<'a>,
+    // the state PDA owning the asset: the input account, or the certificate vault when releasing
+    pub owner: AccountInfo<'a>,
+    pub new_owner: AccountInfo<'a>,
+    pub system_program: AccountInfo<'a>,
+    pub core_program: AccountInfo<'a>,
+}
+
+/// Transfer a Core asset owned by a state PDA. `owner_seed` is the seed of the state PDA owning the asset
+pub fn transfer_core_asset<'a>(
+    state: &Pubkey,
+    accounts_core_transfer: &AccountsCoreTransfer<'a>,
+    owner_seed: &[u8],
+    owner_bump: u8,
+) -> Result<()> {
+    let state_bytes = state.to_bytes();
+    let bump_bytes = &[owner_bump];
+    let seeds = &[owner_seed, &state_bytes[..], bump_bytes][..];
+    let signer_seeds = &[seeds];
+
+    TransferV1CpiBuilder::new(&accounts_core_transfer.core_program)
+        .asset(&accounts_core_transfer.asset)
+        .collection(accounts_core_transfer.collection.as_ref())
+        .payer(&accounts_core_transfer.payer)
+        // the state PDA signs as the asset's owner
+        .authority(Some(&accounts_core_transfer.owner))
+        .new_owner(&accounts_core_transfer.new_owner)
+        .system_program(Some(&accounts_core_transfer.system_program))
+        .invoke_signed(signer_seeds)
+        .map_err(Into::into)
+}
@@ -22,4 +22,76 @@ pub enum ErrorCode {
 
     #[msg("No certificates found")]
     NoCertificatesFound,
+
+    #[msg("Invalid destination proportions")]
+    InvalidProportions,
+
+    #[msg("Too many destinations")]
+    TooManyDestinations,
+
+    #[msg("Incorrect token mint")]
+    IncorrectTokenMint,
+
+    #[msg("Incorrect swap program or pool")]
+    IncorrectSwapPool,
+
+    #[msg("Swap returned less than the minimum amount out")]
+    SlippageExceeded,
+
+    #[msg("Token accounts must be passed as pairs of the same mint")]
+    IncorrectTokenAccountPair,
+
+    #[msg("Certificate does not match the allowed collection, creator or mints")]
+    InvalidCertificate,
+
+    #[msg("Too many allowed certificate mints")]
+    TooManyAllowedCertificateMints,
+
+    #[msg("Incorrect certificate metadata account")]
+    IncorrectCertificateMetadata,
+
+    #[msg("Certificate creators do not match the creator hash")]
+    CreatorHashMismatch,
+
+    #[msg("Incorrect certificate record account")]
+    IncorrectCertificateRecord,
+
+    #[msg("Not enough proof accounts for the certificates")]
+    InsufficientProofAccounts,
+
+    #[msg("Too many certificates in one batch")]
+    TooManyCertificates,
+
+    #[msg("Invalid Metaplex Core asset")]
+    InvalidCoreAsset,
+
+    #[msg("Incorrect Metaplex Core collection account")]
+    IncorrectCoreCollection,
+
+    #[msg("Invalid certificate metadata")]
+    InvalidCertificateMetadata,
+
+    #[msg("Certificate metadata does not match the data hash")]
+    DataHashMismatch,
+
+    #[msg("Certificates are locked in the state's certificate vault")]
+    CertificatesLocked,
+
+    #[msg("Certificates are not locked in the state's certificate vault")]
+    CertificatesNotLocked,
+
+    #[msg("Incorrect certificate vault")]
+    IncorrectCertificateVault,
+
+    #[msg("Account is not a fund sender state in the legacy layout")]
+    InvalidLegacyState,
+
+    #[msg("The state's token and wrapped SOL cannot be stored as certificates")]
+    InvalidCertificateMint,
+
+    #[msg("Certificates have been stored or retired by the state")]
+    CertificatesStored,
+
+    #[msg("The old input account's balance of the state's token was not moved")]
+    TokensNotMigrated,
 }
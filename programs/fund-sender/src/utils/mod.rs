@@ -1,4 +1,6 @@
 pub(crate) mod bubblegum;
+pub(crate) mod certificate;
+pub(crate) mod core_asset;
 pub(crate) mod errors;
 pub(crate) mod seeds;
 pub(crate) mod spend;
@@ -1,2 +1,3 @@
-pub const STATE: &[u8] = b"state";
-pub const INPUT_ACCOUNT: &[u8] = b"input_account";
+pub use yield_controller_common::seeds::{
+    CERTIFICATE_RECORD, CERTIFICATE_VAULT, INPUT_ACCOUNT, STATE,
+};
@@ -1,7 +1,97 @@
+use crate::utils::errors::ErrorCode;
 use crate::utils::seeds::INPUT_ACCOUNT;
+use crate::utils::state::State;
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
-use anchor_spl::token::{self, Token, Transfer as SplTransfer};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::TokenInterface;
+use yield_controller_common::proportions::{is_valid_proportions, split_all_by_proportions};
+use yield_controller_common::token::{
+    close_token_account_signed, transfer_token_checked_signed, transfer_token_signed,
+    wrap_native_signed,
+};
+use yield_controller_common::transfer::{transfer_native_signed, transfer_native_signed_unchecked};
+
+pub use yield_controller_common::rent::spendable_balance;
+
+// the most destinations a state can split funds between
+pub const MAX_DESTINATIONS: usize = 4;
+
+pub fn check_proportions(proportions: &[u8]) -> Result<()> {
+    // check proportions to be sent to the different destinations sum to 100
+    if !is_valid_proportions(proportions) {
+        return Err(ErrorCode::InvalidProportions.into());
+    }
+    Ok(())
+}
+
+pub fn check_destinations(
+    destination_account: &Pubkey,
+    destination_accounts: &[Pubkey],
+    destination_proportions: &[u8],
+) -> Result<()> {
+    // no weighted destinations: everything goes to `destination_account`
+    if destination_accounts.is_empty() && destination_proportions.is_empty() {
+        return Ok(());
+    }
+    if destination_accounts.len() != destination_proportions.len() {
+        return Err(ErrorCode::InvalidProportions.into());
+    }
+    if destination_accounts.len() > MAX_DESTINATIONS {
+        return Err(ErrorCode::TooManyDestinations.into());
+    }
+    // the primary destination is always the first of the weighted destinations
+    if destination_accounts[0] != *destination_account {
+        return Err(ErrorCode::IncorrectDestinationAccount.into());
+    }
+    check_proportions(destination_proportions)
+}
+
+/// Send `amount` from the input account to the state's destinations, split by their proportions.
+/// `destination_account` is the primary destination, any others are given in `other_destinations`.
+pub fn send_to_destinations<'a>(
+    state: &Account<'a, State>,
+    input_account: &AccountInfo<'a>,
+    destination_account: &AccountInfo<'a>,
+    other_destinations: &[AccountInfo<'a>],
+    amount: u64,
+) -> Result<()> {
+    if destination_account.key() != state.destination_account {
+        return Err(ErrorCode::IncorrectDestinationAccount.into());
+    }
+
+    if state.destination_accounts.is_empty() {
+        return transfer_native_cpi(
+            &state.key(),
+            input_account,
+            destination_account,
+            amount,
+            state.input_account_bump,
+        );
+    }
+
+    if other_destinations.len() + 1 != state.destination_accounts.len() {
+        return Err(ErrorCode::IncorrectDestinationAccount.into());
+    }
+
+    // the last destination receives any rounding remainder
+    let amounts_to_send = split_all_by_proportions(amount, &state.destination_proportions);
+    let destinations = std::iter::once(destination_account).chain(other_destinations.iter());
+    for (i, (destination, amount_to_send)) in destinations.zip(amounts_to_send).enumerate() {
+        if destination.key() != state.destination_accounts[i] {
+            return Err(ErrorCode::IncorrectDestinationAccount.into());
+        }
+
+        transfer_native_cpi(
+            &state.key(),
+            input_account,
+            destination,
+            amount_to_send,
+            state.input_account_bump,
+        )?;
+    }
+
+    Ok(())
+}
 
 pub fn transfer_native_cpi<'a>(
     state: &Pubkey,
@@ -9,22 +99,37 @@ pub fn transfer_native_cpi<'a>(
     dest: &AccountInfo<'a>,
     amount: u64,
     source_bump: u8,
-    system_program: &Program<'a, System>,
 ) -> Result<()> {
-    // transfer `amount` (in lamports) from `source` account to `dest` account
+    // transfer `amount` (in lamports) from `source` account to `dest` account,
+    // never taking the source below its rent-exempt minimum
     let state_bytes = state.to_bytes();
     let bump_bytes = &[source_bump];
     let seeds = &[INPUT_ACCOUNT, &state_bytes[..], bump_bytes][..];
-    let signer_seeds = &[seeds];
-    let cpi_ctx = CpiContext::new(
-        system_program.to_account_info(),
-        system_program::Transfer {
-            from: source.clone(),
-            to: dest.clone(),
-        },
+    transfer_native_signed(
+        source,
+        dest,
+        amount,
+        seeds,
+        ErrorCode::InsufficientFundsForTransaction,
     )
-    .with_signer(signer_seeds);
-    system_program::transfer(cpi_ctx, amount)
+}
+
+/// Move the whole balance of an input account, including its rent, e.g. when it is being abandoned.
+pub fn empty_input_account<'a>(
+    state: &Pubkey,
+    source: &AccountInfo<'a>,
+    dest: &AccountInfo<'a>,
+    source_bump: u8,
+) -> Result<()> {
+    let amount = source.lamports();
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let state_bytes = state.to_bytes();
+    let bump_bytes = &[source_bump];
+    let seeds = &[INPUT_ACCOUNT, &state_bytes[..], bump_bytes][..];
+    transfer_native_signed_unchecked(source, dest, amount, seeds)
 }
 
 pub struct AccountsTokenTransfer<'a> {
@@ -36,9 +141,6 @@ pub struct AccountsTokenTransfer<'a> {
 pub fn transfer_token<'a>(
     state: &Pubkey,
     accounts_token_transfer: &AccountsTokenTransfer<'a>,
-    // source: &AccountInfo<'a>,
-    // dest: &AccountInfo<'a>,
-    // authority: &AccountInfo<'a>,
     amount: u64,
     source_bump: u8,
     token_program: &Program<'a, Token>,
@@ -46,15 +148,80 @@ pub fn transfer_token<'a>(
     let state_bytes = state.to_bytes();
     let bump_bytes = &[source_bump];
     let seeds = &[INPUT_ACCOUNT, &state_bytes[..], bump_bytes][..];
-    let signer_seeds = &[seeds];
-    let cpi_ctx = CpiContext::new(
-        token_program.to_account_info(),
-        SplTransfer {
-            from: accounts_token_transfer.source.clone(),
-            to: accounts_token_transfer.dest.clone(),
-            authority: accounts_token_transfer.authority.clone(),
-        },
+    transfer_token_signed(
+        &token_program.to_account_info(),
+        accounts_token_transfer.source.clone(),
+        accounts_token_transfer.dest.clone(),
+        accounts_token_transfer.authority.clone(),
+        amount,
+        seeds,
+    )
+}
+
+pub fn close_token_account<'a>(
+    state: &Pubkey,
+    accounts_token_transfer: &AccountsTokenTransfer<'a>,
+    source_bump: u8,
+    token_program: &Program<'a, Token>,
+) -> Result<()> {
+    let state_bytes = state.to_bytes();
+    let bump_bytes = &[source_bump];
+    let seeds = &[INPUT_ACCOUNT, &state_bytes[..], bump_bytes][..];
+    close_token_account_signed(
+        &token_program.to_account_info(),
+        accounts_token_transfer.source.clone(),
+        accounts_token_transfer.dest.clone(),
+        accounts_token_transfer.authority.clone(),
+        seeds,
+    )
+}
+
+/// Transfer tokens of either the legacy token program or Token-2022, which requires
+/// the mint and its decimals. `source_seed` is the seed of the state PDA owning the source
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_token_checked<'a>(
+    state: &Pubkey,
+    accounts_token_transfer: &AccountsTokenTransfer<'a>,
+    mint: AccountInfo<'a>,
+    amount: u64,
+    decimals: u8,
+    source_seed: &[u8],
+    source_bump: u8,
+    token_program: &Interface<'a, TokenInterface>,
+) -> Result<()> {
+    let state_bytes = state.to_bytes();
+    let bump_bytes = &[source_bump];
+    let seeds = &[source_seed, &state_bytes[..], bump_bytes][..];
+    transfer_token_checked_signed(
+        &token_program.to_account_info(),
+        accounts_token_transfer.source.clone(),
+        mint,
+        accounts_token_transfer.dest.clone(),
+        accounts_token_transfer.authority.clone(),
+        amount,
+        decimals,
+        seeds,
+    )
+}
+
+pub fn wrap_native<'a>(
+    state: &Pubkey,
+    input_account: &AccountInfo<'a>,
+    wrapped_sol_account: &AccountInfo<'a>,
+    amount: u64,
+    input_account_bump: u8,
+    token_program: &Program<'a, Token>,
+) -> Result<()> {
+    // move `amount` lamports into the wrapped SOL account and sync its token balance
+    let state_bytes = state.to_bytes();
+    let bump_bytes = &[input_account_bump];
+    let seeds = &[INPUT_ACCOUNT, &state_bytes[..], bump_bytes][..];
+    wrap_native_signed(
+        &token_program.to_account_info(),
+        input_account,
+        wrapped_sol_account,
+        amount,
+        seeds,
+        ErrorCode::InsufficientFundsForTransaction,
     )
-    .with_signer(signer_seeds);
-    token::transfer(cpi_ctx, amount)
 }
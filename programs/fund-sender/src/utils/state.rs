@@ -1,10 +1,18 @@
 use crate::external_programs::mpl_bubblegum::MplBubblegum;
+use crate::external_programs::mpl_core::{MplCore, MPL_CORE_ID};
 use crate::external_programs::spl_account_compression::SplAccountCompression;
+use crate::utils::bubblegum::AccountsCNFTTransfer;
 use crate::utils::errors::ErrorCode;
-use crate::utils::seeds::{INPUT_ACCOUNT, STATE};
+use crate::utils::seeds::{CERTIFICATE_RECORD, CERTIFICATE_VAULT, INPUT_ACCOUNT, STATE};
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{
+    Mint as InterfaceMint, TokenAccount as InterfaceTokenAccount, TokenInterface,
+};
 /* This struct will be used for both registering and updating the state account */
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct GenericStateInput {
@@ -18,6 +26,34 @@ pub struct GenericStateInput {
     pub certificate_vault: Pubkey,
     // minimum threshold of yield in input_account before it is allowed to send funds (in lamports)
     pub spend_threshold: u64,
+    // optional weighted destinations to split funds between, the first being `destination_account`
+    // (leave empty to send everything to `destination_account`)
+    pub destination_accounts: Vec<Pubkey>,
+    // proportions of funds to send to each of the `destination_accounts` (sum to 100)
+    pub destination_proportions: Vec<u8>,
+    // an SPL token forwarded from the input_account's ATA to the destination's ATA (default key to disable)
+    pub token_mint: Pubkey,
+    // minimum threshold of tokens in the input_account's ATA before they are sent (in token minor units)
+    pub token_spend_threshold: u64,
+    // the AMM program and pool used to swap SOL to `token_mint` before sending (default keys to disable)
+    pub swap_program: Pubkey,
+    pub swap_pool: Pubkey,
+    // certificates must be in this verified Metaplex collection (SPL certificates only),
+    // have this verified creator, or be one of `allowed_certificate_mints`
+    // (leave all empty to accept any certificate)
+    pub certificate_collection: Pubkey,
+    pub certificate_creator: Pubkey,
+    // allowed certificate mints, or asset ids for compressed certificates
+    pub allowed_certificate_mints: Vec<Pubkey>,
+    // where certificates not matching the filter are stored (default key to reject them instead)
+    pub quarantine_vault: Pubkey,
+    // store certificates in a vault owned by this program, derived from the state, which they can
+    // only leave through the `release_*certificate` instructions. `certificate_vault` must then be
+    // that vault. Once set, it cannot be unset.
+    pub lock_certificates: bool,
+    // who stored certificates are recorded against by default, e.g. a Sunrise staker or the gSOL mint
+    // (default key for none)
+    pub certificate_beneficiary: Pubkey,
 }
 
 #[account]
@@ -31,13 +67,120 @@ pub struct State {
     pub spend_threshold: u64,
     pub total_spent: u64,
     pub input_account_bump: u8,
+    pub destination_accounts: Vec<Pubkey>,
+    pub destination_proportions: Vec<u8>, // sum to 100
+    pub token_mint: Pubkey,
+    pub token_spend_threshold: u64,
+    pub total_tokens_spent: u64,
+    pub swap_program: Pubkey,
+    pub swap_pool: Pubkey,
+    pub certificate_collection: Pubkey,
+    pub certificate_creator: Pubkey,
+    pub allowed_certificate_mints: Vec<Pubkey>,
+    pub quarantine_vault: Pubkey,
+    pub total_certificates: u64,
+    pub lock_certificates: bool,
+    pub certificate_vault_bump: u8,
+    pub certificate_beneficiary: Pubkey,
 }
 
 impl State {
-    pub fn space(len_destination_seed: u8) -> usize {
+    pub fn space(len_destination_seed: u8, num_destinations: u8, num_allowed_mints: u8) -> usize {
         // find space needed for state account for current config
+        32 + 32
+            + 4
+            + (len_destination_seed as usize)
+            + 32
+            + 32
+            + 8
+            + 8
+            + 1
+            + 4
+            + (32 * num_destinations as usize)
+            + 4
+            + (num_destinations as usize)
+            + 32
+            + 8
+            + 8
+            + 32
+            + 32
+            + 32
+            + 32
+            + 4
+            + (32 * num_allowed_mints as usize)
+            + 32
+            + 8
+            + 1
+            + 1
+            + 32
+            + 8 /* Discriminator */
+    }
+}
+
+/// The layout of states registered before weighted destinations, token forwarding and certificate
+/// filtering were added. It shares the `State` discriminator, so it is read manually during migration.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyState {
+    pub sunrise_state: Pubkey,
+    pub update_authority: Pubkey,
+    pub destination_name: String,
+    pub destination_account: Pubkey,
+    pub certificate_vault: Pubkey,
+    pub spend_threshold: u64,
+    pub total_spent: u64,
+    pub input_account_bump: u8,
+}
+
+impl LegacyState {
+    pub fn space(len_destination_seed: u8) -> usize {
         32 + 32 + 4 + (len_destination_seed as usize) + 32 + 32 + 8 + 8 + 1 + 8 /* Discriminator */
     }
+
+    pub fn try_from_account_info(account: &AccountInfo) -> Result<Self> {
+        let data = account.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != State::DISCRIMINATOR {
+            return Err(ErrorCode::InvalidLegacyState.into());
+        }
+        let legacy_state =
+            LegacyState::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::InvalidLegacyState)?;
+        // a current state starts with the same fields, but is always longer
+        if data.len() != Self::space(legacy_state.destination_name.len() as u8) {
+            return Err(ErrorCode::InvalidLegacyState.into());
+        }
+        Ok(legacy_state)
+    }
+}
+
+/// A record of certificates stored by a state, one per store instruction,
+/// linking them to the total funds sent by the state at that point
+#[account]
+pub struct CertificateRecord {
+    pub state: Pubkey,
+    // the certificate mint, or asset id for compressed certificates
+    pub certificate: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    // the state's total_spent when the certificate was stored (in lamports)
+    pub total_spent: u64,
+    pub index: u64,
+    // whether the certificate did not match the state's filter and went to the quarantine vault
+    pub quarantined: bool,
+    // who the certificate is attributed to (default key for none),
+    // updated when the certificate is released to a beneficiary
+    pub beneficiary: Pubkey,
+}
+
+impl CertificateRecord {
+    pub const SPACE: usize = 32 + 32 + 8 + 8 + 8 + 8 + 1 + 32 + 8 /* Discriminator */;
+}
+
+#[event]
+pub struct CertificateReleased {
+    pub state: Pubkey,
+    pub certificate: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
 }
 
 #[derive(Accounts)]
@@ -48,7 +191,11 @@ pub struct RegisterState<'info> {
     pub payer: Signer<'info>,
     #[account(
     init,
-    space = State::space(state_in.destination_name.len() as u8),
+    space = State::space(
+        state_in.destination_name.len() as u8,
+        state_in.destination_accounts.len() as u8,
+        state_in.allowed_certificate_mints.len() as u8,
+    ),
     seeds = [STATE, &state_in.destination_name.as_bytes(), sunrise_state.key().as_ref()],
     payer = payer,
     bump,
@@ -72,16 +219,34 @@ pub struct UpdateState<'info> {
     #[account(
     mut,
     constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+    realloc = State::space(
+        state.destination_name.len() as u8,
+        state_in.destination_accounts.len() as u8,
+        state_in.allowed_certificate_mints.len() as u8,
+    ),
+    realloc::payer = payer,
+    realloc::zero = false,
     )]
     pub state: Account<'info, State>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, owner = crate::ID)]
+    /// CHECK: The legacy layout is deserialized (and the authority checked) in the instruction
+    pub state: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct SendFund<'info> {
     // to allocate correct yield proportion to various input_accounts
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(mut)]
     pub state: Account<'info, State>,
     #[account(
         mut,
@@ -94,6 +259,168 @@ pub struct SendFund<'info> {
     /// CHECK: Must be correct destination account (check is done in instruction)
     pub destination_account: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
+    // any further weighted destinations are passed as remaining accounts, in order
+}
+
+#[derive(Accounts)]
+pub struct SendTokenFund<'info> {
+    // to forward the state's SPL token balance to the destination
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = state.token_mint == token_mint.key() @ ErrorCode::IncorrectTokenMint,
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        seeds = [INPUT_ACCOUNT, state.key().as_ref()],
+        bump = state.input_account_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
+    pub input_account: UncheckedAccount<'info>,
+    pub token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = input_account,
+    )]
+    pub input_token_account: Account<'info, TokenAccount>,
+    #[account(
+        constraint = destination_account.key() == state.destination_account @ ErrorCode::IncorrectDestinationAccount,
+    )]
+    /// CHECK: must match the one stated in the state, but can be any account
+    pub destination_account: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = destination_account,
+    )]
+    pub destination_token_account: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// An instruction to swap SOL in the input account to the state's token, delivering it to the destination
+#[derive(Accounts)]
+pub struct SwapAndSend<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+        constraint = state.token_mint == token_mint.key() @ ErrorCode::IncorrectTokenMint,
+        constraint = state.swap_program == swap_program.key() @ ErrorCode::IncorrectSwapPool,
+        constraint = state.swap_pool == swap_pool.key() @ ErrorCode::IncorrectSwapPool,
+    )]
+    pub state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [INPUT_ACCOUNT, state.key().as_ref()],
+        bump = state.input_account_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
+    pub input_account: UncheckedAccount<'info>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = native_mint,
+        associated_token::authority = input_account,
+    )]
+    // the input account's wrapped SOL account, used as the swap input
+    pub wrapped_sol_account: Box<Account<'info, TokenAccount>>,
+    pub token_mint: Box<Account<'info, Mint>>,
+    #[account(
+        constraint = destination_account.key() == state.destination_account @ ErrorCode::IncorrectDestinationAccount,
+    )]
+    /// CHECK: must match the one stated in the state, but can be any account
+    pub destination_account: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = destination_account,
+    )]
+    // receives the swap output directly
+    pub destination_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(executable)]
+    /// CHECK: must match the one stated in the state
+    pub swap_program: UncheckedAccount<'info>,
+    #[account(owner = swap_program.key())]
+    /// CHECK: must match the one stated in the state
+    pub swap_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the swap program
+    pub swap_pool_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_source: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_destination: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: validated by the swap program
+    pub swap_pool_fee_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// An instruction to rename a destination, moving its state to the PDA derived from the new name
+#[derive(Accounts)]
+#[instruction(new_destination_name: String)]
+pub struct MigrateDestination<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        constraint = old_state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    pub old_state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [INPUT_ACCOUNT, old_state.key().as_ref()],
+        bump = old_state.input_account_bump,
+    )]
+    /// CHECK: Must be correctly derived from the old state
+    pub old_input_account: UncheckedAccount<'info>,
+    #[account(
+        constraint = old_token_mint_account.key()
+            == get_associated_token_address(&old_input_account.key(), &old_state.token_mint)
+            @ ErrorCode::IncorrectTokenAccountOwner,
+    )]
+    /// CHECK: The old input account's associated token account for the state's token, which may not exist.
+    /// Its balance must be moved with the remaining accounts.
+    pub old_token_mint_account: UncheckedAccount<'info>,
+    #[account(
+        init,
+        space = State::space(
+            new_destination_name.len() as u8,
+            old_state.destination_accounts.len() as u8,
+            old_state.allowed_certificate_mints.len() as u8,
+        ),
+        seeds = [STATE, new_destination_name.as_bytes(), old_state.sunrise_state.as_ref()],
+        payer = payer,
+        bump,
+    )]
+    pub new_state: Box<Account<'info, State>>,
+    #[account(
+        mut,
+        seeds = [INPUT_ACCOUNT, new_state.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Must be correctly derived from the new state
+    pub new_input_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    // token balances are moved by passing pairs of (old input token account, new input token account)
+    // as remaining accounts; the old token accounts are closed
 }
 
 /// If someone sent funds to the state account, this instruction will send it to the input account
@@ -122,6 +449,7 @@ pub struct StoreCertificates<'info> {
     // to send the received retired climate token to a hold account
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(mut)]
     pub state: Account<'info, State>,
     #[account(
         seeds = [INPUT_ACCOUNT, state.key().as_ref()],
@@ -129,38 +457,149 @@ pub struct StoreCertificates<'info> {
     )]
     /// CHECK: Must be correctly derived from the state
     pub input_account: UncheckedAccount<'info>,
-    pub certificate_mint: Account<'info, Mint>,
+    // a legacy SPL token or Token-2022 mint, selected by the mint's owner.
+    // The state's token and wrapped SOL are funds awaiting sending, not certificates
+    #[account(
+        mint::token_program = token_program,
+        constraint = certificate_mint.key() != state.token_mint @ ErrorCode::InvalidCertificateMint,
+        constraint = certificate_mint.key() != native_mint::ID @ ErrorCode::InvalidCertificateMint,
+    )]
+    pub certificate_mint: Box<InterfaceAccount<'info, InterfaceMint>>,
     #[account(
         mut,
         token::mint = certificate_mint,
+        token::token_program = token_program,
         constraint = input_token_account.owner.key() == input_account.key() @ ErrorCode::IncorrectTokenAccountOwner,
     )]
     ///  A token account owned by the input_account
-    pub input_token_account: Account<'info, TokenAccount>,
+    pub input_token_account: Box<InterfaceAccount<'info, InterfaceTokenAccount>>,
+    /// CHECK: must be the state's certificate vault, or its quarantine vault
+    /// for certificates not matching the state's filter (checked in the instruction)
+    pub certificate_vault: UncheckedAccount<'info>,
     #[account(
-        constraint = certificate_vault.key() == state.certificate_vault.key() @ ErrorCode::IncorrectHoldAccount,
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
     )]
-    /// CHECK: must match the one stated in the state, but can be any account
-    pub certificate_vault: UncheckedAccount<'info>,
+    /// CHECK: The beneficiary the certificates are recorded against,
+    /// if not the state's certificate beneficiary. Only the update authority can name one
+    pub beneficiary: Option<UncheckedAccount<'info>>,
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = certificate_mint,
         associated_token::authority = certificate_vault,
+        associated_token::token_program = token_program,
     )]
     // the ATA of this particular mint of the account where we store all the certificates
-    pub certificate_vault_ata: Account<'info, TokenAccount>,
+    pub certificate_vault_ata: Box<InterfaceAccount<'info, InterfaceTokenAccount>>,
+    // the Metaplex metadata of the certificate mint, required to check its collection or creator
+    pub certificate_metadata: Option<Account<'info, MetadataAccount>>,
+    #[account(
+        init,
+        payer = payer,
+        space = CertificateRecord::SPACE,
+        seeds = [CERTIFICATE_RECORD, state.key().as_ref(), state.total_certificates.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub certificate_record: Account<'info, CertificateRecord>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
+/// An instruction to release certificate tokens from a state's program-owned certificate vault
+#[derive(Accounts)]
+pub struct ReleaseCertificate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+        constraint = state.lock_certificates @ ErrorCode::CertificatesNotLocked,
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        seeds = [CERTIFICATE_VAULT, state.key().as_ref()],
+        bump = state.certificate_vault_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
+    pub certificate_vault: UncheckedAccount<'info>,
+    #[account(mint::token_program = token_program)]
+    pub certificate_mint: Box<InterfaceAccount<'info, InterfaceMint>>,
+    #[account(
+        mut,
+        associated_token::mint = certificate_mint,
+        associated_token::authority = certificate_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub certificate_vault_ata: Box<InterfaceAccount<'info, InterfaceTokenAccount>>,
+    /// CHECK: Any account can receive released certificates
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = certificate_record.state == state.key() @ ErrorCode::IncorrectCertificateRecord,
+        constraint = certificate_record.certificate == certificate_mint.key() @ ErrorCode::IncorrectCertificateRecord,
+    )]
+    /// The ledger record of the released certificate, attributed to the beneficiary
+    pub certificate_record: Option<Account<'info, CertificateRecord>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = certificate_mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_ata: Box<InterfaceAccount<'info, InterfaceTokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+/// An instruction to pass a retirement certificate Metaplex Core asset to the certificate vault
+#[derive(Accounts)]
+pub struct StoreCoreCertificate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub state: Account<'info, State>,
+    #[account(
+        seeds = [INPUT_ACCOUNT, state.key().as_ref()],
+        bump = state.input_account_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
+    pub input_account: UncheckedAccount<'info>,
+    #[account(mut, owner = MPL_CORE_ID)]
+    /// CHECK: An mpl-core asset, deserialized in the instruction
+    pub certificate_asset: UncheckedAccount<'info>,
+    #[account(mut, owner = MPL_CORE_ID)]
+    /// CHECK: The asset's collection, required if it has one (checked in the instruction)
+    pub certificate_collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: must be the state's certificate vault, or its quarantine vault
+    /// for certificates not matching the state's filter (checked in the instruction)
+    pub certificate_vault: UncheckedAccount<'info>,
+    #[account(
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    /// CHECK: The beneficiary the certificates are recorded against,
+    /// if not the state's certificate beneficiary. Only the update authority can name one
+    pub beneficiary: Option<UncheckedAccount<'info>>,
+    #[account(
+        init,
+        payer = payer,
+        space = CertificateRecord::SPACE,
+        seeds = [CERTIFICATE_RECORD, state.key().as_ref(), state.total_certificates.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub certificate_record: Account<'info, CertificateRecord>,
+    pub system_program: Program<'info, System>,
+    pub core_program: Program<'info, MplCore>,
+}
+
 /// An instruction to pass a retirement certificate *Compressed* NFT to the certificate vault
 /// if the CNFT uses the Metaplex Bubblegum program
 #[derive(Accounts)]
 pub struct StoreCNFTCertificates<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(mut)]
     pub state: Account<'info, State>,
 
     #[account(
@@ -170,11 +609,207 @@ pub struct StoreCNFTCertificates<'info> {
     /// CHECK: Must be correctly derived from the state
     pub input_account: UncheckedAccount<'info>,
 
+    /// CHECK: The leaf's delegate, if it is not the input account.
+    /// The bubblegum program checks it against the leaf - we don't need to do so here
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be the state's certificate vault, or its quarantine vault
+    /// for certificates not matching the state's filter (checked in the instruction)
+    pub certificate_vault: UncheckedAccount<'info>,
+    #[account(
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    /// CHECK: The beneficiary the certificates are recorded against,
+    /// if not the state's certificate beneficiary. Only the update authority can name one
+    pub beneficiary: Option<UncheckedAccount<'info>>,
+
     #[account(
-        constraint = certificate_vault.key() == state.certificate_vault.key() @ ErrorCode::IncorrectHoldAccount,
+        init,
+        payer = payer,
+        space = CertificateRecord::SPACE,
+        seeds = [CERTIFICATE_RECORD, state.key().as_ref(), state.total_certificates.to_le_bytes().as_ref()],
+        bump,
     )]
-    /// CHECK: must match the one stated in the state, but can be any account
+    pub certificate_record: Account<'info, CertificateRecord>,
+
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = bubblegum_program.key()
+    )]
+    /// CHECK: This account is neither written to nor read from.
+    /// The bubblegum program checks its type - we don't need to do so here
+    pub tree_authority: UncheckedAccount<'info>, //Account<'info, TreeConfig>,
+
+    #[account(mut)]
+    /// CHECK: This account is modified in the downstream program
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// CHECK: This program swallows logs when calling the account compression program
+    /// via CPI, to workaround the CPI size limit on Solana.
+    /// The bubblegum program checks its type - we don't need to do so here
+    /// While SplAccountCompression is using an older version of Anchor, we cannot get its ID here
+    pub log_wrapper: UncheckedAccount<'info>, //Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+}
+
+impl<'info> StoreCNFTCertificates<'info> {
+    pub fn accounts_cnft_transfer(&self) -> AccountsCNFTTransfer<'info> {
+        AccountsCNFTTransfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: self.input_account.to_account_info(),
+            leaf_delegate: self
+                .leaf_delegate
+                .as_ref()
+                .unwrap_or(&self.input_account)
+                .to_account_info(),
+            new_leaf_owner: self.certificate_vault.to_account_info(),
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            bubblegum_program: self.bubblegum_program.to_account_info(),
+        }
+    }
+}
+
+/// An instruction to pass several retirement certificate *Compressed* NFTs from the same tree
+/// to the certificate vault. The remaining accounts are a certificate record per certificate,
+/// followed by the merkle proofs of each certificate in order.
+#[derive(Accounts)]
+pub struct StoreCNFTCertificateBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub state: Account<'info, State>,
+
+    #[account(
+        seeds = [INPUT_ACCOUNT, state.key().as_ref()],
+        bump = state.input_account_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
+    pub input_account: UncheckedAccount<'info>,
+
+    /// CHECK: The leaf's delegate, if it is not the input account.
+    /// The bubblegum program checks it against the leaf - we don't need to do so here
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: must be the state's certificate vault, or its quarantine vault
+    /// for certificates not matching the state's filter (checked in the instruction)
+    pub certificate_vault: UncheckedAccount<'info>,
+    #[account(
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+    )]
+    /// CHECK: The beneficiary the certificates are recorded against,
+    /// if not the state's certificate beneficiary. Only the update authority can name one
+    pub beneficiary: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = bubblegum_program.key()
+    )]
+    /// CHECK: This account is neither written to nor read from.
+    /// The bubblegum program checks its type - we don't need to do so here
+    pub tree_authority: UncheckedAccount<'info>, //Account<'info, TreeConfig>,
+
+    #[account(mut)]
+    /// CHECK: This account is modified in the downstream program
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// CHECK: This program swallows logs when calling the account compression program
+    /// via CPI, to workaround the CPI size limit on Solana.
+    /// The bubblegum program checks its type - we don't need to do so here
+    /// While SplAccountCompression is using an older version of Anchor, we cannot get its ID here
+    pub log_wrapper: UncheckedAccount<'info>, //Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, MplBubblegum>,
+}
+
+impl<'info> StoreCNFTCertificateBatch<'info> {
+    pub fn accounts_cnft_transfer(&self) -> AccountsCNFTTransfer<'info> {
+        AccountsCNFTTransfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: self.input_account.to_account_info(),
+            leaf_delegate: self
+                .leaf_delegate
+                .as_ref()
+                .unwrap_or(&self.input_account)
+                .to_account_info(),
+            new_leaf_owner: self.certificate_vault.to_account_info(),
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            bubblegum_program: self.bubblegum_program.to_account_info(),
+        }
+    }
+}
+
+/// An instruction to release a Metaplex Core certificate from a state's program-owned certificate vault
+#[derive(Accounts)]
+pub struct ReleaseCoreCertificate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+        constraint = state.lock_certificates @ ErrorCode::CertificatesNotLocked,
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        seeds = [CERTIFICATE_VAULT, state.key().as_ref()],
+        bump = state.certificate_vault_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
+    pub certificate_vault: UncheckedAccount<'info>,
+    #[account(mut, owner = MPL_CORE_ID)]
+    /// CHECK: An mpl-core asset, deserialized in the instruction
+    pub certificate_asset: UncheckedAccount<'info>,
+    #[account(mut, owner = MPL_CORE_ID)]
+    /// CHECK: The asset's collection, required if it has one (checked in the instruction)
+    pub certificate_collection: Option<UncheckedAccount<'info>>,
+    /// CHECK: Any account can receive released certificates
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = certificate_record.state == state.key() @ ErrorCode::IncorrectCertificateRecord,
+        constraint = certificate_record.certificate == certificate_asset.key() @ ErrorCode::IncorrectCertificateRecord,
+    )]
+    /// The ledger record of the released certificate, attributed to the beneficiary
+    pub certificate_record: Option<Account<'info, CertificateRecord>>,
+    pub system_program: Program<'info, System>,
+    pub core_program: Program<'info, MplCore>,
+}
+
+/// An instruction to release a retirement certificate *Compressed* NFT
+/// from a state's program-owned certificate vault
+#[derive(Accounts)]
+pub struct ReleaseCNFTCertificate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        constraint = state.update_authority == payer.key() @ ErrorCode::Unauthorized,
+        constraint = state.lock_certificates @ ErrorCode::CertificatesNotLocked,
+    )]
+    pub state: Account<'info, State>,
+    #[account(
+        seeds = [CERTIFICATE_VAULT, state.key().as_ref()],
+        bump = state.certificate_vault_bump,
+    )]
+    /// CHECK: Must be correctly derived from the state
     pub certificate_vault: UncheckedAccount<'info>,
+    /// CHECK: Any account can receive released certificates
+    pub beneficiary: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = certificate_record.state == state.key() @ ErrorCode::IncorrectCertificateRecord,
+    )]
+    /// The ledger record of the released certificate, attributed to the beneficiary.
+    /// Its certificate is checked against the asset id in the instruction
+    pub certificate_record: Option<Account<'info, CertificateRecord>>,
 
     #[account(
         seeds = [merkle_tree.key().as_ref()],
@@ -198,3 +833,19 @@ pub struct StoreCNFTCertificates<'info> {
     pub compression_program: Program<'info, SplAccountCompression>,
     pub bubblegum_program: Program<'info, MplBubblegum>,
 }
+
+impl<'info> ReleaseCNFTCertificate<'info> {
+    pub fn accounts_cnft_transfer(&self) -> AccountsCNFTTransfer<'info> {
+        AccountsCNFTTransfer {
+            tree_authority: self.tree_authority.to_account_info(),
+            leaf_owner: self.certificate_vault.to_account_info(),
+            leaf_delegate: self.certificate_vault.to_account_info(),
+            new_leaf_owner: self.beneficiary.to_account_info(),
+            merkle_tree: self.merkle_tree.to_account_info(),
+            log_wrapper: self.log_wrapper.to_account_info(),
+            compression_program: self.compression_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            bubblegum_program: self.bubblegum_program.to_account_info(),
+        }
+    }
+}
@@ -5,6 +5,9 @@ use crate::utils::state::*;
 use anchor_lang::prelude::*;
 mod utils;
 
+// account and argument types, for off-chain clients
+pub use crate::utils::state::{GenericStateInput, State};
+
 declare_id!("syriqUnUPcFQjRSaxdFo2wPnXXPjbRsLmhiWUVoGdTo");
 
 #[program]
@@ -62,18 +65,18 @@ pub mod yield_router {
             return Err(ErrorCode::IncorrectOutputYieldAccount.into());
         }
 
+        // compute the amount to be sent to each output_yield_account based on the specified proportions,
+        // rounding each down and leaving any remainder in the input yield account
+        let amounts_to_send = split_by_proportions(amount, &state.spend_proportions);
+
         // loop through all output yield accounts
-        for i in 0..ctx.remaining_accounts.len() {
+        for (i, amount_to_send) in amounts_to_send.into_iter().enumerate() {
             let output_yield_account = &ctx.remaining_accounts[i];
-            let proportion = state.spend_proportions[i];
 
             if output_yield_account.key() != state.output_yield_accounts[i] {
                 return Err(ErrorCode::IncorrectOutputYieldAccount.into());
             }
 
-            // compute the amount to be send to this output_yield_account based on the specifed proportion
-            let amount_to_send = (amount as f64 * (proportion as f64 / 100.0)) as u64;
-
             // send appropriate fund to this output_yield_account
             transfer_native_cpi(
                 &state.key(),
@@ -81,7 +84,6 @@ pub mod yield_router {
                 output_yield_account,
                 amount_to_send,
                 state.input_yield_account_bump,
-                &ctx.accounts.system_program,
             )?;
         }
         // update total sol spent
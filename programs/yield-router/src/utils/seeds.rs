@@ -1,2 +1 @@
-pub const STATE: &[u8] = b"state";
-pub const INPUT_YIELD_ACCOUNT: &[u8] = b"input_yield_account";
+pub use yield_controller_common::seeds::{INPUT_YIELD_ACCOUNT, STATE};